@@ -2,5 +2,7 @@ extern crate wlroots;
 
 fn main() {
     wlroots::utils::init_logging(wlroots::utils::WLR_DEBUG, None);
-    wlroots::CompositorBuilder::new().build_auto(()).run()
+    wlroots::CompositorBuilder::new().build_auto(())
+                                     .expect("Could not open the Wayland socket")
+                                     .run()
 }
@@ -193,6 +193,7 @@ fn main() {
         CompositorBuilder::new().gles2(true)
                                 .input_manager(Box::new(InputManager))
                                 .output_manager(Box::new(OutputManager))
-                                .build_auto(State::new(xcursor_manager, layout, cursor));
+                                .build_auto(State::new(xcursor_manager, layout, cursor))
+                                .expect("Could not open the Wayland socket");
     compositor.run();
 }
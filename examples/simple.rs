@@ -29,7 +29,7 @@ impl KeyboardHandler for ExKeyboardHandler {
             wlr_log!(WLR_DEBUG,
                      "Got key event. Keys: {:?}. Modifiers: {}",
                      keys,
-                     keyboard.get_modifiers());
+                     keyboard.modifiers());
             for key in keys {
                 if key == KEY_Escape {
                     wlroots::terminate()
@@ -93,5 +93,6 @@ fn main() {
     CompositorBuilder::new().input_manager(Box::new(InputManager))
                             .output_manager(Box::new(OutputManager))
                             .build_auto(())
+                            .expect("Could not open the Wayland socket")
                             .run()
 }
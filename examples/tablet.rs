@@ -245,5 +245,6 @@ fn main() {
                             .input_manager(Box::new(InputManagerEx))
                             .output_manager(Box::new(OutputManagerEx))
                             .build_auto(State::new())
+                            .expect("Could not open the Wayland socket")
                             .run()
 }
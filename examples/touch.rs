@@ -147,7 +147,8 @@ fn main() {
     let mut compositor = CompositorBuilder::new().gles2(true)
                                                  .input_manager(Box::new(InputManager))
                                                  .output_manager(Box::new(OutputManager))
-                                                 .build_auto(State::new());
+                                                 .build_auto(State::new())
+                                                 .expect("Could not open the Wayland socket");
     {
         let gles2 = &mut compositor.renderer.as_mut().unwrap();
         let compositor_data: &mut State = (&mut compositor.data).downcast_mut().unwrap();
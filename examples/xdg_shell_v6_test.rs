@@ -9,7 +9,7 @@ use wlroots::{project_box, Area, Capability, CompositorBuilder, CompositorHandle
               Origin, OutputBuilder, OutputBuilderResult, OutputHandle, OutputHandler,
               OutputLayout, OutputLayoutHandle, OutputLayoutHandler, OutputManagerHandler,
               PointerHandle, PointerHandler, Renderer, Seat, SeatHandle, SeatHandler, Size,
-              XCursorManager, XdgV6ShellHandler, XdgV6ShellManagerHandler, XdgV6ShellState,
+              Surface, XCursorManager, XdgV6ShellHandler, XdgV6ShellManagerHandler, XdgV6ShellState,
               XdgV6ShellSurfaceHandle, SurfaceHandler, SurfaceHandle};
 use wlroots::key_events::KeyEvent;
 use wlroots::pointer_events::{AbsoluteMotionEvent, ButtonEvent, MotionEvent};
@@ -271,7 +271,8 @@ fn main() {
                                 .input_manager(Box::new(InputManager))
                                 .output_manager(Box::new(OutputManager))
                                 .xdg_shell_v6_manager(Box::new(XdgV6ShellManager))
-                                .build_auto(State::new(xcursor_manager, layout, cursor));
+                                .build_auto(State::new(xcursor_manager, layout, cursor))
+                                .expect("Could not open the Wayland socket");
 
     {
         let seat_handle =
@@ -289,32 +290,40 @@ fn main() {
 /// Render the shells in the current compositor state on the given output.
 fn render_shells(state: &mut State, renderer: &mut Renderer) {
     let shells = state.shells.clone();
-    for mut shell in shells {
-        dehandle!(
-            @shell = {shell};
-            @surface = {shell.surface()};
-            @layout = {&state.layout};
-            let (width, height) = surface.current_state().size();
-            let (render_width, render_height) =
-                (width * renderer.output.scale() as i32,
-                 height * renderer.output.scale() as i32);
-            let (lx, ly) = (0.0, 0.0);
-            let render_box = Area::new(Origin::new(lx as i32, ly as i32),
-                                       Size::new(render_width,
-                                                 render_height));
-            if layout.intersects(renderer.output, render_box) {
-                let transform = renderer.output.get_transform().invert();
-                let matrix = project_box(render_box,
-                                         transform,
-                                         0.0,
-                                         renderer.output
-                                         .transform_matrix());
-                if let Some(texture) = surface.texture().as_ref() {
-                    renderer.render_texture_with_matrix(texture, matrix);
+    for shell in shells {
+        handles!((shell, state.layout)).run(|shell, layout| {
+            // Walk the whole surface tree so subsurfaces and popups are drawn too.
+            shell.for_each_surface(|mut surface, sx, sy| {
+                let res = surface.run(|surface| render_surface(surface, layout, renderer, sx, sy));
+                if let Err(err) = res {
+                    wlr_log!(WLR_ERROR, "Could not render surface: {}", err)
                 }
-                surface.send_frame_done(current_time());
-            };
-            ()
-        );
+            });
+        }).expect("Could not upgrade shell");
+    }
+}
+
+/// Render a single surface at the given offset from the root of its shell.
+fn render_surface(surface: &mut Surface,
+                  layout: &mut OutputLayout,
+                  renderer: &mut Renderer,
+                  sx: i32,
+                  sy: i32) {
+    let (width, height) = surface.current_state().size();
+    let scale = renderer.output.scale();
+    let render_box = Area::new(Origin::new((sx as f32 * scale) as i32,
+                                           (sy as f32 * scale) as i32),
+                               Size::new(width, height) * scale);
+    if layout.intersects(renderer.output, render_box) {
+        let transform = renderer.output.get_transform().invert();
+        let matrix = project_box(render_box,
+                                 transform,
+                                 0.0,
+                                 renderer.output
+                                 .transform_matrix());
+        if let Some(texture) = surface.texture().as_ref() {
+            renderer.render_texture_with_matrix(texture, matrix);
+        }
+        surface.send_frame_done(current_time());
     }
 }
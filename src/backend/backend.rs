@@ -13,11 +13,13 @@
 //! On the multi backend multiple backends could be running at the same time.
 
 use libc;
-use wlroots_sys::{self, wlr_backend, wlr_backend_is_wl, wlr_backend_is_x11,
+use wlroots_sys::{self, wlr_backend, wlr_input_device_type, wlr_backend_is_wl, wlr_backend_is_x11,
                   wlr_backend_is_drm, wlr_backend_is_headless, wlr_backend_is_multi,
                   wlr_backend_is_libinput};
 
-use super::{WaylandBackend, X11Backend, DRMBackend, HeadlessBackend, MultiBackend, LibInputBackend};
+use {InputHandle, OutputHandle};
+use super::{WaylandBackend, X11Backend, DRMBackend, HeadlessBackend, MultiBackend, LibInputBackend,
+            Session};
 
 /// A custom function to set up the renderer.
 pub type UnsafeRenderSetupFunction = unsafe extern "C" fn(*mut wlroots_sys::wlr_egl,
@@ -26,6 +28,38 @@ pub type UnsafeRenderSetupFunction = unsafe extern "C" fn(*mut wlroots_sys::wlr_
                                                           *mut i32, i32)
                                                           -> *mut wlroots_sys::wlr_renderer;
 
+/// The backends that can be picked with `CompositorBuilder::backend`.
+///
+/// All of them are always compiled into wlroots, but not all of them can be
+/// used in every environment. `CompositorBuilder::build` falls back to `Auto`
+/// when the picked one can't be used.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum BackendKind {
+    /// Pick a backend based on the environment, see
+    /// `CompositorBuilder::build_auto`.
+    Auto,
+    /// No real outputs or inputs. This always works, which makes it useful
+    /// for testing.
+    Headless,
+    /// Nested in another Wayland compositor.
+    ///
+    /// Needs `WAYLAND_DISPLAY` or `CompositorBuilder::wayland_remote`.
+    Wayland,
+    /// Nested in an X server.
+    ///
+    /// Needs `DISPLAY` or `CompositorBuilder::x11_display`.
+    X11,
+    /// Running directly on a TTY, with libinput for input devices.
+    ///
+    /// Needs a session to be opened, e.g through logind.
+    Drm
+}
+
+impl Default for BackendKind {
+    fn default() -> Self {
+        BackendKind::Auto
+    }
+}
 
 #[derive(Debug, Hash, Eq, PartialEq)]
 pub enum Backend {
@@ -57,6 +91,42 @@ impl Backend {
         }
     }
 
+    /// Get the session the backend runs on.
+    ///
+    /// Only the DRM backend (and a multi backend containing it) has one,
+    /// nested backends return `None`.
+    pub fn session(&self) -> Option<Session> {
+        match *self {
+            Backend::DRM(ref backend) => Some(backend.session()),
+            Backend::Multi(ref backend) => backend.wlr_multi_get_session(),
+            _ => None
+        }
+    }
+
+    /// Add a virtual output, if this is the headless backend.
+    ///
+    /// See `HeadlessBackend::add_managed_output`, this returns `None` on
+    /// other backends.
+    pub fn add_headless_output(&mut self, width: u32, height: u32) -> Option<OutputHandle> {
+        match *self {
+            Backend::Headless(ref backend) => backend.add_managed_output(width, height),
+            _ => None
+        }
+    }
+
+    /// Add a virtual input device, if this is the headless backend.
+    ///
+    /// See `HeadlessBackend::add_managed_input_device`, this returns `None` on
+    /// other backends.
+    pub fn add_headless_input_device(&mut self,
+                                     input_type: wlr_input_device_type)
+                                     -> Option<InputHandle> {
+        match *self {
+            Backend::Headless(ref backend) => backend.add_managed_input_device(input_type),
+            _ => None
+        }
+    }
+
     pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_backend {
         match *self {
             Backend::Wayland(WaylandBackend { backend }) |
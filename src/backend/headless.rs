@@ -45,6 +45,27 @@ impl HeadlessBackend {
         }
     }
 
+    /// Like `add_output`, but only returns the output once it has been set up
+    /// by an `OutputManagerHandler`.
+    ///
+    /// Once the backend is started the output is announced right away, so it
+    /// goes through `OutputManagerHandler::output_added` before this returns.
+    /// Outputs added before that are announced when the backend starts, and
+    /// this returns `None` for them.
+    pub fn add_managed_output(&self,
+                              width: libc::c_uint,
+                              height: libc::c_uint)
+                              -> Option<OutputHandle> {
+        unsafe {
+            let output_ptr = wlr_headless_add_output(self.backend, width, height);
+            if output_ptr.is_null() || (*output_ptr).data.is_null() {
+                None
+            } else {
+                Some(OutputHandle::from_ptr(output_ptr))
+            }
+        }
+    }
+
     /// Creates a new input device.
     ///
     /// The caller is responsible for manually raising any event signals on the
@@ -60,6 +81,24 @@ impl HeadlessBackend {
         }
     }
 
+    /// Like `add_input_device`, but only returns the device once the
+    /// `InputManagerHandler` returned a handler for it.
+    ///
+    /// Like outputs, it goes through the `InputManagerHandler` right away if
+    /// the backend is started, otherwise this returns `None`.
+    pub fn add_managed_input_device(&self,
+                                    input_type: wlr_input_device_type)
+                                    -> Option<InputHandle> {
+        unsafe {
+            let device = wlr_headless_add_input_device(self.backend, input_type);
+            if device.is_null() || (*device).data.is_null() {
+                None
+            } else {
+                Some(InputDevice { device }.device())
+            }
+        }
+    }
+
     pub fn is_headless_input_device(&self, input_device: &InputDevice) -> bool {
         unsafe {
             wlr_input_device_is_headless(input_device.as_ptr())
@@ -1,5 +1,9 @@
-use wlroots_sys::{wlr_backend, wlr_backend_autocreate, wl_display, wlr_multi_backend_add,
-                  wlr_multi_backend_remove, wlr_multi_is_empty, wlr_multi_get_session};
+use std::ptr;
+
+use wlroots_sys::{wlr_backend, wlr_backend_autocreate, wlr_backend_destroy, wl_display,
+                  wlr_drm_backend_create, wlr_libinput_backend_create, wlr_multi_backend_add,
+                  wlr_multi_backend_create, wlr_multi_backend_remove, wlr_multi_is_empty,
+                  wlr_multi_get_session, wlr_session_find_gpus};
 
 use super::{Session, UnsafeRenderSetupFunction};
 
@@ -22,6 +26,49 @@ impl MultiBackend {
         MultiBackend { backend }
     }
 
+    /// Opens a session and creates a DRM backend on its first GPU, along with a
+    /// libinput backend for its input devices.
+    ///
+    /// Both sub-backends need the session, so this only works when running on
+    /// a TTY that the session can take over: through logind (with the
+    /// `systemd` or `elogind` feature of wlroots-sys), or directly as root.
+    /// The DRM backend also needs a GPU with a free display controller.
+    ///
+    /// Returns `None` if the session could not be opened (e.g because another
+    /// program controls the TTY), if no GPU was found, or if either
+    /// sub-backend could not be created.
+    pub unsafe fn drm(display: *mut wl_display,
+                      render_setup_func: Option<UnsafeRenderSetupFunction>)
+                      -> Option<Self> {
+        let session = Session::new(display)?;
+        let mut gpu_fd = -1;
+        if wlr_session_find_gpus(session.as_ptr(), 1, &mut gpu_fd) == 0 {
+            session.destroy();
+            return None
+        }
+        let backend = wlr_multi_backend_create(display);
+        let libinput = wlr_libinput_backend_create(display, session.as_ptr());
+        let drm = wlr_drm_backend_create(display,
+                                         session.as_ptr(),
+                                         gpu_fd,
+                                         ptr::null_mut(),
+                                         render_setup_func);
+        if backend.is_null() || libinput.is_null() || drm.is_null() {
+            wlr_log!(WLR_ERROR, "Could not create the DRM and libinput backends");
+            for &created in &[drm, libinput, backend] {
+                if !created.is_null() {
+                    wlr_backend_destroy(created)
+                }
+            }
+            session.destroy();
+            return None
+        }
+        let multi = MultiBackend { backend };
+        multi.add_backend(libinput);
+        multi.add_backend(drm);
+        Some(multi)
+    }
+
     /// Adds the given backend to the multi backend.
     ///
     /// # Safety
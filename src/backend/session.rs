@@ -7,6 +7,7 @@ use wlroots_sys::{wl_display, wlr_session, wlr_session_create, wlr_session_destr
                   wlr_session_change_vt, wl_listener, wl_signal, udev, udev_monitor, wlr_device,
                   dev_t};
 
+use errors::SessionError;
 use utils::safe_as_cstring;
 
 pub struct Device<'session> {
@@ -38,16 +39,25 @@ impl <'session> Session<'session> {
         Session { session, phantom: PhantomData }
     }
 
-	  /// Signal for when the session becomes active/inactive.
+    /// Signal for when the session becomes active/inactive.
     /// It's called when we swap virtual terminal.
+    ///
+    /// Use `CompositorBuilder::session_handler` to listen to it.
     pub fn session_signal(&self) -> wl_signal {
         unsafe { (*self.session).session_signal }
     }
 
-    pub fn active(&self) -> bool {
+    /// Determines if the session is active, i.e if the compositor's virtual
+    /// terminal is the current one.
+    pub fn is_active(&self) -> bool {
         unsafe { (*self.session).active }
     }
 
+    #[deprecated(note = "use `Session::is_active` instead")]
+    pub fn active(&self) -> bool {
+        self.is_active()
+    }
+
     pub fn vtnr(&self) -> c_uint {
         unsafe { (*self.session).vtnr }
     }
@@ -76,10 +86,16 @@ impl <'session> Session<'session> {
         }
     }
 
-    /// Changes the virtual terminal.
-    pub fn change_vt(&mut self, vt: c_uint) -> bool {
+    /// Changes the virtual terminal (e.g on Ctrl+Alt+F2).
+    ///
+    /// The session becomes inactive until the user switches back.
+    pub fn change_vt(&mut self, vt: c_uint) -> Result<(), SessionError> {
         unsafe {
-            wlr_session_change_vt(self.session, vt)
+            if wlr_session_change_vt(self.session, vt) {
+                Ok(())
+            } else {
+                Err(SessionError::ChangeVtFailed)
+            }
         }
     }
 
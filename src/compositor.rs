@@ -2,24 +2,64 @@
 //! See examples for documentation on how to use this struct.
 
 use libc;
-use std::{env, panic, ptr, any::Any, cell::{Cell, UnsafeCell}, ffi::CStr, rc::{Rc, Weak}};
+use std::{env, panic, ptr, any::Any, cell::{Cell, UnsafeCell}, ffi::{CStr, CString},
+          os::unix::io::RawFd, rc::{Rc, Weak}, time::Duration};
 
-use {UnsafeRenderSetupFunction, Backend, MultiBackend, WaylandBackend,
+use {UnsafeRenderSetupFunction, Backend, BackendKind, MultiBackend, WaylandBackend,
      DataDeviceManager, Surface, X11Backend, DRMBackend, HeadlessBackend,
-     SurfaceHandle, XWaylandManagerHandler, XWaylandServer, Session};
-use errors::{HandleErr, HandleResult};
+     Seat, SurfaceHandle, XWaylandManagerHandler, XWaylandServer, Session, OutputLayout,
+     OutputLayoutHandle};
+use errors::{HandleErr, HandleResult, SessionError, SocketError};
+use event_loop::{add_idle, EventMask, FdSourceHandle, TimerHandle};
+use handle::{self, Handle};
+use types::output::output_layouts;
 use types::surface::{InternalSurface, InternalSurfaceState};
+#[cfg(feature = "foreign-toplevel")]
+use extensions::foreign_toplevel::ForeignToplevelManager;
+use extensions::idle::Idle;
+#[cfg(feature = "presentation-time")]
+use extensions::presentation::Presentation;
+#[cfg(feature = "relative-pointer")]
+use extensions::relative_pointer::RelativePointerManager;
 use extensions::server_decoration::ServerDecorationManager;
-use manager::{InputManager, InputManagerHandler, OutputManager, OutputManagerHandler,
-              XdgShellManager,
+#[cfg(feature = "xdg-output")]
+use extensions::xdg_output::XdgOutputManager;
+use manager::{DecorationManager, DecorationManagerHandler, IdleInhibitManager,
+              IdleInhibitManagerHandler, IdleInhibitor, InputManager, InputManagerHandler,
+              LayerShellManager, LayerShellManagerHandler, OutputManager, OutputManagerHandler,
+              PointerConstraintHandle, PointerConstraintsManager,
+              PointerConstraintsManagerHandler,
+              SessionHandler, SessionListener, VirtualKeyboardManager, XdgShellManager,
               XdgShellManagerHandler, XdgV6ShellManager, XdgV6ShellManagerHandler};
+#[cfg(feature = "output-management")]
+use manager::{OutputManagementManager, OutputManagementManagerHandler};
+#[cfg(feature = "text-input")]
+use manager::{set_input_method_global, set_text_input_global, InputMethodManager,
+              InputMethodManagerHandler, TextInputManager, TextInputManagerHandler};
+#[cfg(feature = "virtual-pointer")]
+use manager::VirtualPointerManager;
 use render::GenericRenderer;
 
 use wayland_sys::server::{wl_display, wl_event_loop, signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
 use wlroots_sys::{wlr_backend_destroy, wlr_backend_start,
                   wlr_compositor, wlr_compositor_create, wlr_compositor_destroy,
+                  wlr_idle_inhibit_manager_v1, wlr_idle_inhibit_v1_create, wlr_idle_inhibitor_v1,
+                  wlr_layer_shell, wlr_layer_shell_create,
+                  wlr_pointer_constraints_v1, wlr_pointer_constraints_v1_constraint_for_surface,
+                  wlr_pointer_constraints_v1_create, wlr_virtual_keyboard_manager_v1,
+                  wlr_virtual_keyboard_manager_v1_create, wlr_xdg_decoration_manager_v1,
+                  wlr_xdg_decoration_manager_v1_create,
                   wlr_xdg_shell_v6, wlr_xdg_shell_v6_create,
                   wlr_xdg_shell, wlr_xdg_shell_create};
+#[cfg(feature = "output-management")]
+use wlroots_sys::{wlr_output_configuration_head_v1_create, wlr_output_configuration_v1_create,
+                  wlr_output_manager_v1, wlr_output_manager_v1_create,
+                  wlr_output_manager_v1_set_configuration};
+#[cfg(feature = "text-input")]
+use wlroots_sys::{wlr_input_method_manager_v2, wlr_input_method_manager_v2_create,
+                  wlr_text_input_manager_v3, wlr_text_input_manager_v3_create};
+#[cfg(feature = "virtual-pointer")]
+use wlroots_sys::{wlr_virtual_pointer_manager_v1, wlr_virtual_pointer_manager_v1_create};
 use wlroots_sys::wayland_server::sys::wl_display_init_shm;
 
 /// Global compositor pointer, used to refer to the compositor state unsafely.
@@ -88,6 +128,62 @@ pub struct Compositor {
     /// Pointer to the xdg_shell_v6 global.
     /// If xdg_v6_shell_manager is `None`, this value will be `NULL`.
     xdg_v6_shell_global: *mut wlr_xdg_shell_v6,
+    /// Manager for layer shells.
+    layer_shell_manager: Option<Box<LayerShellManager>>,
+    /// Pointer to the layer_shell global.
+    /// If layer_shell_manager is `None`, this value will be `NULL`.
+    layer_shell_global: *mut wlr_layer_shell,
+    /// Manager for idle inhibitors.
+    idle_inhibit_manager: Option<Box<IdleInhibitManager>>,
+    /// Pointer to the idle_inhibit global.
+    /// If idle_inhibit_manager is `None`, this value will be `NULL`.
+    idle_inhibit_global: *mut wlr_idle_inhibit_manager_v1,
+    /// Manager for output management clients.
+    #[cfg(feature = "output-management")]
+    output_management_manager: Option<Box<OutputManagementManager>>,
+    /// Pointer to the output_management global.
+    /// If output_management_manager is `None`, this value will be `NULL`.
+    #[cfg(feature = "output-management")]
+    output_management_global: *mut wlr_output_manager_v1,
+    /// Manager for pointer constraints.
+    pointer_constraints_manager: Option<Box<PointerConstraintsManager>>,
+    /// Pointer to the pointer_constraints global.
+    /// If pointer_constraints_manager is `None`, this value will be `NULL`.
+    pointer_constraints_global: *mut wlr_pointer_constraints_v1,
+    /// Manager for xdg-decoration clients.
+    decoration_manager: Option<Box<DecorationManager>>,
+    /// Pointer to the xdg_decoration global.
+    /// If decoration_manager is `None`, this value will be `NULL`.
+    decoration_global: *mut wlr_xdg_decoration_manager_v1,
+    /// Manager for virtual keyboards.
+    virtual_keyboard_manager: Option<Box<VirtualKeyboardManager>>,
+    /// Pointer to the virtual_keyboard global.
+    /// If virtual_keyboard_manager is `None`, this value will be `NULL`.
+    virtual_keyboard_global: *mut wlr_virtual_keyboard_manager_v1,
+    /// Manager for virtual pointers.
+    #[cfg(feature = "virtual-pointer")]
+    virtual_pointer_manager: Option<Box<VirtualPointerManager>>,
+    /// Pointer to the virtual_pointer global.
+    /// If virtual_pointer_manager is `None`, this value will be `NULL`.
+    #[cfg(feature = "virtual-pointer")]
+    virtual_pointer_global: *mut wlr_virtual_pointer_manager_v1,
+    /// Manager for text inputs.
+    #[cfg(feature = "text-input")]
+    text_input_manager: Option<Box<TextInputManager>>,
+    /// Pointer to the text_input global.
+    /// If text_input_manager is `None`, this value will be `NULL`.
+    #[cfg(feature = "text-input")]
+    text_input_global: *mut wlr_text_input_manager_v3,
+    /// Manager for input methods.
+    #[cfg(feature = "text-input")]
+    input_method_manager: Option<Box<InputMethodManager>>,
+    /// Pointer to the input_method global.
+    /// If input_method_manager is `None`, this value will be `NULL`.
+    #[cfg(feature = "text-input")]
+    input_method_global: *mut wlr_input_method_manager_v2,
+    /// Listener for the session of the backend becoming active or inactive.
+    /// This is `None` if no handler was provided or the backend has no session.
+    session_listener: Option<Box<SessionListener>>,
     /// Pointer to the wlr_compositor.
     compositor: *mut wlr_compositor,
     /// Pointer to the wlroots backend in use.
@@ -102,6 +198,20 @@ pub struct Compositor {
     socket_name: String,
     /// Optional decoration manager extension.
     pub server_decoration_manager: Option<ServerDecorationManager>,
+    /// Optional xdg_output manager extension.
+    #[cfg(feature = "xdg-output")]
+    pub xdg_output_manager: Option<XdgOutputManager>,
+    /// Optional idle protocol extension.
+    pub idle: Option<Idle>,
+    /// Optional presentation time extension.
+    #[cfg(feature = "presentation-time")]
+    pub presentation: Option<Presentation>,
+    /// Optional relative pointer manager extension.
+    #[cfg(feature = "relative-pointer")]
+    pub relative_pointer_manager: Option<RelativePointerManager>,
+    /// Optional foreign toplevel manager extension.
+    #[cfg(feature = "foreign-toplevel")]
+    pub foreign_toplevel_manager: Option<ForeignToplevelManager>,
     /// The renderer used to draw things to the screen.
     pub renderer: Option<GenericRenderer>,
     /// XWayland server, only Some if it is enabled
@@ -112,6 +222,11 @@ pub struct Compositor {
     panic_error: Option<Box<Any + Send>>,
     /// Custom function to run at shutdown (or when a panic occurs).
     user_terminate: Option<fn()>,
+    /// Callbacks to run when the compositor is terminated.
+    terminate_callbacks: Vec<Box<FnMut()>>,
+    /// The exit code passed to `terminate_with`, returned from
+    /// `run_with_exit_code`.
+    exit_code: i32,
     /// Lock used to borrow the compositor globally.
     /// Should always be set before passing a reference to the compositor
     /// in a callback.
@@ -125,11 +240,36 @@ pub struct CompositorBuilder {
     output_manager_handler: Option<Box<OutputManagerHandler>>,
     xdg_shell_manager_handler: Option<Box<XdgShellManagerHandler>>,
     xdg_v6_shell_manager_handler: Option<Box<XdgV6ShellManagerHandler>>,
+    layer_shell_manager_handler: Option<Box<LayerShellManagerHandler>>,
+    idle_inhibit_manager_handler: Option<Box<IdleInhibitManagerHandler>>,
+    #[cfg(feature = "output-management")]
+    output_management_manager_handler: Option<Box<OutputManagementManagerHandler>>,
+    pointer_constraints_manager_handler: Option<Box<PointerConstraintsManagerHandler>>,
+    decoration_manager_handler: Option<Box<DecorationManagerHandler>>,
+    virtual_keyboard_manager: bool,
+    #[cfg(feature = "virtual-pointer")]
+    virtual_pointer_manager: bool,
+    #[cfg(feature = "text-input")]
+    text_input_manager_handler: Option<Box<TextInputManagerHandler>>,
+    #[cfg(feature = "text-input")]
+    input_method_manager_handler: Option<Box<InputMethodManagerHandler>>,
+    session_handler: Option<Box<SessionHandler>>,
     gles2: bool,
     render_setup_function: Option<UnsafeRenderSetupFunction>,
     server_decoration_manager: bool,
+    #[cfg(feature = "xdg-output")]
+    xdg_output_layout: Option<OutputLayoutHandle>,
+    idle: bool,
+    #[cfg(feature = "presentation-time")]
+    presentation: bool,
+    #[cfg(feature = "relative-pointer")]
+    relative_pointer_manager: bool,
+    #[cfg(feature = "foreign-toplevel")]
+    foreign_toplevel_manager: bool,
+    backend: BackendKind,
     wayland_remote: Option<String>,
     x11_display: Option<String>,
+    socket_name: Option<String>,
     data_device_manager: bool,
     xwayland: Option<Box<XWaylandManagerHandler>>,
     user_terminate: Option<fn()>
@@ -176,9 +316,125 @@ impl CompositorBuilder {
         self
     }
 
+    /// Set the handler for layer shells.
+    ///
+    /// These are used by desktop components such as panels and wallpapers.
+    pub fn layer_shell_manager(mut self,
+                               layer_shell_manager_handler: Box<LayerShellManagerHandler>)
+                               -> Self {
+        self.layer_shell_manager_handler = Some(layer_shell_manager_handler);
+        self
+    }
+
+    /// Set the handler for idle inhibitors.
+    ///
+    /// This advertises the idle_inhibit global, which clients such as video
+    /// players use to keep the compositor from going idle.
+    pub fn idle_inhibit_manager(mut self,
+                                idle_inhibit_manager_handler: Box<IdleInhibitManagerHandler>)
+                                -> Self {
+        self.idle_inhibit_manager_handler = Some(idle_inhibit_manager_handler);
+        self
+    }
+
+    /// Set the handler for output management clients.
+    ///
+    /// These are display configuration tools that let the user rearrange,
+    /// scale or change the mode of the outputs.
+    #[cfg(feature = "output-management")]
+    pub fn output_management_manager(mut self,
+                                     output_management_manager_handler:
+                                         Box<OutputManagementManagerHandler>)
+                                     -> Self {
+        self.output_management_manager_handler = Some(output_management_manager_handler);
+        self
+    }
+
+    /// Set the handler for pointer constraints.
+    ///
+    /// These are used by clients such as games to lock or confine the
+    /// pointer.
+    pub fn pointer_constraints_manager(mut self,
+                                       pointer_constraints_manager_handler:
+                                           Box<PointerConstraintsManagerHandler>)
+                                       -> Self {
+        self.pointer_constraints_manager_handler = Some(pointer_constraints_manager_handler);
+        self
+    }
+
+    /// Set the handler for the xdg-decoration protocol, which decides
+    /// whether stable xdg shell toplevels draw their own decorations.
+    ///
+    /// Without it clients assume they have to draw their own decorations.
+    pub fn decoration_manager(mut self,
+                              decoration_manager_handler: Box<DecorationManagerHandler>)
+                              -> Self {
+        self.decoration_manager_handler = Some(decoration_manager_handler);
+        self
+    }
+
+    /// Set the handler for the text input protocol, which lets clients
+    /// receive text from an input method.
+    ///
+    /// Text inputs follow the keyboard focus of their seat, as set with
+    /// `Seat::keyboard_notify_enter`.
+    #[cfg(feature = "text-input")]
+    pub fn text_input_manager(mut self,
+                              text_input_manager_handler: Box<TextInputManagerHandler>)
+                              -> Self {
+        self.text_input_manager_handler = Some(text_input_manager_handler);
+        self
+    }
+
+    /// Set the handler for the input method protocol, which lets input method
+    /// editors send text to the focused text input of a seat.
+    ///
+    /// This does nothing useful without `text_input_manager`.
+    #[cfg(feature = "text-input")]
+    pub fn input_method_manager(mut self,
+                                input_method_manager_handler: Box<InputMethodManagerHandler>)
+                                -> Self {
+        self.input_method_manager_handler = Some(input_method_manager_handler);
+        self
+    }
+
+    /// Set the handler that is called when the session becomes active or
+    /// inactive, e.g when switching virtual terminals.
+    ///
+    /// Only the DRM backend has a session, on other backends this is ignored.
+    pub fn session_handler(mut self, session_handler: Box<SessionHandler>) -> Self {
+        self.session_handler = Some(session_handler);
+        self
+    }
+
+    /// Decide whether or not to enable the virtual keyboard protocol.
+    ///
+    /// Virtual keyboards created by clients are passed to
+    /// `InputManagerHandler::keyboard_added` like physical keyboards, so this
+    /// does nothing without an input manager.
+    pub fn virtual_keyboard_manager(mut self, virtual_keyboard_manager: bool) -> Self {
+        self.virtual_keyboard_manager = virtual_keyboard_manager;
+        self
+    }
+
+    /// Decide whether or not to enable the virtual pointer protocol.
+    ///
+    /// Virtual pointers created by clients are passed to
+    /// `InputManagerHandler::pointer_added` like physical pointers, and then
+    /// to `InputManagerHandler::virtual_pointer_added` with the output they
+    /// target. This does nothing without an input manager.
+    #[cfg(feature = "virtual-pointer")]
+    pub fn virtual_pointer_manager(mut self, virtual_pointer_manager: bool) -> Self {
+        self.virtual_pointer_manager = virtual_pointer_manager;
+        self
+    }
+
     /// Decide whether or not to enable the data device manager.
     ///
-    /// This is used to do DnD, or "drag 'n drop" copy paste.
+    /// This advertises the `wl_data_device_manager` global, which clients
+    /// need in order to copy and paste or do DnD ("drag 'n drop"). Without it
+    /// clients can't set a selection, so `SeatHandler::received_selection`
+    /// will only fire for selections set by the compositor.
     pub fn data_device(mut self, data_device_manager: bool) -> Self {
         self.data_device_manager = data_device_manager;
         self
@@ -197,6 +453,61 @@ impl CompositorBuilder {
         self
     }
 
+    /// Enable the xdg_output protocol extension, which lets clients query the
+    /// logical position and size of outputs.
+    ///
+    /// The logical geometry is derived from the given `OutputLayout`, so
+    /// clients are kept up to date as outputs move around in it.
+    #[cfg(feature = "xdg-output")]
+    pub fn xdg_output_manager(mut self, layout: OutputLayoutHandle) -> Self {
+        self.xdg_output_layout = Some(layout);
+        self
+    }
+
+    /// Decide whether or not to enable the idle protocol extension.
+    ///
+    /// This lets clients (e.g screen lockers) know when the user is idle, and
+    /// lets the compositor register its own idle timeouts with
+    /// `Idle::add_timeout`.
+    pub fn idle(mut self, idle: bool) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Decide whether or not to enable the presentation time protocol
+    /// extension.
+    ///
+    /// Clients are only sent feedback that the compositor forwards with
+    /// `Presentation::sample_output_timings`.
+    #[cfg(feature = "presentation-time")]
+    pub fn presentation(mut self, presentation: bool) -> Self {
+        self.presentation = presentation;
+        self
+    }
+
+    /// Decide whether or not to enable the relative pointer protocol
+    /// extension.
+    ///
+    /// Clients are only sent the motion the compositor forwards with
+    /// `RelativePointerManager::send_relative_motion`.
+    #[cfg(feature = "relative-pointer")]
+    pub fn relative_pointer_manager(mut self, relative_pointer_manager: bool) -> Self {
+        self.relative_pointer_manager = relative_pointer_manager;
+        self
+    }
+
+    /// Decide whether or not to enable the foreign toplevel management
+    /// protocol extension.
+    ///
+    /// This lets clients such as taskbars list and control the toplevels
+    /// the compositor advertises with
+    /// `ForeignToplevelManager::create_toplevel`.
+    #[cfg(feature = "foreign-toplevel")]
+    pub fn foreign_toplevel_manager(mut self, foreign_toplevel_manager: bool) -> Self {
+        self.foreign_toplevel_manager = foreign_toplevel_manager;
+        self
+    }
+
     /// Add a handler for xwayland.
     ///
     /// If you do not provide a handler then the xwayland server does not run.
@@ -218,12 +529,66 @@ impl CompositorBuilder {
         self
     }
 
+    /// Pick the backend that `build` uses.
+    ///
+    /// By default it is picked automatically, like `build_auto` does.
+    pub fn backend(mut self, backend: BackendKind) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Makes a new compositor with the backend picked with `backend`.
+    ///
+    /// If that backend can't be used in this environment (e.g `X11` without
+    /// a running X server) this logs an error and falls back to `build_auto`.
+    pub fn build<D>(self, data: D) -> Result<Compositor, SocketError>
+        where D: Any + 'static
+    {
+        match self.backend {
+            BackendKind::Auto => self.build_auto(data),
+            BackendKind::Headless => self.build_headless(data),
+            BackendKind::Wayland => {
+                if self.wayland_remote.is_some() || env::var_os("WAYLAND_DISPLAY").is_some() {
+                    self.build_wayland(data)
+                } else {
+                    wlr_log!(WLR_ERROR, "No Wayland display to nest in, picking a backend");
+                    self.build_auto(data)
+                }
+            }
+            BackendKind::X11 => {
+                if self.x11_display.is_some() || env::var_os("DISPLAY").is_some() {
+                    self.build_x11(data)
+                } else {
+                    wlr_log!(WLR_ERROR, "No X11 display to nest in, picking a backend");
+                    self.build_auto(data)
+                }
+            }
+            BackendKind::Drm => unsafe {
+                let display =
+                    ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_create,) as *mut wl_display;
+                let event_loop =
+                    ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_event_loop, display);
+                let render_setup_function = self.render_setup_function;
+                let backend = match MultiBackend::drm(display as *mut _, render_setup_function) {
+                    Some(backend) => backend,
+                    None => {
+                        wlr_log!(WLR_ERROR, "Could not set up the DRM backend, picking a backend");
+                        MultiBackend::auto_create(display as *mut _, render_setup_function)
+                    }
+                };
+                self.finish_build(data, display, event_loop, Backend::Multi(backend))
+            }
+        }
+    }
+
     /// Makes a new compositor that handles the setup of the graphical backend
     /// (e.g, Wayland, X11, or DRM).
     ///
     /// Also automatically opens the socket for clients to communicate to the
     /// compositor with.
-    pub fn build_auto<D>(self, data: D) -> Compositor
+    ///
+    /// Returns an error if that socket can't be opened, see `socket_name`.
+    pub fn build_auto<D>(self, data: D) -> Result<Compositor, SocketError>
         where D: Any + 'static
     {
         unsafe {
@@ -252,7 +617,21 @@ impl CompositorBuilder {
         self
     }
 
-    pub fn build_x11<D>(mut self, data: D) -> Compositor
+    /// Set the name of the Wayland socket clients will connect to
+    /// (e.g. `wayland-1`).
+    ///
+    /// By default the first free name is picked automatically, it can be
+    /// retrieved afterwards with `Compositor::socket_name`.
+    ///
+    /// Building the compositor fails with `SocketError::InvalidName` if the name
+    /// contains a NUL byte, or `SocketError::CouldNotOpen` if the socket can't
+    /// be opened (e.g because another compositor already uses the name).
+    pub fn socket_name(mut self, socket_name: String) -> Self {
+        self.socket_name = Some(socket_name);
+        self
+    }
+
+    pub fn build_x11<D>(mut self, data: D) -> Result<Compositor, SocketError>
         where D: Any + 'static
     {
         unsafe {
@@ -270,7 +649,7 @@ impl CompositorBuilder {
     /// Creates the compositor using an already running Wayland instance as a backend.
     ///
     /// The instance starts with no outputs.
-    pub fn build_wayland<D>(mut self, data: D) -> Compositor
+    pub fn build_wayland<D>(mut self, data: D) -> Result<Compositor, SocketError>
         where D: Any + 'static
     {
         unsafe {
@@ -290,7 +669,7 @@ impl CompositorBuilder {
                                session: Session,
                                gpu_fd: libc::c_int,
                                parent: Option<DRMBackend>)
-                               -> Compositor
+                               -> Result<Compositor, SocketError>
         where D: Any + 'static
     {
         unsafe {
@@ -307,7 +686,7 @@ impl CompositorBuilder {
         }
     }
 
-    pub fn build_headless<D>(self, data: D) -> Compositor
+    pub fn build_headless<D>(self, data: D) -> Result<Compositor, SocketError>
         where D: Any + 'static
     {
         unsafe {
@@ -321,13 +700,23 @@ impl CompositorBuilder {
         }
     }
 
-    unsafe fn finish_build<D>(self,
+    unsafe fn finish_build<D>(mut self,
                               data: D,
                               display: *mut wl_display,
                               event_loop: *mut wl_event_loop,
                               backend: Backend)
-                              -> Compositor
+                              -> Result<Compositor, SocketError>
         where D: Any + 'static {
+            // Open the socket to the Wayland server first, so nothing else
+            // has been set up if it fails.
+            let socket_name = match open_socket(display, self.socket_name.take()) {
+                Ok(socket_name) => socket_name,
+                Err(err) => {
+                    wlr_backend_destroy(backend.as_ptr());
+                    ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_destroy, display);
+                    return Err(err)
+                }
+            };
             // Set up shared memory buffer for Wayland clients.
             let shm_fd = wl_display_init_shm(display as *mut _);
             // Create optional extensions.
@@ -336,6 +725,35 @@ impl CompositorBuilder {
             } else {
                 None
             };
+            #[cfg(feature = "xdg-output")]
+            let xdg_output_manager = self.xdg_output_layout.and_then(|layout| {
+                layout.run(|layout| XdgOutputManager::new(display, layout))
+                      .unwrap_or_else(|err| {
+                          wlr_log!(WLR_ERROR,
+                                   "Could not create xdg_output manager: {}",
+                                   err);
+                          None
+                      })
+            });
+            let idle = if self.idle { Idle::new(display) } else { None };
+            #[cfg(feature = "presentation-time")]
+            let presentation = if self.presentation {
+                Presentation::new(display, backend.as_ptr())
+            } else {
+                None
+            };
+            #[cfg(feature = "relative-pointer")]
+            let relative_pointer_manager = if self.relative_pointer_manager {
+                RelativePointerManager::new(display)
+            } else {
+                None
+            };
+            #[cfg(feature = "foreign-toplevel")]
+            let foreign_toplevel_manager = if self.foreign_toplevel_manager {
+                ForeignToplevelManager::new(display)
+            } else {
+                None
+            };
             let data_device_manager = if self.data_device_manager {
                 DataDeviceManager::new(display as _)
             } else {
@@ -364,14 +782,76 @@ impl CompositorBuilder {
                 compositor_handler
             });
 
+            // Listen to the session becoming active or inactive, if the backend has one.
+            let session_listener = self.session_handler.and_then(|handler| {
+                match backend.session() {
+                    Some(session) => {
+                        let mut session_listener = SessionListener::new(handler);
+                        wl_signal_add(&mut (*session.as_ptr()).session_signal as *mut _ as _,
+                                      session_listener.active_listener() as *mut _ as _);
+                        Some(session_listener)
+                    }
+                    None => {
+                        wlr_log!(WLR_INFO, "Backend has no session, ignoring the session handler");
+                        None
+                    }
+                }
+            });
+
             // Set up input manager, if the user provided it.
-            let input_manager = self.input_manager_handler.map(|handler| {
-                let mut input_manager = InputManager::new(handler);
+            let mut input_manager = self.input_manager_handler.map(|handler| {
+                let mut input_manager = InputManager::new((handler, Rc::new(Cell::new(false))));
                 wl_signal_add(&mut (*backend.as_ptr()).events.new_input as *mut _ as _,
                               input_manager.add_listener() as *mut _ as _);
                 input_manager
             });
 
+            // Set up the virtual_keyboard global, which feeds into the input manager.
+            let mut virtual_keyboard_global = ptr::null_mut();
+            let virtual_keyboard_manager = match input_manager {
+                Some(ref mut input_manager) if self.virtual_keyboard_manager => {
+                    virtual_keyboard_global =
+                        wlr_virtual_keyboard_manager_v1_create(display as *mut _);
+                    let input_manager_ptr = &mut **input_manager as *mut InputManager;
+                    let manager_data = (input_manager_ptr, input_manager.liveliness());
+                    let mut virtual_keyboard_manager = VirtualKeyboardManager::new(manager_data);
+                    wl_signal_add(&mut (*virtual_keyboard_global).events.new_virtual_keyboard
+                                      as *mut _ as _,
+                                  virtual_keyboard_manager.new_virtual_keyboard_listener()
+                                      as *mut _ as _);
+                    Some(virtual_keyboard_manager)
+                }
+                None if self.virtual_keyboard_manager => {
+                    wlr_log!(WLR_ERROR,
+                             "Virtual keyboards need an input manager, not enabling them");
+                    None
+                }
+                _ => None
+            };
+
+            // Set up the virtual_pointer global, which feeds into the input manager.
+            #[cfg(feature = "virtual-pointer")]
+            let (virtual_pointer_manager, virtual_pointer_global) = match input_manager {
+                Some(ref mut input_manager) if self.virtual_pointer_manager => {
+                    let virtual_pointer_global =
+                        wlr_virtual_pointer_manager_v1_create(display as *mut _);
+                    let input_manager_ptr = &mut **input_manager as *mut InputManager;
+                    let manager_data = (input_manager_ptr, input_manager.liveliness());
+                    let mut virtual_pointer_manager = VirtualPointerManager::new(manager_data);
+                    wl_signal_add(&mut (*virtual_pointer_global).events.new_virtual_pointer
+                                      as *mut _ as _,
+                                  virtual_pointer_manager.new_virtual_pointer_listener()
+                                      as *mut _ as _);
+                    (Some(virtual_pointer_manager), virtual_pointer_global)
+                }
+                None if self.virtual_pointer_manager => {
+                    wlr_log!(WLR_ERROR,
+                             "Virtual pointers need an input manager, not enabling them");
+                    (None, ptr::null_mut())
+                }
+                _ => (None, ptr::null_mut())
+            };
+
             // Set up output manager, if the user provided it.
             let output_manager = self.output_manager_handler.map(|handler| {
                 let mut output_manager = OutputManager::new(handler);
@@ -402,6 +882,104 @@ impl CompositorBuilder {
                 xdg_v6_shell_manager
             });
 
+            // Set up the layer_shell handler and associated Wayland global,
+            // if user provided a manager for it.
+            let mut layer_shell_global = ptr::null_mut();
+            let layer_shell_manager = self.layer_shell_manager_handler.map(|handler| {
+                layer_shell_global = wlr_layer_shell_create(display as *mut _);
+                let mut layer_shell_manager = LayerShellManager::new(handler);
+                wl_signal_add(&mut (*layer_shell_global).events.new_surface as *mut _ as _,
+                              layer_shell_manager.add_listener() as *mut _ as _);
+                layer_shell_manager
+            });
+
+            // Set up the idle_inhibit handler and associated Wayland global,
+            // if user provided a manager for it.
+            let mut idle_inhibit_global = ptr::null_mut();
+            let idle_inhibit_manager = self.idle_inhibit_manager_handler.map(|handler| {
+                idle_inhibit_global = wlr_idle_inhibit_v1_create(display as *mut _);
+                let mut idle_inhibit_manager =
+                    IdleInhibitManager::new((handler, Rc::new(Cell::new(false))));
+                wl_signal_add(&mut (*idle_inhibit_global).events.new_inhibitor as *mut _ as _,
+                              idle_inhibit_manager.new_inhibitor_listener() as *mut _ as _);
+                idle_inhibit_manager
+            });
+
+            // Set up the output_management handler and associated Wayland global,
+            // if user provided a manager for it.
+            #[cfg(feature = "output-management")]
+            let mut output_management_global = ptr::null_mut();
+            #[cfg(feature = "output-management")]
+            let output_management_manager =
+                self.output_management_manager_handler.map(|handler| {
+                    output_management_global = wlr_output_manager_v1_create(display as *mut _);
+                    let mut output_management_manager = OutputManagementManager::new(handler);
+                    wl_signal_add(&mut (*output_management_global).events.apply as *mut _ as _,
+                                  output_management_manager.apply_listener() as *mut _ as _);
+                    wl_signal_add(&mut (*output_management_global).events.test as *mut _ as _,
+                                  output_management_manager.test_listener() as *mut _ as _);
+                    output_management_manager
+                });
+
+            // Set up the pointer_constraints handler and associated Wayland global,
+            // if user provided a manager for it.
+            let mut pointer_constraints_global = ptr::null_mut();
+            let pointer_constraints_manager =
+                self.pointer_constraints_manager_handler.map(|handler| {
+                    pointer_constraints_global =
+                        wlr_pointer_constraints_v1_create(display as *mut _);
+                    let mut pointer_constraints_manager =
+                        PointerConstraintsManager::new((handler, Rc::new(Cell::new(false))));
+                    wl_signal_add(&mut (*pointer_constraints_global).events.new_constraint
+                                      as *mut _ as _,
+                                  pointer_constraints_manager.new_constraint_listener()
+                                      as *mut _ as _);
+                    pointer_constraints_manager
+                });
+
+            // Set up the xdg_decoration handler and associated Wayland global,
+            // if user provided a manager for it.
+            let mut decoration_global = ptr::null_mut();
+            let decoration_manager = self.decoration_manager_handler.map(|handler| {
+                decoration_global = wlr_xdg_decoration_manager_v1_create(display as *mut _);
+                let mut decoration_manager =
+                    DecorationManager::new((handler, Rc::new(Cell::new(false))));
+                wl_signal_add(&mut (*decoration_global).events.new_toplevel_decoration
+                                  as *mut _ as _,
+                              decoration_manager.new_decoration_listener() as *mut _ as _);
+                decoration_manager
+            });
+
+            // Set up the text_input handler and associated Wayland global,
+            // if user provided a manager for it.
+            #[cfg(feature = "text-input")]
+            let mut text_input_global = ptr::null_mut();
+            #[cfg(feature = "text-input")]
+            let text_input_manager = self.text_input_manager_handler.map(|handler| {
+                text_input_global = wlr_text_input_manager_v3_create(display as *mut _);
+                set_text_input_global(text_input_global);
+                let mut text_input_manager =
+                    TextInputManager::new((handler, Rc::new(Cell::new(false))));
+                wl_signal_add(&mut (*text_input_global).events.text_input as *mut _ as _,
+                              text_input_manager.text_input_listener() as *mut _ as _);
+                text_input_manager
+            });
+
+            // Set up the input_method handler and associated Wayland global,
+            // if user provided a manager for it.
+            #[cfg(feature = "text-input")]
+            let mut input_method_global = ptr::null_mut();
+            #[cfg(feature = "text-input")]
+            let input_method_manager = self.input_method_manager_handler.map(|handler| {
+                input_method_global = wlr_input_method_manager_v2_create(display as *mut _);
+                set_input_method_global(input_method_global);
+                let mut input_method_manager =
+                    InputMethodManager::new((handler, Rc::new(Cell::new(false))));
+                wl_signal_add(&mut (*input_method_global).events.input_method as *mut _ as _,
+                              input_method_manager.input_method_listener() as *mut _ as _);
+                input_method_manager
+            });
+
             // Set up the XWayland server, if the user wants it.
             let xwayland = self.xwayland.and_then(|manager| {
                                                       Some(XWaylandServer::new(display as _,
@@ -412,17 +990,7 @@ impl CompositorBuilder {
 
             let user_terminate = self.user_terminate;
 
-            // Open the socket to the Wayland server.
-            let socket = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_add_socket_auto, display);
-            if socket.is_null() {
-                // NOTE Rationale for panicking:
-                // * Won't be in C land just yet, so it's safe to panic
-                // * Can always be returned in a Result instead, but for now
-                //   if you auto create it's assumed you can't recover.
-                panic!("Unable to open wayland socket");
-            }
-            let socket_name = CStr::from_ptr(socket).to_string_lossy().into_owned();
-            wlr_log!(WLR_DEBUG,
+            wlr_log!(WLR_INFO,
                      "Running compositor on wayland display {}",
                      socket_name);
             env::set_var("_WAYLAND_DISPLAY", socket_name.clone());
@@ -435,6 +1003,33 @@ impl CompositorBuilder {
                                           xdg_shell_global,
                                           xdg_v6_shell_manager,
                                           xdg_v6_shell_global,
+                                          layer_shell_manager,
+                                          layer_shell_global,
+                                          idle_inhibit_manager,
+                                          idle_inhibit_global,
+                                          #[cfg(feature = "output-management")]
+                                          output_management_manager,
+                                          #[cfg(feature = "output-management")]
+                                          output_management_global,
+                                          pointer_constraints_manager,
+                                          pointer_constraints_global,
+                                          decoration_manager,
+                                          decoration_global,
+                                          virtual_keyboard_manager,
+                                          virtual_keyboard_global,
+                                          #[cfg(feature = "virtual-pointer")]
+                                          virtual_pointer_manager,
+                                          #[cfg(feature = "virtual-pointer")]
+                                          virtual_pointer_global,
+                                          #[cfg(feature = "text-input")]
+                                          text_input_manager,
+                                          #[cfg(feature = "text-input")]
+                                          text_input_global,
+                                          #[cfg(feature = "text-input")]
+                                          input_method_manager,
+                                          #[cfg(feature = "text-input")]
+                                          input_method_global,
+                                          session_listener,
                                           data_device_manager,
                                           compositor,
                                           backend,
@@ -442,13 +1037,54 @@ impl CompositorBuilder {
                                           event_loop,
                                           shm_fd,
                                           server_decoration_manager,
+                                          #[cfg(feature = "xdg-output")]
+                                          xdg_output_manager,
+                                          idle,
+                                          #[cfg(feature = "presentation-time")]
+                                          presentation,
+                                          #[cfg(feature = "relative-pointer")]
+                                          relative_pointer_manager,
+                                          #[cfg(feature = "foreign-toplevel")]
+                                          foreign_toplevel_manager,
                                           renderer,
                                           xwayland,
                                           user_terminate,
+                                          terminate_callbacks: Vec::new(),
+                                          exit_code: 0,
                                           panic_error: None,
                                           lock: Rc::new(Cell::new(false)) };
             compositor.set_lock(true);
-            compositor
+            Ok(compositor)
+    }
+}
+
+/// Opens the Wayland socket clients connect to, picking a free name if none
+/// was given.
+unsafe fn open_socket(display: *mut wl_display,
+                      socket_name: Option<String>)
+                      -> Result<String, SocketError> {
+    match socket_name {
+        Some(socket_name) => {
+            let socket_name_c =
+                CString::new(socket_name.clone()).map_err(|_| SocketError::InvalidName)?;
+            let res = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                    wl_display_add_socket,
+                                    display,
+                                    socket_name_c.as_ptr());
+            if res != 0 {
+                wlr_log!(WLR_ERROR, "Unable to open wayland socket {}", socket_name);
+                return Err(SocketError::CouldNotOpen)
+            }
+            Ok(socket_name)
+        }
+        None => {
+            let socket = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_add_socket_auto, display);
+            if socket.is_null() {
+                wlr_log!(WLR_ERROR, "Unable to open wayland socket");
+                return Err(SocketError::CouldNotOpen)
+            }
+            Ok(CStr::from_ptr(socket).to_string_lossy().into_owned())
+        }
     }
 }
 
@@ -462,11 +1098,19 @@ impl Compositor {
     /// Enters the wayland event loop. Won't return until the compositor is
     /// shut off
     pub fn run(self) {
-        self.run_with(|_| unsafe {
-                          ffi_dispatch!(WAYLAND_SERVER_HANDLE,
-                                        wl_display_run,
-                                        (*COMPOSITOR_PTR).display);
-                      })
+        self.run_with_exit_code();
+    }
+
+    /// Enters the wayland event loop, like `run`.
+    ///
+    /// Returns the exit code the compositor was terminated with, see
+    /// `terminate_with`.
+    pub fn run_with_exit_code(self) -> i32 {
+        self.run_inner(|_| unsafe {
+                           ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                         wl_display_run,
+                                         (*COMPOSITOR_PTR).display);
+                       })
     }
 
     /// Prepare to enter the wayland event loop. Instead of calling
@@ -474,6 +1118,12 @@ impl Compositor {
     /// integration with a different event loop.
     pub fn run_with<F>(self, runner: F)
         where F: FnOnce(&Compositor)
+    {
+        self.run_inner(runner);
+    }
+
+    fn run_inner<F>(self, runner: F) -> i32
+        where F: FnOnce(&Compositor)
     {
         unsafe {
             self.set_lock(false);
@@ -503,21 +1153,227 @@ impl Compositor {
                     ::std::panic::resume_unwind(err)
                 }
             }
+            (*compositor.get()).exit_code
         }
     }
 
-    /// Shutdown the wayland server
-    fn terminate(&mut self) {
+    /// Call the callback from the event loop once the delay has passed.
+    ///
+    /// The timer can be cancelled or armed again through the returned
+    /// handle. Panics in the callback terminate the compositor.
+    pub fn add_timer<F>(&mut self, delay: Duration, callback: F) -> TimerHandle
+        where F: FnMut() + 'static
+    {
+        unsafe { TimerHandle::new(self.event_loop, delay, Box::new(callback)) }
+    }
+
+    /// Call the callback from the event loop whenever the file descriptor has
+    /// one of the events in the mask (e.g it can be read from).
+    ///
+    /// This integrates external IO (e.g a control socket) into the
+    /// compositor without spawning a thread. The source can be removed
+    /// through the returned handle. Panics in the callback terminate the
+    /// compositor.
+    pub fn add_fd_source<F>(&mut self, fd: RawFd, mask: EventMask, callback: F) -> FdSourceHandle
+        where F: FnMut(RawFd, EventMask) + 'static
+    {
+        unsafe { FdSourceHandle::new(self.event_loop, fd, mask, Box::new(callback)) }
+    }
+
+    /// Call the callback once the event loop is done dispatching the current
+    /// events.
+    ///
+    /// This is the way to defer work (e.g destroying something or laying
+    /// out the views again) until it is safe to do. Panics in the callback
+    /// terminate the compositor.
+    pub fn add_idle<F>(&mut self, callback: F)
+        where F: FnOnce() + 'static
+    {
+        // NOTE Boxed `FnOnce`s can't be called, so it's wrapped in a `FnMut`
+        // that only runs it the first time.
+        let mut callback = Some(callback);
+        let callback = move || {
+            if let Some(callback) = callback.take() {
+                callback()
+            }
+        };
+        unsafe { add_idle(self.event_loop, Box::new(callback)) }
+    }
+
+    /// Get handles to all of the output layouts that exist, in the order they
+    /// were created.
+    ///
+    /// Layouts are tracked from when they are made with `OutputLayout::create`
+    /// until they are destroyed with `OutputLayoutHandle::destroy`. Once a
+    /// layout is destroyed it's no longer returned here, and the handles to
+    /// it that were returned before fail with `HandleErr::AlreadyDropped`.
+    pub fn output_layouts(&self) -> Vec<OutputLayoutHandle> {
+        output_layouts()
+    }
+
+    /// Add a callback to run when the compositor is terminated.
+    ///
+    /// The callbacks run in the order they were added, before the event
+    /// loop stops and before the function given to
+    /// `CompositorBuilder::custom_terminate`.
+    pub fn on_terminate<F>(&mut self, callback: F)
+        where F: FnOnce() + 'static
+    {
+        let mut callback = Some(callback);
+        self.terminate_callbacks.push(Box::new(move || {
+                                                   if let Some(callback) = callback.take() {
+                                                       callback()
+                                                   }
+                                               }));
+    }
+
+    /// Shutdown the wayland server, making `run_with_exit_code` return the
+    /// exit code.
+    ///
+    /// The callbacks added with `on_terminate` run before the event loop
+    /// stops.
+    pub fn terminate_with(&mut self, code: i32) {
+        self.exit_code = code;
+        for mut callback in self.terminate_callbacks.drain(..) {
+            callback()
+        }
+        self.user_terminate.map(|f| f());
         unsafe {
             ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_terminate, self.display);
         }
     }
 
+    /// Shutdown the wayland server.
+    ///
+    /// This is the same as `terminate_with(0)`.
+    pub fn terminate(&mut self) {
+        self.terminate_with(0)
+    }
+
+    /// Get the name of the Wayland socket clients connect to.
+    ///
+    /// Spawned clients should have `WAYLAND_DISPLAY` set to this value, which
+    /// is done automatically for the compositor's own process when it starts
+    /// running.
+    pub fn socket_name(&self) -> String {
+        self.socket_name.clone()
+    }
+
+    /// Get the idle inhibitors that currently exist.
+    ///
+    /// This is always empty unless `CompositorBuilder::idle_inhibit_manager`
+    /// was used. The compositor should not go idle while any of their
+    /// surfaces are visible.
+    pub fn idle_inhibitors(&self) -> Vec<IdleInhibitor> {
+        let mut result = Vec::new();
+        if self.idle_inhibit_global.is_null() {
+            return result
+        }
+        unsafe {
+            wl_list_for_each!((*self.idle_inhibit_global).inhibitors,
+                              link,
+                              (inhibitor: wlr_idle_inhibitor_v1) => {
+                result.push(IdleInhibitor::from_ptr(inhibitor));
+            });
+        }
+        result
+    }
+
+    /// Tell output management clients the current configuration of the
+    /// outputs in the layout, i.e whether they are enabled and their mode,
+    /// position, transform and scale.
+    ///
+    /// This must be called whenever an output is added to, removed from or
+    /// changed in the layout, including after applying an
+    /// `OutputConfiguration`. Outputs that aren't in the layout aren't
+    /// advertised.
+    ///
+    /// Does nothing unless `CompositorBuilder::output_management_manager`
+    /// was used.
+    #[cfg(feature = "output-management")]
+    pub fn set_output_configuration(&mut self, layout: &mut OutputLayout) {
+        if self.output_management_global.is_null() {
+            return
+        }
+        unsafe {
+            let config = wlr_output_configuration_v1_create();
+            if config.is_null() {
+                wlr_log!(WLR_ERROR, "Could not allocate the output configuration");
+                return
+            }
+            for (output, layout_output) in layout.outputs() {
+                let head = wlr_output_configuration_head_v1_create(config, output.as_ptr());
+                if head.is_null() {
+                    continue
+                }
+                // NOTE Filled in explicitly, as not every wlroots version
+                // copies the state of the output into new heads.
+                let output_ptr = output.as_ptr();
+                let state = &mut (*head).state;
+                let position = layout_output.position();
+                state.enabled = (*output_ptr).enabled;
+                state.mode = (*output_ptr).current_mode;
+                state.custom_mode.width = (*output_ptr).width;
+                state.custom_mode.height = (*output_ptr).height;
+                state.custom_mode.refresh = (*output_ptr).refresh;
+                state.x = position.x;
+                state.y = position.y;
+                state.transform = (*output_ptr).transform;
+                state.scale = (*output_ptr).scale;
+            }
+            wlr_output_manager_v1_set_configuration(self.output_management_global, config);
+        }
+    }
+
+    /// Get the pointer constraint a client placed on the seat's pointer for
+    /// the surface, if there is one.
+    ///
+    /// This should be checked whenever the surface gains pointer focus.
+    /// Always returns `None` unless
+    /// `CompositorBuilder::pointer_constraints_manager` was used.
+    pub fn pointer_constraint_for_surface(&self,
+                                          surface: &Surface,
+                                          seat: &Seat)
+                                          -> Option<PointerConstraintHandle> {
+        if self.pointer_constraints_global.is_null() {
+            return None
+        }
+        unsafe {
+            let constraint =
+                wlr_pointer_constraints_v1_constraint_for_surface(self.pointer_constraints_global,
+                                                                  surface.as_ptr(),
+                                                                  seat.as_ptr());
+            if constraint.is_null() {
+                None
+            } else {
+                Some(PointerConstraintHandle::from_ptr(constraint))
+            }
+        }
+    }
+
     /// Get a reference to the currently running backend.
     pub fn backend(&self) -> &Backend {
         &self.backend
     }
 
+    /// Get a mutable reference to the currently running backend, e.g to add
+    /// virtual outputs to the headless backend.
+    pub fn backend_mut(&mut self) -> &mut Backend {
+        &mut self.backend
+    }
+
+    /// Switch to another virtual terminal, e.g when the user presses
+    /// Ctrl+Alt+F2.
+    ///
+    /// This only works on the DRM backend, nested backends return
+    /// `SessionError::NoSession`.
+    pub fn change_vt(&mut self, vt: u32) -> Result<(), SessionError> {
+        match self.backend.session() {
+            Some(mut session) => session.change_vt(vt),
+            None => Err(SessionError::NoSession)
+        }
+    }
+
     /// Saves the panic error information in the compositor, to be re-thrown
     /// later when we are out of the C callback stack.
     pub(crate) fn save_panic_error(&mut self, error: Box<Any + Send>) {
@@ -610,15 +1466,49 @@ impl CompositorHandle {
             Err(err) => panic::resume_unwind(err)
         }
     }
+
+    /// Run a function on the referenced `Compositor`, if it still exists.
+    ///
+    /// Unlike `run` this doesn't panic when the `Compositor` is already
+    /// borrowed, it returns `HandleErr::AlreadyBorrowed` without calling the
+    /// function.
+    pub fn try_run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut Compositor) -> R
+    {
+        handle::try_run(self, runner)
+    }
+}
+
+impl Handle for CompositorHandle {
+    type Target = Compositor;
+    type Upgraded = &'static mut Compositor;
+
+    unsafe fn acquire(&self) -> HandleResult<&'static mut Compositor> {
+        handle::check_lock(&self.handle)?;
+        self.upgrade().map(|compositor| &mut *(compositor as *mut Compositor))
+    }
+
+    unsafe fn release(&self, _upgraded: &'static mut Compositor) {
+        handle::unlock(&self.handle)
+    }
 }
 
 /// Terminates the compositor and execute any user clean up code.
+///
+/// This is the same as `terminate_with(0)`.
 pub fn terminate() {
+    terminate_with(0)
+}
+
+/// Terminates the compositor with the exit code and execute any user clean
+/// up code.
+///
+/// See `Compositor::terminate_with`.
+pub fn terminate_with(code: i32) {
     unsafe {
         if COMPOSITOR_PTR != 0 as _ {
             let compositor = &mut *COMPOSITOR_PTR;
-            compositor.terminate();
-            compositor.user_terminate.map(|f| f());
+            compositor.terminate_with(code);
         }
     }
 }
@@ -8,6 +8,9 @@ use std::fmt;
 pub type HandleResult<T> = Result<T, HandleErr>;
 
 /// The types of ways upgrading a handle can fail.
+///
+/// This implements `Error`, so it can be propagated with `?` into a
+/// `Box<Error>`.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum HandleErr {
     /// Attempting a handle that already has a mutable borrow to its
@@ -36,3 +39,239 @@ impl Error for HandleErr {
         }
     }
 }
+
+/// The ways configuring an input device through libinput can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum InputConfigError {
+    /// The input device is not backed by libinput, so it can't be configured.
+    NotLibinput,
+    /// The device does not support this configuration option.
+    Unsupported,
+    /// The configuration value is invalid for this device.
+    Invalid
+}
+
+impl fmt::Display for InputConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use InputConfigError::*;
+        match *self {
+            NotLibinput => write!(f, "NotLibinput"),
+            Unsupported => write!(f, "Unsupported"),
+            Invalid => write!(f, "Invalid")
+        }
+    }
+}
+
+impl Error for InputConfigError {
+    fn description(&self) -> &str {
+        use InputConfigError::*;
+        match *self {
+            NotLibinput => "Input device is not a libinput device",
+            Unsupported => "Configuration is not supported by the input device",
+            Invalid => "Configuration value is invalid for the input device"
+        }
+    }
+}
+
+/// The ways using the session of the backend can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SessionError {
+    /// The backend doesn't control a session, e.g because it is nested in
+    /// another Wayland compositor or X server.
+    NoSession,
+    /// The session could not switch to the virtual terminal.
+    ChangeVtFailed
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SessionError::*;
+        match *self {
+            NoSession => write!(f, "NoSession"),
+            ChangeVtFailed => write!(f, "ChangeVtFailed")
+        }
+    }
+}
+
+impl Error for SessionError {
+    fn description(&self) -> &str {
+        use SessionError::*;
+        match *self {
+            NoSession => "Backend does not control a session",
+            ChangeVtFailed => "Could not switch to the virtual terminal"
+        }
+    }
+}
+
+/// The ways setting the mode of an `Output` can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ModeSetError {
+    /// The backend rejected the mode.
+    Rejected,
+    /// The output is being destroyed, so its mode can't be changed.
+    Destroyed
+}
+
+impl fmt::Display for ModeSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ModeSetError::*;
+        match *self {
+            Rejected => write!(f, "Rejected"),
+            Destroyed => write!(f, "Destroyed")
+        }
+    }
+}
+
+impl Error for ModeSetError {
+    fn description(&self) -> &str {
+        use ModeSetError::*;
+        match *self {
+            Rejected => "Backend rejected the output mode",
+            Destroyed => "Output is being destroyed"
+        }
+    }
+}
+
+/// The ways making an `Output` the current rendering context with
+/// `Output::acquire` can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AcquireError {
+    /// The output is being destroyed, so it can't be rendered to.
+    Destroyed,
+    /// The backend could not make the output the current rendering context.
+    MakeCurrentFailed
+}
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use AcquireError::*;
+        match *self {
+            Destroyed => write!(f, "Destroyed"),
+            MakeCurrentFailed => write!(f, "MakeCurrentFailed")
+        }
+    }
+}
+
+impl Error for AcquireError {
+    fn description(&self) -> &str {
+        use AcquireError::*;
+        match *self {
+            Destroyed => "Output is being destroyed",
+            MakeCurrentFailed => "Could not make the output the current rendering context"
+        }
+    }
+}
+
+/// The ways setting the gamma ramps of an `Output` can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GammaError {
+    /// The output does not support setting the gamma.
+    Unsupported,
+    /// A gamma ramp did not have the length the output expects.
+    LengthMismatch { expected: usize, actual: usize }
+}
+
+impl fmt::Display for GammaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use GammaError::*;
+        match *self {
+            Unsupported => write!(f, "Unsupported"),
+            LengthMismatch { expected, actual } => {
+                write!(f, "LengthMismatch: expected {}, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl Error for GammaError {
+    fn description(&self) -> &str {
+        use GammaError::*;
+        match *self {
+            Unsupported => "Output does not support gamma control",
+            LengthMismatch { .. } => "Gamma ramp length does not match the output gamma size"
+        }
+    }
+}
+
+/// The ways looking up where an `Output` is in an `OutputLayout` can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputLayoutError {
+    /// The output has been destroyed.
+    OutputDropped,
+    /// The output is alive, but it isn't part of the layout.
+    NotInLayout
+}
+
+impl fmt::Display for OutputLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use OutputLayoutError::*;
+        match *self {
+            OutputDropped => write!(f, "OutputDropped"),
+            NotInLayout => write!(f, "NotInLayout")
+        }
+    }
+}
+
+impl Error for OutputLayoutError {
+    fn description(&self) -> &str {
+        use OutputLayoutError::*;
+        match *self {
+            OutputDropped => "Output has already been dropped",
+            NotInLayout => "Output is not in the output layout"
+        }
+    }
+}
+
+/// The ways opening the Wayland socket of a compositor can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SocketError {
+    /// The name given to `CompositorBuilder::socket_name` contains a NUL byte.
+    InvalidName,
+    /// The socket could not be opened, e.g because the name is already taken.
+    CouldNotOpen
+}
+
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SocketError::*;
+        match *self {
+            InvalidName => write!(f, "InvalidName"),
+            CouldNotOpen => write!(f, "CouldNotOpen")
+        }
+    }
+}
+
+impl Error for SocketError {
+    fn description(&self) -> &str {
+        use SocketError::*;
+        match *self {
+            InvalidName => "Socket name contains a NUL byte",
+            CouldNotOpen => "Could not open the Wayland socket"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrade(err: HandleErr) -> HandleResult<()> {
+        Err(err)
+    }
+
+    fn propagate(err: HandleErr) -> Result<(), Box<Error>> {
+        upgrade(err)?;
+        Ok(())
+    }
+
+    #[test]
+    fn handle_err_boxes_into_error() {
+        let err = propagate(HandleErr::AlreadyDropped).unwrap_err();
+        assert_eq!(err.to_string(), "AlreadyDropped");
+        assert_eq!(err.description(), "Structure has already been dropped");
+
+        let err: Box<Error> = Box::new(HandleErr::AlreadyBorrowed);
+        assert_eq!(err.to_string(), "AlreadyBorrowed");
+        assert_eq!(err.description(), "Structure is already mutably borrowed");
+    }
+}
@@ -0,0 +1,443 @@
+//! Sources for the Wayland event loop the compositor runs on.
+//!
+//! These let the compositor run its own code from the event loop, e.g to
+//! animate something, to time out an operation, to read from a control
+//! socket or to defer work until the current dispatch is done.
+
+use std::{cmp, panic, ptr, cell::{Cell, RefCell}, os::unix::io::RawFd, rc::{Rc, Weak},
+          time::Duration};
+
+use libc::{self, c_int};
+use wayland_sys::server::{wl_event_loop, wl_event_source, WAYLAND_SERVER_HANDLE};
+
+use errors::{HandleErr, HandleResult};
+use utils::{handle_unwind, ToMS};
+
+bitflags! {
+    /// The events a file descriptor added with `Compositor::add_fd_source`
+    /// is watched for.
+    ///
+    /// `HANGUP` and `ERROR` are always reported, even if they aren't asked
+    /// for.
+    pub struct EventMask: u32 {
+        const READABLE = 0x01;
+        const WRITABLE = 0x02;
+        const HANGUP = 0x04;
+        const ERROR = 0x08;
+    }
+}
+
+/// The state of an event source, shared between the event loop and the
+/// handles to it.
+struct EventSource<F: ?Sized> {
+    source: Cell<*mut wl_event_source>,
+    callback: RefCell<Box<F>>
+}
+
+impl<F: ?Sized> EventSource<F> {
+    /// Add the source with the function that adds it to the event loop.
+    ///
+    /// Returns a dead handle if the source couldn't be added.
+    unsafe fn add<A>(callback: Box<F>, add: A) -> Weak<Self>
+        where A: FnOnce(*mut libc::c_void) -> *mut wl_event_source
+    {
+        let source = Rc::new(EventSource { source: Cell::new(ptr::null_mut()),
+                                           callback: RefCell::new(callback) });
+        let handle = Rc::downgrade(&source);
+        let data = Rc::into_raw(source);
+        let event_source = add(data as *mut libc::c_void);
+        if event_source.is_null() {
+            wlr_log!(WLR_ERROR, "Could not add a source to the event loop");
+            Rc::from_raw(data);
+        } else {
+            (*data).source.set(event_source);
+        }
+        handle
+    }
+
+    /// Get the source passed to a callback by the event loop.
+    ///
+    /// NOTE Rationale for the extra reference:
+    /// The callback may remove the source, which drops the reference owned
+    /// by the event loop while the callback is still running.
+    unsafe fn from_data(data: *mut libc::c_void) -> Rc<Self> {
+        let owned = Rc::from_raw(data as *const Self);
+        let result = owned.clone();
+        Rc::into_raw(owned);
+        result
+    }
+
+    /// Get the source to update, unless it has been removed.
+    fn upgrade(handle: &Weak<Self>) -> HandleResult<Rc<Self>> {
+        let source = handle.upgrade().ok_or(HandleErr::AlreadyDropped)?;
+        if source.source.get().is_null() {
+            // Removed from its own callback, which is still running.
+            return Err(HandleErr::AlreadyDropped)
+        }
+        Ok(source)
+    }
+
+    /// Remove the source from the event loop, freeing it once no callback is
+    /// running anymore.
+    unsafe fn remove(&self) {
+        let source = self.source.replace(ptr::null_mut());
+        if source.is_null() {
+            return
+        }
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_source_remove, source);
+        // Drop the reference owned by the event loop.
+        Rc::from_raw(self as *const Self);
+    }
+}
+
+/// Called by the event loop when a timer expires.
+unsafe extern "C" fn timer_notify(data: *mut libc::c_void) -> c_int {
+    let timer = EventSource::<FnMut()>::from_data(data);
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        if let Ok(mut callback) = timer.callback.try_borrow_mut() {
+            (&mut **callback)()
+        }
+    }));
+    handle_unwind(res);
+    0
+}
+
+/// Called by the event loop when a file descriptor has events.
+unsafe extern "C" fn fd_notify(fd: c_int, mask: u32, data: *mut libc::c_void) -> c_int {
+    let fd_source = EventSource::<FnMut(RawFd, EventMask)>::from_data(data);
+    let mask = EventMask::from_bits_truncate(mask);
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        if let Ok(mut callback) = fd_source.callback.try_borrow_mut() {
+            (&mut **callback)(fd, mask)
+        }
+    }));
+    handle_unwind(res);
+    0
+}
+
+wayland_listener!(IdleCallback, Option<Box<FnMut()>>, [
+    destroy_listener => destroy_notify: |this: &mut IdleCallback, _data: *mut libc::c_void,|
+    unsafe {
+        // The event loop was destroyed before the callback could run.
+        Box::from_raw(this);
+    };
+]);
+
+impl Drop for IdleCallback {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+/// Called by the event loop once it's done dispatching, which also removes
+/// the idle source.
+unsafe extern "C" fn idle_notify(data: *mut libc::c_void) {
+    let mut idle = Box::from_raw(data as *mut IdleCallback);
+    if let Some(mut callback) = idle.data.take() {
+        handle_unwind(panic::catch_unwind(panic::AssertUnwindSafe(|| callback())));
+    }
+}
+
+/// Add a callback to the event loop that runs once it's done dispatching the
+/// current events.
+///
+/// The callback is dropped after it runs, or when the event loop is
+/// destroyed if that happens first.
+pub(crate) unsafe fn add_idle(event_loop: *mut wl_event_loop, callback: Box<FnMut()>) {
+    let mut idle = IdleCallback::new(Some(callback));
+    ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                  wl_event_loop_add_destroy_listener,
+                  event_loop,
+                  idle.destroy_listener() as *mut _ as _);
+    let data = Box::into_raw(idle);
+    let source = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                               wl_event_loop_add_idle,
+                               event_loop,
+                               idle_notify,
+                               data as *mut libc::c_void);
+    if source.is_null() {
+        wlr_log!(WLR_ERROR, "Could not add an idle callback to the event loop");
+        Box::from_raw(data);
+    }
+}
+
+/// A handle to a timer added with `Compositor::add_timer`.
+///
+/// The timer fires once, it can be armed again with `reset` (e.g from its own
+/// callback through a clone of the handle).
+///
+/// By default the timer keeps existing when the handle is dropped, use
+/// `remove_on_drop` to change that.
+pub struct TimerHandle {
+    timer: Weak<EventSource<FnMut()>>,
+    remove_on_drop: bool
+}
+
+impl TimerHandle {
+    /// Add a timer to the event loop that calls the callback after the
+    /// delay.
+    pub(crate) unsafe fn new(event_loop: *mut wl_event_loop,
+                             delay: Duration,
+                             callback: Box<FnMut()>)
+                             -> Self {
+        let timer = EventSource::add(callback, |data| {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_loop_add_timer,
+                          event_loop,
+                          timer_notify,
+                          data)
+        });
+        let handle = TimerHandle { timer,
+                                   remove_on_drop: false };
+        // The handle is dead if the timer couldn't be added.
+        handle.reset(delay).ok();
+        handle
+    }
+
+    /// Determines if the timer still exists, i.e it hasn't been cancelled.
+    pub fn is_alive(&self) -> bool {
+        EventSource::upgrade(&self.timer).is_ok()
+    }
+
+    /// Remove the timer from the event loop.
+    ///
+    /// The callback is dropped once it's not running anymore.
+    pub fn cancel(&self) {
+        if let Some(timer) = self.timer.upgrade() {
+            unsafe { timer.remove() }
+        }
+    }
+
+    /// Arm the timer again, so it fires after the delay instead of when it
+    /// was going to.
+    ///
+    /// A delay of zero fires it as soon as possible.
+    pub fn reset(&self, delay: Duration) -> HandleResult<()> {
+        let timer = EventSource::upgrade(&self.timer)?;
+        // NOTE A timeout of zero disarms the timer, so round it up.
+        let ms = cmp::max(delay.to_ms(), 1);
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_source_timer_update,
+                          timer.source.get(),
+                          ms as c_int);
+        }
+        Ok(())
+    }
+
+    /// Decide whether the timer is cancelled when this handle is dropped.
+    ///
+    /// Clones of the handle don't inherit this.
+    pub fn remove_on_drop(mut self, remove_on_drop: bool) -> Self {
+        self.remove_on_drop = remove_on_drop;
+        self
+    }
+}
+
+impl Clone for TimerHandle {
+    fn clone(&self) -> Self {
+        TimerHandle { timer: self.timer.clone(),
+                      remove_on_drop: false }
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            self.cancel()
+        }
+    }
+}
+
+/// A handle to a file descriptor watched with `Compositor::add_fd_source`.
+///
+/// The file descriptor is not closed when the source is removed.
+///
+/// By default the source keeps existing when the handle is dropped, use
+/// `remove_on_drop` to change that.
+pub struct FdSourceHandle {
+    fd_source: Weak<EventSource<FnMut(RawFd, EventMask)>>,
+    remove_on_drop: bool
+}
+
+impl FdSourceHandle {
+    /// Add a source to the event loop that calls the callback whenever the
+    /// file descriptor has one of the events in the mask.
+    pub(crate) unsafe fn new(event_loop: *mut wl_event_loop,
+                             fd: RawFd,
+                             mask: EventMask,
+                             callback: Box<FnMut(RawFd, EventMask)>)
+                             -> Self {
+        let fd_source = EventSource::add(callback, |data| {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_loop_add_fd,
+                          event_loop,
+                          fd,
+                          mask.bits(),
+                          fd_notify,
+                          data)
+        });
+        FdSourceHandle { fd_source,
+                         remove_on_drop: false }
+    }
+
+    /// Determines if the source still exists, i.e it hasn't been removed.
+    pub fn is_alive(&self) -> bool {
+        EventSource::upgrade(&self.fd_source).is_ok()
+    }
+
+    /// Stop watching the file descriptor.
+    ///
+    /// The callback is dropped once it's not running anymore.
+    pub fn remove(&self) {
+        if let Some(fd_source) = self.fd_source.upgrade() {
+            unsafe { fd_source.remove() }
+        }
+    }
+
+    /// Change the events the file descriptor is watched for.
+    pub fn set_mask(&self, mask: EventMask) -> HandleResult<()> {
+        let fd_source = EventSource::upgrade(&self.fd_source)?;
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_source_fd_update,
+                          fd_source.source.get(),
+                          mask.bits());
+        }
+        Ok(())
+    }
+
+    /// Decide whether the source is removed when this handle is dropped.
+    ///
+    /// Clones of the handle don't inherit this.
+    pub fn remove_on_drop(mut self, remove_on_drop: bool) -> Self {
+        self.remove_on_drop = remove_on_drop;
+        self
+    }
+}
+
+impl Clone for FdSourceHandle {
+    fn clone(&self) -> Self {
+        FdSourceHandle { fd_source: self.fd_source.clone(),
+                         remove_on_drop: false }
+    }
+}
+
+impl Drop for FdSourceHandle {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            self.remove()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An event loop that isn't attached to a display.
+    struct EventLoop(*mut wl_event_loop);
+
+    impl EventLoop {
+        fn new() -> Self {
+            let event_loop = unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_loop_create,) };
+            assert!(!event_loop.is_null());
+            EventLoop(event_loop)
+        }
+
+        /// Dispatch the ready sources, waiting at most `timeout` milliseconds
+        /// for one.
+        fn dispatch(&self, timeout: c_int) {
+            unsafe {
+                ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_loop_dispatch, self.0, timeout);
+            }
+        }
+    }
+
+    impl Drop for EventLoop {
+        fn drop(&mut self) {
+            unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_loop_destroy, self.0) }
+        }
+    }
+
+    /// A callback counting how many times it ran.
+    fn counter() -> (Rc<Cell<u32>>, Box<FnMut()>) {
+        let count = Rc::new(Cell::new(0));
+        let callback_count = count.clone();
+        (count, Box::new(move || callback_count.set(callback_count.get() + 1)))
+    }
+
+    #[test]
+    fn timers_fire_once_armed() {
+        let event_loop = EventLoop::new();
+        let (count, callback) = counter();
+        let timer = unsafe { TimerHandle::new(event_loop.0, Duration::from_millis(1), callback) };
+        assert!(timer.is_alive());
+        event_loop.dispatch(1000);
+        assert_eq!(count.get(), 1);
+        // It stays around to be armed again.
+        assert!(timer.is_alive());
+        event_loop.dispatch(20);
+        assert_eq!(count.get(), 1);
+        timer.reset(Duration::from_millis(1)).unwrap();
+        event_loop.dispatch(1000);
+        assert_eq!(count.get(), 2);
+        timer.cancel();
+        assert!(!timer.is_alive());
+    }
+
+    #[test]
+    fn timers_are_removed_on_drop() {
+        let event_loop = EventLoop::new();
+        let (count, callback) = counter();
+        let timer = unsafe { TimerHandle::new(event_loop.0, Duration::from_millis(1), callback) };
+        let clone = timer.clone();
+        drop(timer.remove_on_drop(true));
+        assert!(!clone.is_alive());
+        // The callback was dropped along with the timer.
+        assert_eq!(Rc::strong_count(&count), 1);
+        event_loop.dispatch(20);
+        assert_eq!(count.get(), 0);
+    }
+
+    #[test]
+    fn timers_outlive_their_handles_by_default() {
+        let event_loop = EventLoop::new();
+        let (count, callback) = counter();
+        let timer = unsafe { TimerHandle::new(event_loop.0, Duration::from_millis(1), callback) };
+        let clone = timer.clone().remove_on_drop(true);
+        drop(timer);
+        event_loop.dispatch(1000);
+        assert_eq!(count.get(), 1);
+        // Dropping a clone that removes on drop still removes it.
+        drop(clone);
+        assert_eq!(Rc::strong_count(&count), 1);
+    }
+
+    #[test]
+    fn idle_callbacks_run_once() {
+        let event_loop = EventLoop::new();
+        let (count, callback) = counter();
+        unsafe { add_idle(event_loop.0, callback) }
+        assert_eq!(count.get(), 0);
+        event_loop.dispatch(0);
+        assert_eq!(count.get(), 1);
+        assert_eq!(Rc::strong_count(&count), 1);
+        event_loop.dispatch(0);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn idle_callbacks_are_dropped_with_the_event_loop() {
+        let event_loop = EventLoop::new();
+        let (count, callback) = counter();
+        unsafe { add_idle(event_loop.0, callback) }
+        drop(event_loop);
+        assert_eq!(count.get(), 0);
+        assert_eq!(Rc::strong_count(&count), 1);
+    }
+}
@@ -5,5 +5,7 @@ pub mod xdg_shell_events;
 pub mod tablet_tool_events;
 pub mod touch_events;
 pub mod seat_events;
+pub mod switch_events;
 pub mod tablet_pad_events;
 pub mod xwayland_events;
+pub mod output_events;
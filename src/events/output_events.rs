@@ -0,0 +1,51 @@
+//! Events emitted by outputs.
+
+use std::time::Duration;
+
+use wlroots_sys::wlr_output_event_present;
+
+/// Event that triggers when a buffer has been presented on an output.
+#[derive(Debug)]
+pub struct PresentEvent {
+    event: *mut wlr_output_event_present
+}
+
+impl PresentEvent {
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_output_event_present) -> Self {
+        PresentEvent { event }
+    }
+
+    #[cfg(feature = "presentation-time")]
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_output_event_present {
+        self.event
+    }
+
+    /// Get the time at which the buffer was presented, if it is known.
+    pub fn when(&self) -> Option<Duration> {
+        unsafe {
+            let when = (*self.event).when;
+            if when.is_null() {
+                None
+            } else {
+                Some(Duration::new((*when).tv_sec as u64, (*when).tv_nsec as u32))
+            }
+        }
+    }
+
+    /// Get the vertical retrace counter, or 0 if unknown.
+    pub fn seq(&self) -> u32 {
+        unsafe { (*self.event).seq as u32 }
+    }
+
+    /// Get the predicted duration until the next presentation in nanoseconds,
+    /// or 0 if unknown.
+    pub fn refresh(&self) -> i32 {
+        unsafe { (*self.event).refresh }
+    }
+
+    /// Get the `wp_presentation_feedback` flags describing how the buffer
+    /// was presented.
+    pub fn flags(&self) -> u32 {
+        unsafe { (*self.event).flags }
+    }
+}
@@ -6,7 +6,10 @@ use libc::c_double;
 
 pub use wlroots_sys::{wlr_axis_orientation, wlr_axis_source, wlr_button_state};
 use wlroots_sys::{wlr_event_pointer_axis, wlr_event_pointer_button, wlr_event_pointer_motion,
-                  wlr_event_pointer_motion_absolute};
+                  wlr_event_pointer_motion_absolute, wlr_event_pointer_pinch_begin,
+                  wlr_event_pointer_pinch_end, wlr_event_pointer_pinch_update,
+                  wlr_event_pointer_swipe_begin, wlr_event_pointer_swipe_end,
+                  wlr_event_pointer_swipe_update};
 
 // NOTE Taken from linux/input-event-codes.h
 // TODO Find a way to automatically parse and fetch from there.
@@ -53,6 +56,50 @@ pub struct AbsoluteMotionEvent {
     device: InputDevice
 }
 
+/// Event that triggers when a swipe gesture (e.g three fingers moving across
+/// a touchpad) begins.
+#[derive(Debug)]
+pub struct SwipeBeginEvent {
+    event: *mut wlr_event_pointer_swipe_begin,
+    device: InputDevice
+}
+
+/// Event that triggers when the fingers of a swipe gesture move.
+#[derive(Debug)]
+pub struct SwipeUpdateEvent {
+    event: *mut wlr_event_pointer_swipe_update,
+    device: InputDevice
+}
+
+/// Event that triggers when a swipe gesture ends.
+#[derive(Debug)]
+pub struct SwipeEndEvent {
+    event: *mut wlr_event_pointer_swipe_end,
+    device: InputDevice
+}
+
+/// Event that triggers when a pinch gesture (e.g two fingers moving towards
+/// each other on a touchpad) begins.
+#[derive(Debug)]
+pub struct PinchBeginEvent {
+    event: *mut wlr_event_pointer_pinch_begin,
+    device: InputDevice
+}
+
+/// Event that triggers when the fingers of a pinch gesture move.
+#[derive(Debug)]
+pub struct PinchUpdateEvent {
+    event: *mut wlr_event_pointer_pinch_update,
+    device: InputDevice
+}
+
+/// Event that triggers when a pinch gesture ends.
+#[derive(Debug)]
+pub struct PinchEndEvent {
+    event: *mut wlr_event_pointer_pinch_end,
+    device: InputDevice
+}
+
 impl ButtonEvent {
     /// Constructs a `ButtonEvent` from the raw event pointer.
     pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_button) -> Self {
@@ -145,6 +192,16 @@ impl MotionEvent {
     pub fn delta(&self) -> (f64, f64) {
         unsafe { ((*self.event).delta_x, (*self.event).delta_y) }
     }
+
+    /// Get the change from the last positional value, before any pointer
+    /// acceleration was applied.
+    ///
+    /// Returned in (x, y) form. The field was added to wlroots along with the
+    /// relative pointer protocol, so this needs the `relative-pointer` feature.
+    #[cfg(feature = "relative-pointer")]
+    pub fn unaccel_delta(&self) -> (f64, f64) {
+        unsafe { ((*self.event).unaccel_dx, (*self.event).unaccel_dy) }
+    }
 }
 
 impl AbsoluteMotionEvent {
@@ -169,3 +226,168 @@ impl AbsoluteMotionEvent {
         &self.device
     }
 }
+
+impl SwipeBeginEvent {
+    /// Constructs a `SwipeBeginEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_swipe_begin) -> Self {
+        SwipeBeginEvent { device: InputDevice::from_ptr((*event).device),
+                          event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Get the number of fingers used in the gesture.
+    pub fn fingers(&self) -> u32 {
+        unsafe { (*self.event).fingers }
+    }
+}
+
+impl SwipeUpdateEvent {
+    /// Constructs a `SwipeUpdateEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_swipe_update) -> Self {
+        SwipeUpdateEvent { device: InputDevice::from_ptr((*event).device),
+                           event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Get the number of fingers used in the gesture.
+    pub fn fingers(&self) -> u32 {
+        unsafe { (*self.event).fingers }
+    }
+
+    /// Get how far the center of the fingers moved since the last event.
+    ///
+    /// Returned in (x, y) form.
+    pub fn delta(&self) -> (f64, f64) {
+        unsafe { ((*self.event).dx, (*self.event).dy) }
+    }
+}
+
+impl SwipeEndEvent {
+    /// Constructs a `SwipeEndEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_swipe_end) -> Self {
+        SwipeEndEvent { device: InputDevice::from_ptr((*event).device),
+                        event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Whether the gesture was cancelled instead of completed,
+    /// in which case its effects should be undone.
+    pub fn cancelled(&self) -> bool {
+        unsafe { (*self.event).cancelled }
+    }
+}
+
+impl PinchBeginEvent {
+    /// Constructs a `PinchBeginEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_pinch_begin) -> Self {
+        PinchBeginEvent { device: InputDevice::from_ptr((*event).device),
+                          event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Get the number of fingers used in the gesture.
+    pub fn fingers(&self) -> u32 {
+        unsafe { (*self.event).fingers }
+    }
+}
+
+impl PinchUpdateEvent {
+    /// Constructs a `PinchUpdateEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_pinch_update) -> Self {
+        PinchUpdateEvent { device: InputDevice::from_ptr((*event).device),
+                           event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Get the number of fingers used in the gesture.
+    pub fn fingers(&self) -> u32 {
+        unsafe { (*self.event).fingers }
+    }
+
+    /// Get how far the center of the fingers moved since the last event.
+    ///
+    /// Returned in (x, y) form.
+    pub fn delta(&self) -> (f64, f64) {
+        unsafe { ((*self.event).dx, (*self.event).dy) }
+    }
+
+    /// Get the absolute scale compared to the start of the gesture,
+    /// e.g `2.0` when the fingers are twice as far apart as they were.
+    pub fn scale(&self) -> f64 {
+        unsafe { (*self.event).scale }
+    }
+
+    /// Get the relative angle in degrees clockwise since the last event.
+    pub fn rotation(&self) -> f64 {
+        unsafe { (*self.event).rotation }
+    }
+}
+
+impl PinchEndEvent {
+    /// Constructs a `PinchEndEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_pointer_pinch_end) -> Self {
+        PinchEndEvent { device: InputDevice::from_ptr((*event).device),
+                        event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Whether the gesture was cancelled instead of completed,
+    /// in which case its effects should be undone.
+    pub fn cancelled(&self) -> bool {
+        unsafe { (*self.event).cancelled }
+    }
+}
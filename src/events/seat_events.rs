@@ -1,6 +1,6 @@
-use wlroots_sys::wlr_seat_pointer_request_set_cursor_event;
+use wlroots_sys::{wlr_seat_pointer_request_set_cursor_event, wlr_seat_request_start_drag_event};
 
-use {SeatClient, SurfaceHandle};
+use {Drag, SeatClient, SurfaceHandle};
 
 #[derive(Debug)]
 pub struct SetCursorEvent {
@@ -37,3 +37,37 @@ impl SetCursorEvent {
         unsafe { ((*self.event).hotspot_x, (*self.event).hotspot_y) }
     }
 }
+
+/// Event that triggers when a client requests to start a drag-and-drop operation.
+#[derive(Debug)]
+pub struct RequestStartDragEvent {
+    event: *mut wlr_seat_request_start_drag_event
+}
+
+impl RequestStartDragEvent {
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_seat_request_start_drag_event) -> Self {
+        RequestStartDragEvent { event }
+    }
+
+    /// Get the drag that the client wants to start.
+    pub fn drag(&self) -> Drag {
+        unsafe { Drag::from_ptr((*self.event).drag) }
+    }
+
+    /// Get the surface the drag was started from.
+    pub fn origin(&self) -> Option<SurfaceHandle> {
+        unsafe {
+            let surface = (*self.event).origin;
+            if surface.is_null() {
+                None
+            } else {
+                Some(SurfaceHandle::from_ptr(surface))
+            }
+        }
+    }
+
+    /// Get the serial of the input event that started the drag.
+    pub fn serial(&self) -> u32 {
+        unsafe { (*self.event).serial }
+    }
+}
@@ -0,0 +1,82 @@
+//! Events for switch devices, such as the lid of a laptop.
+
+use wlroots_sys::{wlr_event_switch_toggle, wlr_switch_state, wlr_switch_type};
+
+use InputDevice;
+
+/// The kinds of switches.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SwitchType {
+    /// The lid of a laptop.
+    Lid,
+    /// The switch of a convertible laptop that is on in tablet mode.
+    TabletMode
+}
+
+impl From<wlr_switch_type> for SwitchType {
+    fn from(switch_type: wlr_switch_type) -> Self {
+        use wlroots_sys::wlr_switch_type::*;
+        match switch_type {
+            WLR_SWITCH_TYPE_LID => SwitchType::Lid,
+            WLR_SWITCH_TYPE_TABLET_MODE => SwitchType::TabletMode
+        }
+    }
+}
+
+/// The state a switch was put in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SwitchState {
+    Off,
+    On,
+    /// The switch was flipped, but the device doesn't say which way.
+    Toggle
+}
+
+impl From<wlr_switch_state> for SwitchState {
+    fn from(switch_state: wlr_switch_state) -> Self {
+        use wlroots_sys::wlr_switch_state::*;
+        match switch_state {
+            WLR_SWITCH_STATE_OFF => SwitchState::Off,
+            WLR_SWITCH_STATE_ON => SwitchState::On,
+            WLR_SWITCH_STATE_TOGGLE => SwitchState::Toggle
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Event that is triggered when a switch is flipped, e.g. when the lid
+/// of a laptop is closed.
+pub struct SwitchEvent {
+    event: *mut wlr_event_switch_toggle,
+    device: InputDevice
+}
+
+impl SwitchEvent {
+    /// Constructs a `SwitchEvent` from a raw event pointer.
+    pub(crate) unsafe fn from_ptr(event: *mut wlr_event_switch_toggle) -> Self {
+        SwitchEvent { device: InputDevice::from_ptr((*event).device),
+                      event }
+    }
+
+    /// Get the device this event refers to.
+    pub fn device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Get the timestamp of this event.
+    pub fn time_msec(&self) -> u32 {
+        unsafe { (*self.event).time_msec }
+    }
+
+    /// Get what kind of switch was toggled, i.e a lid or a tablet mode switch.
+    pub fn switch_type(&self) -> SwitchType {
+        unsafe { (*self.event).switch_type.into() }
+    }
+
+    /// Get the new state of the switch.
+    ///
+    /// For a lid switch `SwitchState::On` means the lid is closed.
+    pub fn switch_state(&self) -> SwitchState {
+        unsafe { (*self.event).switch_state.into() }
+    }
+}
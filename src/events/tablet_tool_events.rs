@@ -16,11 +16,13 @@ pub struct ProximityEvent {
     event: *mut wlr_event_tablet_tool_proximity
 }
 
+#[derive(Debug)]
 /// Event that is triggered when a tablet tool tip event occurs.
 pub struct TipEvent {
     event: *mut wlr_event_tablet_tool_tip
 }
 
+#[derive(Debug)]
 /// Event that is triggered when a tablet tool button event occurs.
 pub struct ButtonEvent {
     event: *mut wlr_event_tablet_tool_button
@@ -35,6 +37,10 @@ impl AxisEvent {
         unsafe { (*self.event).time_msec }
     }
 
+    /// Gets which axes changed in this event.
+    ///
+    /// The values of the other axes are left over from previous events
+    /// and should not be relied on.
     pub fn updated_axes(&self) -> TabletToolAxis {
         unsafe { TabletToolAxis::from_bits_truncate((*self.event).updated_axes) }
     }
@@ -46,15 +52,20 @@ impl AxisEvent {
         unsafe { ((*self.event).x, (*self.event).y) }
     }
 
+    /// Gets how hard the tool is pressed against the tablet,
+    /// normalized to the range `[0, 1]`.
     pub fn pressure(&self) -> f64 {
         unsafe { (*self.event).pressure }
     }
 
+    /// Gets how far the tool is from the tablet, normalized to the
+    /// range `[0, 1]`. This is `0` while the tip touches the tablet.
     pub fn distance(&self) -> f64 {
         unsafe { (*self.event).distance }
     }
 
-    /// Gets the tilt of the event.
+    /// Gets the tilt of the tool away from the perpendicular in degrees,
+    /// in the range `[-90, 90]`.
     ///
     /// Return value is in (x, y) format.
     pub fn tilt(&self) -> (f64, f64) {
@@ -3,9 +3,9 @@
 use wlroots_sys::{wlr_xdg_toplevel_move_event, wlr_xdg_toplevel_resize_event,
                   wlr_xdg_toplevel_set_fullscreen_event,
                   wlr_xdg_toplevel_show_window_menu_event};
-use utils::Edges;
+use utils::{Edges, ResizeEdge};
 
-use {OutputHandle, XdgShellSurfaceHandle};
+use {OutputHandle, SeatClient, XdgShellSurfaceHandle};
 
 /// Event that triggers when the surface has been moved in coordinate space.
 #[derive(Debug, PartialEq, Eq)]
@@ -42,8 +42,12 @@ impl MoveEvent {
         unsafe { XdgShellSurfaceHandle::from_ptr((*self.event).surface) }
     }
 
-    // TODO Get seat client
+    /// Get the seat client of the seat that initiated the request.
+    pub fn seat_client<'seat>(&'seat self) -> SeatClient<'seat> {
+        unsafe { SeatClient::from_ptr((*self.event).seat) }
+    }
 
+    /// Get the serial of the input event that initiated the request.
     pub fn serial(&self) -> u32 {
         unsafe { (*self.event).serial }
     }
@@ -59,12 +63,17 @@ impl ResizeEvent {
         unsafe { XdgShellSurfaceHandle::from_ptr((*self.event).surface) }
     }
 
-    // TODO Get seat client
+    /// Get the seat client of the seat that initiated the request.
+    pub fn seat_client<'seat>(&'seat self) -> SeatClient<'seat> {
+        unsafe { SeatClient::from_ptr((*self.event).seat) }
+    }
 
+    /// Get the serial of the input event that initiated the request.
     pub fn serial(&self) -> u32 {
         unsafe { (*self.event).serial }
     }
 
+    /// Get the raw edges that are being dragged.
     pub fn edges(&self) -> Edges {
         unsafe {
             let edges_bits = (*self.event).edges;
@@ -74,6 +83,14 @@ impl ResizeEvent {
             }
         }
     }
+
+    /// Get the edge or corner that is being dragged.
+    ///
+    /// Returns `None` if the client sent no edges or a nonsensical
+    /// combination of them.
+    pub fn edge(&self) -> Option<ResizeEdge> {
+        ResizeEdge::from_edges(self.edges())
+    }
 }
 
 impl SetFullscreenEvent {
@@ -108,8 +125,12 @@ impl ShowWindowMenuEvent {
         unsafe { XdgShellSurfaceHandle::from_ptr((*self.event).surface) }
     }
 
-    // TODO seat client
+    /// Get the seat client of the seat that initiated the request.
+    pub fn seat_client<'seat>(&'seat self) -> SeatClient<'seat> {
+        unsafe { SeatClient::from_ptr((*self.event).seat) }
+    }
 
+    /// Get the serial of the input event that initiated the request.
     pub fn serial(&self) -> u32 {
         unsafe { (*self.event).serial }
     }
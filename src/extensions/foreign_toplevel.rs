@@ -0,0 +1,212 @@
+//! Support for the foreign toplevel management protocol.
+//!
+//! Taskbars, docks and window switchers use this to list the open windows
+//! and to ask the compositor to activate, close, maximize or minimize them.
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, wl_display as wl_server_display,
+                          WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wl_display, wlr_foreign_toplevel_handle_v1,
+                  wlr_foreign_toplevel_handle_v1_activated_event,
+                  wlr_foreign_toplevel_handle_v1_create, wlr_foreign_toplevel_handle_v1_destroy,
+                  wlr_foreign_toplevel_handle_v1_maximized_event,
+                  wlr_foreign_toplevel_handle_v1_minimized_event,
+                  wlr_foreign_toplevel_handle_v1_output_enter,
+                  wlr_foreign_toplevel_handle_v1_output_leave,
+                  wlr_foreign_toplevel_handle_v1_set_activated,
+                  wlr_foreign_toplevel_handle_v1_set_app_id,
+                  wlr_foreign_toplevel_handle_v1_set_maximized,
+                  wlr_foreign_toplevel_handle_v1_set_minimized,
+                  wlr_foreign_toplevel_handle_v1_set_title, wlr_foreign_toplevel_manager_v1,
+                  wlr_foreign_toplevel_manager_v1_create, wlr_foreign_toplevel_manager_v1_destroy};
+
+use {Output, SeatHandle};
+use compositor::{compositor_handle, CompositorHandle};
+use utils::safe_as_cstring;
+
+/// Handles requests from clients (e.g a taskbar) about a toplevel.
+///
+/// None of the requests change the state of the toplevel by themselves, the
+/// compositor has to act on them and then update the `ForeignToplevel`.
+pub trait ForeignToplevelHandler {
+    /// Called when a client wants the toplevel to be focused on the seat.
+    fn request_activate(&mut self, CompositorHandle, SeatHandle) {}
+
+    /// Called when a client wants the toplevel to be closed.
+    fn request_close(&mut self, CompositorHandle) {}
+
+    /// Called when a client wants the toplevel to be maximized or
+    /// unmaximized.
+    fn request_maximize(&mut self, CompositorHandle, bool) {}
+
+    /// Called when a client wants the toplevel to be minimized or
+    /// unminimized.
+    fn request_minimize(&mut self, CompositorHandle, bool) {}
+}
+
+wayland_listener!(ForeignToplevelListener,
+                  (*mut wlr_foreign_toplevel_handle_v1, Box<ForeignToplevelHandler>), [
+    request_activate_listener => request_activate_notify: |this: &mut ForeignToplevelListener,
+                                                           data: *mut libc::c_void,|
+    unsafe {
+        let event = data as *mut wlr_foreign_toplevel_handle_v1_activated_event;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.1.request_activate(compositor, SeatHandle::from_ptr((*event).seat));
+    };
+    request_close_listener => request_close_notify: |this: &mut ForeignToplevelListener,
+                                                     _data: *mut libc::c_void,|
+    unsafe {
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.1.request_close(compositor);
+    };
+    request_maximize_listener => request_maximize_notify: |this: &mut ForeignToplevelListener,
+                                                           data: *mut libc::c_void,|
+    unsafe {
+        let event = data as *mut wlr_foreign_toplevel_handle_v1_maximized_event;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.1.request_maximize(compositor, (*event).maximized);
+    };
+    request_minimize_listener => request_minimize_notify: |this: &mut ForeignToplevelListener,
+                                                           data: *mut libc::c_void,|
+    unsafe {
+        let event = data as *mut wlr_foreign_toplevel_handle_v1_minimized_event;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.1.request_minimize(compositor, (*event).minimized);
+    };
+]);
+
+/// A toplevel (i.e a window) advertised to clients of the foreign toplevel
+/// management protocol.
+///
+/// The compositor should create one for each of its toplevels (e.g in
+/// `XdgShellHandler::map_request`) and keep its state up to date, clients
+/// are notified of every change. The toplevel is removed from the clients'
+/// lists when this is dropped.
+pub struct ForeignToplevel {
+    listener: Box<ForeignToplevelListener>
+}
+
+impl ForeignToplevel {
+    fn as_ptr(&self) -> *mut wlr_foreign_toplevel_handle_v1 {
+        self.listener.data.0
+    }
+
+    /// Set the title of the toplevel.
+    pub fn set_title(&mut self, title: &str) {
+        let title = safe_as_cstring(title);
+        unsafe { wlr_foreign_toplevel_handle_v1_set_title(self.as_ptr(), title.as_ptr()) }
+    }
+
+    /// Set the app id of the toplevel.
+    pub fn set_app_id(&mut self, app_id: &str) {
+        let app_id = safe_as_cstring(app_id);
+        unsafe { wlr_foreign_toplevel_handle_v1_set_app_id(self.as_ptr(), app_id.as_ptr()) }
+    }
+
+    /// Set whether the toplevel is maximized.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        unsafe { wlr_foreign_toplevel_handle_v1_set_maximized(self.as_ptr(), maximized) }
+    }
+
+    /// Set whether the toplevel is minimized.
+    pub fn set_minimized(&mut self, minimized: bool) {
+        unsafe { wlr_foreign_toplevel_handle_v1_set_minimized(self.as_ptr(), minimized) }
+    }
+
+    /// Set whether the toplevel is activated (i.e focused).
+    pub fn set_activated(&mut self, activated: bool) {
+        unsafe { wlr_foreign_toplevel_handle_v1_set_activated(self.as_ptr(), activated) }
+    }
+
+    /// Tell clients the toplevel is now visible on the output.
+    pub fn output_enter(&mut self, output: &Output) {
+        unsafe { wlr_foreign_toplevel_handle_v1_output_enter(self.as_ptr(), output.as_ptr()) }
+    }
+
+    /// Tell clients the toplevel is no longer visible on the output.
+    pub fn output_leave(&mut self, output: &Output) {
+        unsafe { wlr_foreign_toplevel_handle_v1_output_leave(self.as_ptr(), output.as_ptr()) }
+    }
+}
+
+impl Drop for ForeignToplevel {
+    fn drop(&mut self) {
+        unsafe {
+            // NOTE The listeners are removed before the toplevel is destroyed,
+            // since that frees the signals they are in.
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.listener.request_activate_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.listener.request_close_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.listener.request_maximize_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.listener.request_minimize_listener()).link as *mut _ as _);
+            wlr_foreign_toplevel_handle_v1_destroy(self.as_ptr())
+        }
+    }
+}
+
+/// Manager for the `zwlr_foreign_toplevel_manager_v1` global.
+#[derive(Debug)]
+pub struct ForeignToplevelManager {
+    manager: *mut wlr_foreign_toplevel_manager_v1
+}
+
+impl ForeignToplevelManager {
+    pub(crate) unsafe fn new(display: *mut wl_server_display) -> Option<Self> {
+        let manager_raw = wlr_foreign_toplevel_manager_v1_create(display as *mut wl_display);
+
+        if !manager_raw.is_null() {
+            Some(ForeignToplevelManager { manager: manager_raw })
+        } else {
+            None
+        }
+    }
+
+    /// Advertise a new toplevel to clients.
+    ///
+    /// Requests from clients about the toplevel are passed to the handler.
+    pub fn create_toplevel(&mut self,
+                           handler: Box<ForeignToplevelHandler>)
+                           -> Option<ForeignToplevel> {
+        unsafe {
+            let toplevel = wlr_foreign_toplevel_handle_v1_create(self.manager);
+            if toplevel.is_null() {
+                return None
+            }
+            let mut listener = ForeignToplevelListener::new((toplevel, handler));
+            wl_signal_add(&mut (*toplevel).events.request_activate as *mut _ as _,
+                          listener.request_activate_listener() as _);
+            wl_signal_add(&mut (*toplevel).events.request_close as *mut _ as _,
+                          listener.request_close_listener() as _);
+            wl_signal_add(&mut (*toplevel).events.request_maximize as *mut _ as _,
+                          listener.request_maximize_listener() as _);
+            wl_signal_add(&mut (*toplevel).events.request_minimize as *mut _ as _,
+                          listener.request_minimize_listener() as _);
+            Some(ForeignToplevel { listener })
+        }
+    }
+}
+
+impl Drop for ForeignToplevelManager {
+    fn drop(&mut self) {
+        unsafe { wlr_foreign_toplevel_manager_v1_destroy(self.manager) }
+    }
+}
@@ -0,0 +1,203 @@
+//! Support for the idle protocol (`org_kde_kwin_idle`).
+//!
+//! Clients such as screen lockers use this to be told when the user stops
+//! interacting with a seat. The compositor can also register its own idle
+//! timeouts, e.g to blank the screen or turn off the outputs.
+
+use std::{panic, ptr, cell::RefCell, time::Duration};
+
+use libc::{self, c_int};
+use wayland_sys::server::{signal::wl_signal_add, wl_display as wl_server_display, wl_event_loop,
+                          wl_event_source, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wl_display, wlr_idle, wlr_idle_create, wlr_idle_destroy,
+                  wlr_idle_notify_activity, wlr_idle_set_enabled, wlr_seat};
+
+use Seat;
+use compositor::{compositor_handle, CompositorHandle};
+use utils::{handle_unwind, ToMS};
+
+thread_local! {
+    /// The idle global that is currently advertised, if there is one.
+    ///
+    /// Used to notify activity whenever a seat forwards input to a client.
+    static IDLE_GLOBAL: RefCell<*mut wlr_idle> = RefCell::new(ptr::null_mut());
+}
+
+/// Handles a seat becoming idle.
+pub trait IdleTimeoutHandler {
+    /// Called when there has been no activity on the seat for the length of
+    /// the timeout.
+    fn on_idle(&mut self, CompositorHandle) {}
+
+    /// Called on the first activity on the seat after it went idle.
+    fn on_resume(&mut self, CompositorHandle) {}
+}
+
+pub(crate) struct IdleTimeoutState {
+    seat: *mut wlr_seat,
+    timeout: u32,
+    idle: bool,
+    timer: *mut wl_event_source,
+    handler: Box<IdleTimeoutHandler>
+}
+
+wayland_listener!(IdleTimeout, IdleTimeoutState, [
+    activity_listener => activity_notify: |this: &mut IdleTimeout, data: *mut libc::c_void,|
+    unsafe {
+        let state = &mut this.data;
+        if data as *mut wlr_seat != state.seat {
+            return
+        }
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_event_source_timer_update,
+                      state.timer,
+                      state.timeout as c_int);
+        if state.idle {
+            state.idle = false;
+            let compositor = match compositor_handle() {
+                Some(handle) => handle,
+                None => return
+            };
+            state.handler.on_resume(compositor);
+        }
+    };
+]);
+
+impl Drop for IdleTimeout {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.activity_listener()).link as *mut _ as _);
+            if !self.data.timer.is_null() {
+                ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_source_remove, self.data.timer);
+            }
+        }
+    }
+}
+
+/// Called by the event loop when a timeout expires without any activity.
+///
+/// The timer is removed when the timeout is dropped, so the data pointer is
+/// always valid here.
+unsafe extern "C" fn timeout_notify(data: *mut libc::c_void) -> c_int {
+    let state = &mut (*(data as *mut IdleTimeout)).data;
+    state.idle = true;
+    if let Some(compositor) = compositor_handle() {
+        let handler = &mut state.handler;
+        handle_unwind(panic::catch_unwind(panic::AssertUnwindSafe(|| handler.on_idle(compositor))));
+    }
+    0
+}
+
+/// Manager for the `org_kde_kwin_idle` global.
+///
+/// Activity is automatically reported whenever input is forwarded to a
+/// client through the `Seat` (e.g `Seat::pointer_notify_motion` or
+/// `Seat::keyboard_notify_key`). Input the compositor handles itself should
+/// be reported with `notify_activity`.
+pub struct Idle {
+    idle: *mut wlr_idle,
+    event_loop: *mut wl_event_loop,
+    timeouts: Vec<Box<IdleTimeout>>
+}
+
+impl Idle {
+    pub(crate) unsafe fn new(display: *mut wl_server_display) -> Option<Self> {
+        let idle = wlr_idle_create(display as *mut wl_display);
+        if idle.is_null() {
+            return None
+        }
+        let event_loop = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_event_loop, display);
+        IDLE_GLOBAL.with(|global| *global.borrow_mut() = idle);
+        Some(Idle { idle,
+                    event_loop,
+                    timeouts: Vec::new() })
+    }
+
+    /// Notify the idle timers of the seat that there has been user activity,
+    /// resetting them.
+    pub fn notify_activity(&mut self, seat: &Seat) {
+        unsafe { wlr_idle_notify_activity(self.idle, seat.as_ptr()) }
+    }
+
+    /// Enable or disable the idle timers of the seat.
+    ///
+    /// While disabled the seat never goes idle. This should be done while
+    /// there are idle inhibitors for visible surfaces (see
+    /// `IdleInhibitManagerHandler`).
+    pub fn set_enabled(&mut self, seat: &Seat, enabled: bool) {
+        unsafe {
+            wlr_idle_set_enabled(self.idle, seat.as_ptr(), enabled);
+            for timeout in self.timeouts.iter_mut().filter(|t| t.data.seat == seat.as_ptr()) {
+                let ms = if enabled { timeout.data.timeout } else { 0 };
+                ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                              wl_event_source_timer_update,
+                              timeout.data.timer,
+                              ms as c_int);
+            }
+        }
+    }
+
+    /// Register a timeout that calls `IdleTimeoutHandler::on_idle` once
+    /// there has been no activity on the seat for the given duration.
+    ///
+    /// The timeout lives until `clear_timeouts` is called or the global is
+    /// destroyed.
+    pub fn add_timeout(&mut self,
+                       seat: &Seat,
+                       timeout: Duration,
+                       handler: Box<IdleTimeoutHandler>) {
+        unsafe {
+            let state = IdleTimeoutState { seat: seat.as_ptr(),
+                                           timeout: timeout.to_ms(),
+                                           idle: false,
+                                           timer: ptr::null_mut(),
+                                           handler };
+            let mut idle_timeout = IdleTimeout::new(state);
+            // NOTE The box is never moved out of, so this pointer stays valid
+            // until the timeout is dropped, at which point the timer is removed.
+            let data = &mut *idle_timeout as *mut IdleTimeout as *mut libc::c_void;
+            idle_timeout.data.timer = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                                    wl_event_loop_add_timer,
+                                                    self.event_loop,
+                                                    timeout_notify,
+                                                    data);
+            if idle_timeout.data.timer.is_null() {
+                wlr_log!(WLR_ERROR, "Could not create the idle timer");
+                return
+            }
+            wl_signal_add(&mut (*self.idle).events.activity_notify as *mut _ as _,
+                          idle_timeout.activity_listener() as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_event_source_timer_update,
+                          idle_timeout.data.timer,
+                          idle_timeout.data.timeout as c_int);
+            self.timeouts.push(idle_timeout);
+        }
+    }
+
+    /// Remove all the timeouts added with `add_timeout`.
+    pub fn clear_timeouts(&mut self) {
+        self.timeouts.clear()
+    }
+}
+
+impl Drop for Idle {
+    fn drop(&mut self) {
+        // The timeouts listen to the global, so they must go first.
+        self.timeouts.clear();
+        unsafe {
+            IDLE_GLOBAL.with(|global| *global.borrow_mut() = ptr::null_mut());
+            wlr_idle_destroy(self.idle)
+        }
+    }
+}
+
+/// Report activity on the seat to the idle global, if it is enabled.
+pub(crate) unsafe fn notify_seat_activity(seat: *mut wlr_seat) {
+    let idle = IDLE_GLOBAL.with(|global| *global.borrow());
+    if !idle.is_null() {
+        wlr_idle_notify_activity(idle, seat)
+    }
+}
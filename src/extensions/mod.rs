@@ -1 +1,10 @@
 pub mod server_decoration;
+#[cfg(feature = "xdg-output")]
+pub mod xdg_output;
+#[cfg(feature = "foreign-toplevel")]
+pub mod foreign_toplevel;
+pub mod idle;
+#[cfg(feature = "presentation-time")]
+pub mod presentation;
+#[cfg(feature = "relative-pointer")]
+pub mod relative_pointer;
@@ -0,0 +1,59 @@
+use std::mem;
+
+use wayland_sys::server::wl_display as wl_server_display;
+use wlroots_sys::{wl_display, wlr_backend, wlr_presentation, wlr_presentation_create,
+                  wlr_presentation_destroy, wlr_presentation_event,
+                  wlr_presentation_event_from_output, wlr_presentation_send_surface_presented};
+
+use Surface;
+use events::output_events::PresentEvent;
+
+/// Manager for the `wp_presentation` global.
+///
+/// Clients such as media players use this to find out exactly when their
+/// surfaces were shown, e.g to keep audio and video in sync.
+///
+/// The compositor has to forward the timings of each output's present event
+/// (see `OutputHandler::on_present`) for the surfaces that were shown with
+/// `sample_output_timings`.
+#[derive(Debug)]
+pub struct Presentation {
+    presentation: *mut wlr_presentation
+}
+
+impl Presentation {
+    pub(crate) unsafe fn new(display: *mut wl_server_display,
+                             backend: *mut wlr_backend)
+                             -> Option<Self> {
+        let presentation = wlr_presentation_create(display as *mut wl_display, backend);
+
+        if !presentation.is_null() {
+            Some(Presentation { presentation })
+        } else {
+            None
+        }
+    }
+
+    /// Send the timings of an output's present event to the client of a
+    /// surface that was shown in the presented frame.
+    ///
+    /// This should be called from `OutputHandler::on_present` for each
+    /// surface that was rendered to the output.
+    pub fn sample_output_timings(&mut self, surface: &mut Surface, event: &PresentEvent) {
+        unsafe {
+            // NOTE Rationale for zeroed memory:
+            // * It is completely filled in by wlr_presentation_event_from_output.
+            let mut presentation_event: wlr_presentation_event = mem::zeroed();
+            wlr_presentation_event_from_output(&mut presentation_event, event.as_ptr());
+            wlr_presentation_send_surface_presented(self.presentation,
+                                                    surface.as_ptr(),
+                                                    &mut presentation_event)
+        }
+    }
+}
+
+impl Drop for Presentation {
+    fn drop(&mut self) {
+        unsafe { wlr_presentation_destroy(self.presentation) }
+    }
+}
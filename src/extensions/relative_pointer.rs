@@ -0,0 +1,55 @@
+use wayland_sys::server::wl_display as wl_server_display;
+use wlroots_sys::{wl_display, wlr_relative_pointer_manager_v1,
+                  wlr_relative_pointer_manager_v1_create, wlr_relative_pointer_manager_v1_destroy,
+                  wlr_relative_pointer_manager_v1_send_relative_motion};
+
+use Seat;
+use events::pointer_events::MotionEvent;
+
+/// Manager for the `zwp_relative_pointer_manager_v1` global.
+///
+/// Clients such as games use this to receive the raw motion of the pointer,
+/// which keeps coming even while the pointer is locked with a pointer
+/// constraint or stuck against the edge of the layout.
+#[derive(Debug)]
+pub struct RelativePointerManager {
+    manager: *mut wlr_relative_pointer_manager_v1
+}
+
+impl RelativePointerManager {
+    pub(crate) unsafe fn new(display: *mut wl_server_display) -> Option<Self> {
+        let manager_raw = wlr_relative_pointer_manager_v1_create(display as *mut wl_display);
+
+        if !manager_raw.is_null() {
+            Some(RelativePointerManager { manager: manager_raw })
+        } else {
+            None
+        }
+    }
+
+    /// Send the relative motion of a pointer event to the client the seat's
+    /// pointer is focused on.
+    ///
+    /// Both the accelerated and unaccelerated deltas of the event are sent.
+    /// This should be called for every motion event, including the ones that
+    /// don't move the cursor because of a pointer constraint.
+    pub fn send_relative_motion(&mut self, seat: &Seat, event: &MotionEvent) {
+        let (dx, dy) = event.delta();
+        let (dx_unaccel, dy_unaccel) = event.unaccel_delta();
+        unsafe {
+            wlr_relative_pointer_manager_v1_send_relative_motion(self.manager,
+                                                                 seat.as_ptr(),
+                                                                 event.time_msec() as u64 * 1000,
+                                                                 dx,
+                                                                 dy,
+                                                                 dx_unaccel,
+                                                                 dy_unaccel)
+        }
+    }
+}
+
+impl Drop for RelativePointerManager {
+    fn drop(&mut self) {
+        unsafe { wlr_relative_pointer_manager_v1_destroy(self.manager) }
+    }
+}
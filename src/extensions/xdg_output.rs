@@ -0,0 +1,72 @@
+use std::ptr;
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, wl_display as wl_server_display,
+                          WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wl_display, wlr_xdg_output_manager, wlr_xdg_output_manager_create,
+                  wlr_xdg_output_manager_destroy};
+
+use OutputLayout;
+
+wayland_listener!(XdgOutputManagerListener, *mut wlr_xdg_output_manager, [
+    destroy_listener => destroy_notify: |this: &mut XdgOutputManagerListener,
+                                         _data: *mut libc::c_void,|
+    unsafe {
+        // wlroots destroys the manager along with its output layout (or the
+        // display), so it must not be destroyed again when this is dropped.
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.destroy_listener()).link as *mut _ as _);
+        this.data = ptr::null_mut();
+    };
+]);
+
+/// Manager for the `zxdg_output_manager_v1` global.
+///
+/// Clients use this to learn the logical position and size of each output.
+/// The logical geometry is taken from the `OutputLayout` the manager was
+/// created with, and clients are sent updates whenever that layout changes.
+///
+/// The global is removed when the layout is destroyed.
+pub struct XdgOutputManager {
+    listener: Box<XdgOutputManagerListener>
+}
+
+impl XdgOutputManager {
+    pub(crate) unsafe fn new(display: *mut wl_server_display,
+                             layout: &mut OutputLayout)
+                             -> Option<Self> {
+        let manager_raw = wlr_xdg_output_manager_create(display as *mut wl_display,
+                                                        layout.as_ptr());
+
+        if !manager_raw.is_null() {
+            let mut listener = XdgOutputManagerListener::new(manager_raw);
+            wl_signal_add(&mut (*manager_raw).events.destroy as *mut _ as _,
+                          listener.destroy_listener() as _);
+            Some(XdgOutputManager { listener })
+        } else {
+            None
+        }
+    }
+
+    /// Determines if the global is still advertised, i.e the output layout
+    /// it was created with hasn't been destroyed.
+    pub fn is_alive(&self) -> bool {
+        !self.listener.data.is_null()
+    }
+}
+
+impl Drop for XdgOutputManager {
+    fn drop(&mut self) {
+        let manager = self.listener.data;
+        if manager.is_null() {
+            return
+        }
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.listener.destroy_listener()).link as *mut _ as _);
+            wlr_xdg_output_manager_destroy(manager)
+        }
+    }
+}
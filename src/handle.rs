@@ -0,0 +1,369 @@
+//! Running a callback on several handles at once.
+//!
+//! This is what `handles!` uses to upgrade several handles without nesting
+//! `run` calls.
+
+use std::{fmt, panic, borrow::{Borrow, BorrowMut}, cell::{Cell, RefCell},
+          ops::{Deref, DerefMut}, rc::{Rc, Weak}};
+
+use errors::{HandleErr, HandleResult};
+
+/// A handle to a structure owned by wlroots, e.g an `OutputHandle`.
+pub trait Handle {
+    /// The structure the handle refers to.
+    type Target;
+
+    #[doc(hidden)]
+    type Upgraded: BorrowMut<Self::Target>;
+
+    #[doc(hidden)]
+    unsafe fn acquire(&self) -> HandleResult<Self::Upgraded>;
+
+    #[doc(hidden)]
+    unsafe fn release(&self, upgraded: Self::Upgraded);
+}
+
+/// Checks that the structure behind a handle lock still exists and isn't
+/// borrowed, without taking the lock.
+pub(crate) fn check_lock(handle: &Weak<Cell<bool>>) -> HandleResult<()> {
+    match handle.upgrade() {
+        None => Err(HandleErr::AlreadyDropped),
+        Some(ref check) if check.get() => Err(HandleErr::AlreadyBorrowed),
+        Some(_) => Ok(())
+    }
+}
+
+/// Releases a handle lock taken by `upgrade`.
+///
+/// Logs an error if the lock was cleared while it was held.
+pub(crate) fn unlock(handle: &Weak<Cell<bool>>) {
+    if let Some(check) = handle.upgrade() {
+        if !check.get() {
+            wlr_log!(WLR_ERROR, "Releasing a handle whose mutable lock was already false");
+        }
+        check.set(false)
+    }
+}
+
+/// Shared implementation of the handles' `try_run`.
+///
+/// The lock is checked in `acquire` before anything is upgraded, so once the
+/// runner was called this always returns `Ok`.
+pub(crate) fn try_run<H, F, R>(handle: &H, runner: F) -> HandleResult<R>
+    where H: Handle,
+          F: FnOnce(&mut H::Target) -> R
+{
+    let mut upgraded = unsafe { handle.acquire()? };
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| runner(upgraded.borrow_mut())));
+    unsafe { handle.release(upgraded) };
+    match res {
+        Ok(res) => Ok(res),
+        Err(err) => panic::resume_unwind(err)
+    }
+}
+
+/// Several handles to upgrade at once, see `handles!`.
+pub struct Handles<T>(pub T);
+
+/// A handle upgraded by `Handles::upgrade`, waiting to be released.
+struct Upgraded<'a, H: Handle + 'a> {
+    handle: &'a H,
+    upgraded: H::Upgraded
+}
+
+trait Release {
+    unsafe fn release(self: Box<Self>);
+}
+
+impl<'a, H: Handle + 'a> Release for Upgraded<'a, H> {
+    unsafe fn release(self: Box<Self>) {
+        let Upgraded { handle, upgraded } = *self;
+        handle.release(upgraded)
+    }
+}
+
+/// The handles upgraded together by `Handles::upgrade`.
+///
+/// Shared by their guards, and dropped along with the last one of them.
+struct Releases<'a> {
+    /// The upgraded handles whose guards were dropped, in upgrade order.
+    pending: RefCell<Vec<Option<Box<Release + 'a>>>>
+}
+
+impl<'a> Drop for Releases<'a> {
+    fn drop(&mut self) {
+        for upgraded in self.pending.get_mut().drain(..).rev() {
+            if let Some(upgraded) = upgraded {
+                unsafe { upgraded.release() }
+            }
+        }
+    }
+}
+
+/// A handle upgraded by `Handles::upgrade`, which dereferences to the
+/// structure the handle refers to.
+///
+/// The handles upgraded together are only released once all of their guards
+/// have been dropped, in the reverse of the order they were upgraded in.
+pub struct HandleGuard<'a, H: Handle + 'a> {
+    upgraded: Option<Upgraded<'a, H>>,
+    index: usize,
+    releases: Rc<Releases<'a>>
+}
+
+impl<'a, H: Handle + 'a> HandleGuard<'a, H> {
+    /// Upgrade the handle, to be released along with the other handles
+    /// sharing `releases`.
+    fn new(handle: &'a H, releases: &Rc<Releases<'a>>) -> HandleResult<Self> {
+        let upgraded = unsafe { handle.acquire()? };
+        let mut pending = releases.pending.borrow_mut();
+        pending.push(None);
+        Ok(HandleGuard { upgraded: Some(Upgraded { handle, upgraded }),
+                         index: pending.len() - 1,
+                         releases: releases.clone() })
+    }
+}
+
+impl<'a, H: Handle + 'a> Deref for HandleGuard<'a, H> {
+    type Target = H::Target;
+
+    fn deref(&self) -> &H::Target {
+        match self.upgraded {
+            Some(ref upgraded) => upgraded.upgraded.borrow(),
+            None => unreachable!()
+        }
+    }
+}
+
+impl<'a, H: Handle + 'a> DerefMut for HandleGuard<'a, H> {
+    fn deref_mut(&mut self) -> &mut H::Target {
+        match self.upgraded {
+            Some(ref mut upgraded) => upgraded.upgraded.borrow_mut(),
+            None => unreachable!()
+        }
+    }
+}
+
+impl<'a, H: Handle + 'a> fmt::Debug for HandleGuard<'a, H>
+    where H::Target: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("HandleGuard").field(&**self).finish()
+    }
+}
+
+impl<'a, H: Handle + 'a> Drop for HandleGuard<'a, H> {
+    fn drop(&mut self) {
+        if let Some(upgraded) = self.upgraded.take() {
+            self.releases.pending.borrow_mut()[self.index] = Some(Box::new(upgraded));
+        }
+    }
+}
+
+/// Nests `try_run` calls on the handles, so they are upgraded left to right
+/// and released in reverse order, even if the runner panics.
+macro_rules! nest_try_run {
+    ($runner: ident; $($done: ident),*; ) => {
+        Ok($runner($($done),*))
+    };
+    ($runner: ident; $($done: ident),*; $next: ident $(, $rest: ident)*) => {
+        try_run($next, |$next| nest_try_run!($runner; $($done,)* $next; $($rest),*))
+            .and_then(|res| res)
+    };
+}
+
+macro_rules! impl_handles {
+    ($($handle: ident: $name: ident),+) => {
+        impl<'a, $($handle: Handle + 'a),+> Handles<($(&'a $handle,)+)> {
+            /// Upgrade all of the handles, returning a guard for each of them.
+            ///
+            /// The handles are upgraded left to right. If one of them can't be
+            /// upgraded the ones before it are released again and its error is
+            /// returned. Otherwise they stay upgraded until all of the guards
+            /// are dropped, and are then released in reverse order.
+            pub fn upgrade(self) -> HandleResult<($(HandleGuard<'a, $handle>,)+)> {
+                let ($($name,)+) = self.0;
+                let releases = Rc::new(Releases { pending: RefCell::new(Vec::new()) });
+                Ok(($(HandleGuard::new($name, &releases)?,)+))
+            }
+
+            /// Run a function on the structures all of the handles refer to.
+            ///
+            /// The handles are upgraded left to right. If one of them can't be
+            /// upgraded the ones before it are released again and the function
+            /// is not called. Once it returns they are released in reverse
+            /// order.
+            pub fn run<Func, R>(self, runner: Func) -> HandleResult<R>
+                where Func: FnOnce($(&mut $handle::Target),+) -> R
+            {
+                let ($($name,)+) = self.0;
+                nest_try_run!(runner; ; $($name),+)
+            }
+        }
+    }
+}
+
+impl_handles!(A: a);
+impl_handles!(A: a, B: b);
+impl_handles!(A: a, B: b, C: c);
+impl_handles!(A: a, B: b, C: c, D: d);
+impl_handles!(A: a, B: b, C: c, D: d, E: e);
+impl_handles!(A: a, B: b, C: c, D: d, E: e, F: f);
+
+#[cfg(test)]
+mod tests {
+    use std::{panic, cell::{Cell, RefCell}, rc::{Rc, Weak}};
+
+    use errors::{HandleErr, HandleResult};
+    use super::Handle;
+
+    #[derive(Debug)]
+    struct Thing {
+        id: u32
+    }
+
+    /// A handle to a `Thing` kept alive by the test instead of wlroots.
+    struct ThingHandle {
+        handle: Weak<Cell<bool>>,
+        id: u32
+    }
+
+    impl ThingHandle {
+        unsafe fn upgrade(&self) -> HandleResult<Thing> {
+            let check = self.handle.upgrade().ok_or(HandleErr::AlreadyDropped)?;
+            check.set(true);
+            Ok(Thing { id: self.id })
+        }
+    }
+
+    impl_handle!(ThingHandle => Thing);
+
+    fn thing(id: u32) -> (Rc<Cell<bool>>, ThingHandle) {
+        let liveliness = Rc::new(Cell::new(false));
+        let handle = ThingHandle { handle: Rc::downgrade(&liveliness),
+                                   id };
+        (liveliness, handle)
+    }
+
+    /// A handle that records the id of the `Thing` when it's released.
+    struct LoggedHandle {
+        thing: ThingHandle,
+        log: Rc<RefCell<Vec<u32>>>
+    }
+
+    impl Handle for LoggedHandle {
+        type Target = Thing;
+        type Upgraded = Thing;
+
+        unsafe fn acquire(&self) -> HandleResult<Thing> {
+            self.thing.acquire()
+        }
+
+        unsafe fn release(&self, thing: Thing) {
+            self.log.borrow_mut().push(thing.id);
+            self.thing.release(thing)
+        }
+    }
+
+    fn logged_thing(id: u32, log: &Rc<RefCell<Vec<u32>>>) -> (Rc<Cell<bool>>, LoggedHandle) {
+        let (liveliness, thing) = thing(id);
+        (liveliness, LoggedHandle { thing, log: log.clone() })
+    }
+
+    #[test]
+    fn upgrades_several_handles() {
+        let (a_lock, a) = thing(1);
+        let (b_lock, b) = thing(2);
+        {
+            let (mut a, b) = handles!((a, b)).upgrade().unwrap();
+            assert!(a_lock.get() && b_lock.get());
+            a.id += 10;
+            assert_eq!((a.id, b.id), (11, 2));
+        }
+        assert!(!a_lock.get() && !b_lock.get());
+    }
+
+    #[test]
+    fn guards_are_released_together_in_reverse_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (a_lock, a) = logged_thing(1, &log);
+        let (b_lock, b) = logged_thing(2, &log);
+        let (c_lock, c) = logged_thing(3, &log);
+        let (a_guard, b_guard, c_guard) = handles!((a, b, c)).upgrade().unwrap();
+        drop(a_guard);
+        assert!(a_lock.get());
+        assert!(log.borrow().is_empty());
+        drop(c_guard);
+        drop(b_guard);
+        assert_eq!(*log.borrow(), vec![3, 2, 1]);
+        assert!(!a_lock.get() && !b_lock.get() && !c_lock.get());
+    }
+
+    #[test]
+    fn a_dropped_handle_fails_the_upgrade() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (a_lock, a) = logged_thing(1, &log);
+        let (b_lock, b) = logged_thing(2, &log);
+        let (c_lock, c) = logged_thing(3, &log);
+        drop(b_lock);
+        assert_eq!(handles!((a, b, c)).upgrade().err(), Some(HandleErr::AlreadyDropped));
+        // Only the handle before the dropped one was upgraded, and it was
+        // released again.
+        assert_eq!(*log.borrow(), vec![1]);
+        assert!(!a_lock.get() && !c_lock.get());
+    }
+
+    #[test]
+    fn the_same_handle_is_only_upgraded_once() {
+        let (lock, a) = thing(1);
+        assert_eq!(handles!((a, a)).upgrade().err(), Some(HandleErr::AlreadyBorrowed));
+        assert!(!lock.get());
+    }
+
+    #[test]
+    fn runs_on_several_handles() {
+        let (a_lock, a) = thing(1);
+        let (b_lock, b) = thing(2);
+        let ids = handles!((a, b)).run(|a, b| {
+                                           assert!(a_lock.get() && b_lock.get());
+                                           (a.id, b.id)
+                                       });
+        assert_eq!(ids, Ok((1, 2)));
+        assert!(!a_lock.get() && !b_lock.get());
+    }
+
+    #[test]
+    fn a_dropped_handle_stops_the_run() {
+        let (a_lock, a) = thing(1);
+        let (b_lock, b) = thing(2);
+        drop(b_lock);
+        let mut called = false;
+        assert_eq!(handles!((a, b)).run(|_, _| called = true),
+                   Err(HandleErr::AlreadyDropped));
+        assert_eq!(handles!((b, a)).run(|_, _| called = true),
+                   Err(HandleErr::AlreadyDropped));
+        assert!(!called);
+        // The handle before the dropped one was released again.
+        assert!(!a_lock.get());
+        assert_eq!(b.try_run(|b| b.id), Err(HandleErr::AlreadyDropped));
+        assert_eq!(a.try_run(|a| a.id), Ok(1));
+    }
+
+    #[test]
+    fn the_same_handle_is_only_borrowed_once() {
+        let (lock, a) = thing(1);
+        assert_eq!(handles!((a, a)).run(|_, _| ()), Err(HandleErr::AlreadyBorrowed));
+        assert!(!lock.get());
+    }
+
+    #[test]
+    fn panics_release_the_handles() {
+        let (a_lock, a) = thing(1);
+        let (b_lock, b) = thing(2);
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                                          handles!((a, b)).run(|_, _| panic!("runner"))
+                                      }));
+        assert!(res.is_err());
+        assert!(!a_lock.get() && !b_lock.get());
+    }
+}
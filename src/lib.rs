@@ -20,6 +20,8 @@ mod macros;
 mod manager;
 mod compositor;
 mod errors;
+mod event_loop;
+mod handle;
 pub mod events;
 pub mod types;
 pub mod extensions;
@@ -29,18 +31,31 @@ mod xwayland;
 mod backend;
 
 pub use self::backend::*;
-pub use self::compositor::{compositor_handle, terminate, Compositor, CompositorBuilder,
-                           CompositorHandle, CompositorHandler};
-pub use self::events::{key_events, seat_events, tablet_pad_events, tablet_tool_events,
-                       touch_events, xwayland_events,
+pub use self::compositor::{compositor_handle, terminate, terminate_with, Compositor,
+                           CompositorBuilder, CompositorHandle, CompositorHandler};
+pub use self::events::{key_events, output_events, seat_events, switch_events, tablet_pad_events,
+                       tablet_tool_events, touch_events, xwayland_events,
                        pointer_events::{self, BTN_BACK, BTN_EXTRA, BTN_FORWARD, BTN_LEFT,
                                         BTN_MIDDLE, BTN_MOUSE, BTN_RIGHT, BTN_SIDE, BTN_TASK},
                        xdg_shell_v6_events, xdg_shell_events};
-pub use self::manager::{InputManagerHandler, KeyboardHandler, OutputBuilder, OutputBuilderResult,
-                        OutputHandler, OutputManagerHandler, PointerHandler, TabletPadHandler,
-                        TabletToolHandler, TouchHandler, XdgV6ShellHandler,
+pub use self::manager::{DecorationManagerHandler, DecorationMode, XdgToplevelDecoration,
+                        XdgToplevelDecorationHandle,
+                        IdleInhibitManagerHandler, IdleInhibitor, InputManagerHandler,
+                        KeyboardHandler, LayerShellHandler,
+                        LayerShellManagerHandler, OutputBuilder, OutputBuilderResult,
+                        OutputDamageHandler, OutputHandler, OutputManagerHandler,
+                        PointerConstraint, PointerConstraintHandle, PointerConstraintType,
+                        PointerConstraintsManagerHandler, PointerHandler, SessionHandler,
+                        SwitchHandler, TabletPadHandler, TabletToolHandler, TouchHandler,
+                        XdgV6ShellHandler,
                         XdgV6ShellManagerHandler, XdgShellHandler, XdgShellManagerHandler,
                         DragIconHandler};
+#[cfg(feature = "text-input")]
+pub use self::manager::{InputMethod, InputMethodHandle, InputMethodManagerHandler, TextInput,
+                        TextInputHandle, TextInputManagerHandler};
+#[cfg(feature = "output-management")]
+pub use self::manager::{OutputConfiguration, OutputConfigurationHead,
+                        OutputManagementManagerHandler};
 
 pub use self::types::area::*;
 pub use self::types::cursor::*;
@@ -64,6 +79,9 @@ pub use wlroots_sys::{wlr_keyboard_modifiers, wlr_tablet_tool_axes, wl_shm_forma
 
 pub use self::render::{matrix_identity, matrix_multiply, matrix_projection, matrix_rotate,
                        matrix_scale, matrix_transform, matrix_translate, matrix_transpose,
-                       project_box, GenericRenderer, Image, Renderer, Texture, TextureFormat};
+                       project_box, GenericRenderer, Image, OcclusionTracker, Renderer, Texture,
+                       TextureFormat};
 
 pub use self::errors::*;
+pub use self::event_loop::{EventMask, FdSourceHandle, TimerHandle};
+pub use self::handle::{Handle, HandleGuard, Handles};
@@ -191,6 +191,63 @@ macro_rules! wayland_listener {
     }
 }
 
+/// Implements `Handle` for a handle type, using its `upgrade` method and its
+/// `handle` lock, and adds a `try_run` method to it.
+///
+/// Use the `Box` form if `upgrade` returns a box that must not be freed.
+macro_rules! impl_handle {
+    (@try_run $handle: ty => $target: ty) => {
+        impl $handle {
+            /// Run a function on the referenced structure, if it still exists.
+            ///
+            /// Unlike `run` this doesn't panic when the structure is already
+            /// borrowed (e.g from within `run` on another handle to it), it
+            /// returns `HandleErr::AlreadyBorrowed` without calling the
+            /// function.
+            pub fn try_run<F, R>(&self, runner: F) -> $crate::HandleResult<R>
+                where F: FnOnce(&mut $target) -> R
+            {
+                $crate::handle::try_run(self, runner)
+            }
+        }
+    };
+    ($handle: ty => Box<$target: ty>) => {
+        impl $crate::Handle for $handle {
+            type Target = $target;
+            type Upgraded = Box<$target>;
+
+            unsafe fn acquire(&self) -> $crate::HandleResult<Box<$target>> {
+                $crate::handle::check_lock(&self.handle)?;
+                self.upgrade()
+            }
+
+            unsafe fn release(&self, upgraded: Box<$target>) {
+                Box::into_raw(upgraded);
+                $crate::handle::unlock(&self.handle)
+            }
+        }
+
+        impl_handle!(@try_run $handle => $target);
+    };
+    ($handle: ty => $target: ty) => {
+        impl $crate::Handle for $handle {
+            type Target = $target;
+            type Upgraded = $target;
+
+            unsafe fn acquire(&self) -> $crate::HandleResult<$target> {
+                $crate::handle::check_lock(&self.handle)?;
+                self.upgrade()
+            }
+
+            unsafe fn release(&self, _upgraded: $target) {
+                $crate::handle::unlock(&self.handle)
+            }
+        }
+
+        impl_handle!(@try_run $handle => $target);
+    };
+}
+
 /// Used to indicate what data is global compositor data.
 /// It will automatically implement the CompositorData trait for the struct,
 /// and also add a method to `Compositor` to unwrap the data from the fat
@@ -262,6 +319,34 @@ macro_rules! with_handles {
     };
 }
 
+/// Upgrades several handles at once, without nesting `run` calls.
+///
+/// The handles are borrowed, like the arguments of `format!`. Call `run` on
+/// the result with a function taking the structures the handles refer to, or
+/// `upgrade` to get a tuple of guards that dereference to them.
+///
+/// If any of the handles can't be upgraded none of them are, and the first
+/// `HandleErr` encountered is returned.
+///
+/// ```rust,ignore
+/// handles!((shell_handle, state.layout)).run(|shell, layout| {
+///     shell.for_each_surface(|surface, sx, sy| {
+///         ...
+///     });
+/// })?;
+///
+/// let (mut shell, layout) = handles!((shell_handle, state.layout)).upgrade()?;
+/// shell.for_each_surface(|surface, sx, sy| {
+///     ...
+/// });
+/// ```
+#[macro_export]
+macro_rules! handles {
+    (($($handle: expr),+)) => {
+        $crate::Handles(($(&$handle,)+))
+    };
+}
+
 /// An even more convenient macro for use with Handle types.
 ///
 /// This allows you to avoid rightward drift from having handles nested inside
@@ -0,0 +1,264 @@
+//! Manager for the xdg-decoration protocol.
+//!
+//! Clients use this to negotiate whether their toplevels draw their own
+//! decorations (e.g title bars) or leave that to the compositor.
+//!
+//! Older KDE and Qt clients use the server decoration protocol instead, see
+//! `CompositorBuilder::server_decoration_manager`.
+
+use std::{cell::Cell, rc::{Rc, Weak}};
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wlr_xdg_toplevel_decoration_v1, wlr_xdg_toplevel_decoration_v1_mode,
+                  wlr_xdg_toplevel_decoration_v1_set_mode};
+use wlroots_sys::wlr_xdg_toplevel_decoration_v1_mode::*;
+
+use XdgShellSurfaceHandle;
+use compositor::{compositor_handle, CompositorHandle};
+use errors::{HandleErr, HandleResult};
+
+/// Who draws the decorations of a toplevel.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DecorationMode {
+    /// The client draws its own decorations.
+    ClientSide,
+    /// The compositor draws the decorations.
+    ServerSide
+}
+
+impl DecorationMode {
+    fn from_raw(mode: wlr_xdg_toplevel_decoration_v1_mode) -> Option<Self> {
+        match mode {
+            WLR_XDG_TOPLEVEL_DECORATION_V1_MODE_NONE => None,
+            WLR_XDG_TOPLEVEL_DECORATION_V1_MODE_CLIENT_SIDE => Some(DecorationMode::ClientSide),
+            WLR_XDG_TOPLEVEL_DECORATION_V1_MODE_SERVER_SIDE => Some(DecorationMode::ServerSide)
+        }
+    }
+
+    fn as_raw(self) -> wlr_xdg_toplevel_decoration_v1_mode {
+        match self {
+            DecorationMode::ClientSide => WLR_XDG_TOPLEVEL_DECORATION_V1_MODE_CLIENT_SIDE,
+            DecorationMode::ServerSide => WLR_XDG_TOPLEVEL_DECORATION_V1_MODE_SERVER_SIDE
+        }
+    }
+}
+
+/// The decoration object of a stable xdg shell toplevel.
+#[derive(Debug)]
+pub struct XdgToplevelDecoration {
+    liveliness: Rc<Cell<bool>>,
+    decoration: *mut wlr_xdg_toplevel_decoration_v1
+}
+
+impl XdgToplevelDecoration {
+    /// Get a handle to the shell surface of the toplevel.
+    pub fn surface(&self) -> XdgShellSurfaceHandle {
+        unsafe { XdgShellSurfaceHandle::from_ptr((*self.decoration).surface) }
+    }
+
+    /// Get the mode the client asked for, or `None` if it leaves it up to
+    /// the compositor.
+    pub fn requested_mode(&self) -> Option<DecorationMode> {
+        unsafe { DecorationMode::from_raw((*self.decoration).client_pending_mode) }
+    }
+
+    /// Get the mode the client last acknowledged, or `None` if none has been
+    /// acknowledged yet.
+    pub fn current_mode(&self) -> Option<DecorationMode> {
+        unsafe { DecorationMode::from_raw((*self.decoration).current_mode) }
+    }
+
+    /// Tell the client who should draw the decorations of the toplevel.
+    ///
+    /// Returns the serial of the configure event that was sent.
+    pub fn set_mode(&mut self, mode: DecorationMode) -> u32 {
+        unsafe { wlr_xdg_toplevel_decoration_v1_set_mode(self.decoration, mode.as_raw()) }
+    }
+
+    /// Creates a weak reference to an `XdgToplevelDecoration`.
+    pub fn weak_reference(&self) -> XdgToplevelDecorationHandle {
+        XdgToplevelDecorationHandle { handle: Rc::downgrade(&self.liveliness),
+                                      decoration: self.decoration }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct XdgToplevelDecorationHandle {
+    handle: Weak<Cell<bool>>,
+    decoration: *mut wlr_xdg_toplevel_decoration_v1
+}
+
+impl XdgToplevelDecorationHandle {
+    unsafe fn from_ptr(decoration: *mut wlr_xdg_toplevel_decoration_v1) -> Self {
+        let listener = (*decoration).data as *mut XdgToplevelDecorationListener;
+        let handle = if listener.is_null() {
+            Weak::new()
+        } else {
+            Rc::downgrade(&(*listener).data.0)
+        };
+        XdgToplevelDecorationHandle { handle, decoration }
+    }
+
+    unsafe fn upgrade(&self) -> HandleResult<XdgToplevelDecoration> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            .and_then(|check| {
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                check.set(true);
+                Ok(XdgToplevelDecoration { liveliness: check,
+                                           decoration: self.decoration })
+            })
+    }
+
+    /// Run a function on the referenced `XdgToplevelDecoration`, if it still exists.
+    ///
+    /// Returns the result of the function, if successful.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `XdgToplevelDecoration` is already
+    /// borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut XdgToplevelDecoration) -> R
+    {
+        self.try_run(runner)
+    }
+}
+
+impl_handle!(XdgToplevelDecorationHandle => XdgToplevelDecoration);
+
+impl PartialEq for XdgToplevelDecorationHandle {
+    fn eq(&self, other: &XdgToplevelDecorationHandle) -> bool {
+        self.decoration == other.decoration
+    }
+}
+
+impl Eq for XdgToplevelDecorationHandle {}
+
+/// Handles the decoration objects clients create for their toplevels.
+pub trait DecorationManagerHandler {
+    /// Called when a client creates a decoration object for one of its
+    /// toplevels.
+    ///
+    /// The mode returned by `request_mode` is set right after this returns.
+    fn new_decoration(&mut self, CompositorHandle, XdgToplevelDecorationHandle) {}
+
+    /// Decide who draws the decorations of the toplevel when the decoration
+    /// object is created or the client asks for a different mode.
+    ///
+    /// `requested` is `None` if the client leaves the choice to the
+    /// compositor. By default the client gets what it asked for, falling
+    /// back to `default_mode`.
+    fn request_mode(&mut self,
+                    _compositor: CompositorHandle,
+                    _decoration: XdgToplevelDecorationHandle,
+                    requested: Option<DecorationMode>)
+                    -> DecorationMode {
+        requested.unwrap_or_else(|| self.default_mode())
+    }
+
+    /// The mode used when clients leave the choice to the compositor.
+    fn default_mode(&self) -> DecorationMode {
+        DecorationMode::ClientSide
+    }
+
+    /// Called when a decoration object is about to be destroyed.
+    fn destroyed(&mut self, CompositorHandle, XdgToplevelDecorationHandle) {}
+}
+
+/// Ask the handler for the mode of the decoration and apply it.
+unsafe fn apply_mode(handler: &mut DecorationManagerHandler,
+                     compositor: CompositorHandle,
+                     decoration: XdgToplevelDecorationHandle) {
+    let requested = DecorationMode::from_raw((*decoration.decoration).client_pending_mode);
+    let mode = handler.request_mode(compositor, decoration.clone(), requested);
+    wlr_xdg_toplevel_decoration_v1_set_mode(decoration.decoration, mode.as_raw());
+}
+
+wayland_listener!(DecorationManager, (Box<DecorationManagerHandler>, Rc<Cell<bool>>), [
+    new_decoration_listener => new_decoration_notify: |this: &mut DecorationManager,
+                                                       data: *mut libc::c_void,|
+    unsafe {
+        let decoration_ptr = data as *mut wlr_xdg_toplevel_decoration_v1;
+        let mut listener =
+            XdgToplevelDecorationListener::new((Rc::new(Cell::new(false)),
+                                                this as *mut DecorationManager,
+                                                Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*decoration_ptr).events.request_mode as *mut _ as _,
+                      listener.request_mode_listener() as _);
+        wl_signal_add(&mut (*decoration_ptr).events.destroy as *mut _ as _,
+                      listener.destroy_listener() as _);
+        (*decoration_ptr).data = Box::into_raw(listener) as *mut libc::c_void;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let decoration = XdgToplevelDecorationHandle::from_ptr(decoration_ptr);
+        this.data.0.new_decoration(compositor.clone(), decoration.clone());
+        apply_mode(&mut *this.data.0, compositor, decoration);
+    };
+]);
+
+impl Drop for DecorationManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.new_decoration_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+// The weak reference is to the liveness token of the manager, decoration
+// objects can outlive it and must not call into it after it's been dropped.
+wayland_listener!(XdgToplevelDecorationListener,
+                  (Rc<Cell<bool>>, *mut DecorationManager, Weak<Cell<bool>>), [
+    request_mode_listener => request_mode_notify: |this: &mut XdgToplevelDecorationListener,
+                                                   data: *mut libc::c_void,|
+    unsafe {
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let decoration =
+            XdgToplevelDecorationHandle::from_ptr(data as *mut wlr_xdg_toplevel_decoration_v1);
+        apply_mode(&mut *(*manager).data.0, compositor, decoration);
+    };
+    destroy_listener => destroy_notify: |this: &mut XdgToplevelDecorationListener,
+                                         data: *mut libc::c_void,|
+    unsafe {
+        let decoration_ptr = data as *mut wlr_xdg_toplevel_decoration_v1;
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            let decoration = XdgToplevelDecorationHandle::from_ptr(decoration_ptr);
+            (*manager).data.0.destroyed(compositor, decoration);
+        }
+        (*decoration_ptr).data = ::std::ptr::null_mut();
+        Box::from_raw(this);
+    };
+]);
+
+impl XdgToplevelDecorationListener {
+    /// Get the manager that made the decoration object, unless it's been
+    /// dropped.
+    fn manager(&self) -> Option<*mut DecorationManager> {
+        self.data.2.upgrade().map(|_| self.data.1)
+    }
+}
+
+impl Drop for XdgToplevelDecorationListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.request_mode_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
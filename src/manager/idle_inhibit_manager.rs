@@ -0,0 +1,124 @@
+//! Manager for idle inhibitors.
+//!
+//! Clients such as video players create an inhibitor for one of their
+//! surfaces to keep the compositor from going idle (e.g blanking the screen)
+//! while that surface is visible.
+
+use std::{cell::Cell, rc::{Rc, Weak}};
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::wlr_idle_inhibitor_v1;
+
+use SurfaceHandle;
+use compositor::{compositor_handle, CompositorHandle};
+
+/// An idle inhibitor a client created for one of its surfaces.
+///
+/// Inhibitors can be compared to find out which one was destroyed in
+/// `IdleInhibitManagerHandler::on_destroy_inhibitor`.
+#[derive(Clone, Debug)]
+pub struct IdleInhibitor {
+    inhibitor: *mut wlr_idle_inhibitor_v1,
+    surface: SurfaceHandle
+}
+
+impl IdleInhibitor {
+    pub(crate) unsafe fn from_ptr(inhibitor: *mut wlr_idle_inhibitor_v1) -> Self {
+        IdleInhibitor { inhibitor,
+                        surface: SurfaceHandle::from_ptr((*inhibitor).surface) }
+    }
+
+    /// Get a handle to the surface that is inhibiting idle.
+    ///
+    /// The compositor should only honor the inhibitor while this surface is
+    /// visible.
+    pub fn surface(&self) -> SurfaceHandle {
+        self.surface.clone()
+    }
+}
+
+impl PartialEq for IdleInhibitor {
+    fn eq(&self, other: &IdleInhibitor) -> bool {
+        self.inhibitor == other.inhibitor
+    }
+}
+
+impl Eq for IdleInhibitor {}
+
+/// Handles idle inhibitors being created and destroyed by clients.
+///
+/// While there are inhibitors for visible surfaces the compositor should not
+/// go idle, e.g by disabling the idle timers with `Idle::set_enabled`.
+pub trait IdleInhibitManagerHandler {
+    /// Called when a client creates a new idle inhibitor.
+    fn on_new_inhibitor(&mut self, CompositorHandle, IdleInhibitor) {}
+
+    /// Called when an idle inhibitor is destroyed, either by the client or
+    /// because its surface was destroyed.
+    fn on_destroy_inhibitor(&mut self, CompositorHandle, IdleInhibitor) {}
+}
+
+wayland_listener!(IdleInhibitManager, (Box<IdleInhibitManagerHandler>, Rc<Cell<bool>>), [
+    new_inhibitor_listener => new_inhibitor_notify: |this: &mut IdleInhibitManager,
+                                                     data: *mut libc::c_void,|
+    unsafe {
+        let inhibitor_ptr = data as *mut wlr_idle_inhibitor_v1;
+        let inhibitor = IdleInhibitor::from_ptr(inhibitor_ptr);
+        let mut listener = IdleInhibitorListener::new((inhibitor.clone(),
+                                                       this as *mut IdleInhibitManager,
+                                                       Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*inhibitor_ptr).events.destroy as *mut _ as _,
+                      listener.destroy_listener() as _);
+        Box::into_raw(listener);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.0.on_new_inhibitor(compositor, inhibitor);
+    };
+]);
+
+impl Drop for IdleInhibitManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.new_inhibitor_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+// The weak reference is to the liveness token of the manager, inhibitors can
+// outlive it and must not call into it after it's been dropped.
+wayland_listener!(IdleInhibitorListener,
+                  (IdleInhibitor, *mut IdleInhibitManager, Weak<Cell<bool>>), [
+    destroy_listener => destroy_notify: |this: &mut IdleInhibitorListener,
+                                         _data: *mut libc::c_void,|
+    unsafe {
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            (*manager).data.0.on_destroy_inhibitor(compositor, this.data.0.clone());
+        }
+        // NOTE Freed even when the compositor isn't running, the inhibitor
+        // is gone either way.
+        Box::from_raw(this);
+    };
+]);
+
+impl IdleInhibitorListener {
+    /// Get the manager that was told about the inhibitor, unless it's been
+    /// dropped.
+    fn manager(&self) -> Option<*mut IdleInhibitManager> {
+        self.data.2.upgrade().map(|_| self.data.1)
+    }
+}
+
+impl Drop for IdleInhibitorListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
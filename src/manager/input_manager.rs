@@ -5,16 +5,21 @@
 use libc;
 
 use std::{env, panic};
+use std::cell::Cell;
 use std::process::abort;
+use std::rc::{Rc, Weak};
 
-use super::{KeyboardHandler, KeyboardWrapper, PointerHandler, PointerWrapper, TabletPadHandler,
-            TabletPadWrapper, TabletToolHandler, TabletToolWrapper, TouchHandler, TouchWrapper};
+use super::{KeyboardHandler, KeyboardWrapper, PointerHandler, PointerWrapper, SwitchHandler,
+            SwitchWrapper, TabletPadHandler, TabletPadWrapper, TabletToolHandler,
+            TabletToolWrapper, TouchHandler, TouchWrapper};
+use OutputHandle;
 use compositor::{compositor_handle, CompositorHandle};
-use types::input::{InputDevice, Keyboard, KeyboardHandle, Pointer, PointerHandle, TabletPad,
-                   TabletPadHandle, TabletTool, TabletToolHandle, Touch, TouchHandle};
+use types::input::{InputDevice, Keyboard, KeyboardHandle, Pointer, PointerHandle, Switch,
+                   SwitchHandle, TabletPad, TabletPadHandle, TabletTool, TabletToolHandle, Touch,
+                   TouchHandle};
 use utils::safe_as_cstring;
 
-use wayland_sys::server::signal::wl_signal_add;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
 use wlroots_sys::{wlr_input_device, wlr_input_device_type, wlr_keyboard_set_keymap,
                   wlr_keyboard_set_repeat_info, xkb_context_new, xkb_context_unref,
                   xkb_keymap_new_from_names, xkb_keymap_unref, xkb_rule_names};
@@ -29,8 +34,17 @@ pub trait InputManagerHandler {
     /// Any panic in this function will cause the process to abort.
     fn input_added(&mut self, CompositorHandle, &mut InputDevice) {}
 
+    /// Callback triggered when an input device of any kind is removed (e.g
+    /// unplugged).
+    ///
+    /// Called before the `destroyed` callback of the device's own handler.
+    fn input_removed(&mut self, CompositorHandle, &mut InputDevice) {}
+
     /// Callback triggered when a keyboard device is added.
     ///
+    /// This includes virtual keyboards created by clients, if
+    /// `CompositorBuilder::virtual_keyboard_manager` is enabled.
+    ///
     /// # Panics
     /// Any panic in this function will cause the process to abort.
     fn keyboard_added(&mut self, CompositorHandle, KeyboardHandle) -> Option<Box<KeyboardHandler>> {
@@ -39,6 +53,10 @@ pub trait InputManagerHandler {
 
     /// Callback triggered when a pointer device is added.
     ///
+    /// This includes virtual pointers created by clients, if the
+    /// `virtual-pointer` feature is enabled and
+    /// `CompositorBuilder::virtual_pointer_manager` is set.
+    ///
     /// # Panics
     /// Any panic in this function will cause the process to abort.
     fn pointer_added(&mut self, CompositorHandle, PointerHandle) -> Option<Box<PointerHandler>> {
@@ -76,16 +94,46 @@ pub trait InputManagerHandler {
                         -> Option<Box<TabletPadHandler>> {
         None
     }
+
+    /// Callback triggered when a switch (e.g the lid of a laptop) is added.
+    ///
+    /// # Panics
+    /// Any panic in this function will cause the process to abort.
+    fn switch_added(&mut self, CompositorHandle, SwitchHandle) -> Option<Box<SwitchHandler>> {
+        None
+    }
+
+    /// Callback triggered after a virtual pointer created by a client was
+    /// set up with `pointer_added`, if a handler was returned for it.
+    ///
+    /// The output is the one the client wants absolute motion mapped to, if
+    /// it asked for one. Pass it to `Cursor::map_input_to_output` to honour
+    /// that.
+    ///
+    /// Only called if the `virtual-pointer` feature is enabled and
+    /// `CompositorBuilder::virtual_pointer_manager` is set.
+    fn virtual_pointer_added(&mut self,
+                             CompositorHandle,
+                             PointerHandle,
+                             Option<OutputHandle>) {
+    }
 }
 
-wayland_listener!(InputManager, Box<InputManagerHandler>, [
+wayland_listener!(InputManager, (Box<InputManagerHandler>, Rc<Cell<bool>>), [
     add_listener => add_notify: |this: &mut InputManager, data: *mut libc::c_void,| unsafe {
         let compositor = match compositor_handle() {
             Some(handle) => handle,
             None => return
         };
         let data = data as *mut wlr_input_device;
-        let ref mut manager = this.data;
+        // NOTE Added before the listeners of the device's own handler, so that
+        // `input_removed` is called first.
+        let mut device_listener =
+            InputDeviceListener::new((this as *mut InputManager, Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*data).events.destroy as *mut _ as _,
+                      device_listener.destroy_listener() as _);
+        Box::into_raw(device_listener);
+        let ref mut manager = this.data.0;
         use self::wlr_input_device_type::*;
         let mut dev = InputDevice::from_ptr(data);
         let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
@@ -141,6 +189,21 @@ wayland_listener!(InputManager, Box<InputManagerHandler>, [
                                     pointer.button_listener() as *mut _ as _);
                         wl_signal_add(&mut (*dev.dev_union().pointer).events.axis as *mut _ as _,
                                     pointer.axis_listener() as *mut _ as _);
+                        let pointer_ptr = &mut (*dev.dev_union().pointer);
+                        wl_signal_add(&mut pointer_ptr.events.swipe_begin as *mut _ as _,
+                                      pointer.swipe_begin_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.swipe_update as *mut _ as _,
+                                      pointer.swipe_update_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.swipe_end as *mut _ as _,
+                                      pointer.swipe_end_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.pinch_begin as *mut _ as _,
+                                      pointer.pinch_begin_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.pinch_update as *mut _ as _,
+                                      pointer.pinch_update_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.pinch_end as *mut _ as _,
+                                      pointer.pinch_end_listener() as *mut _ as _);
+                        wl_signal_add(&mut pointer_ptr.events.frame as *mut _ as _,
+                                      pointer.frame_listener() as *mut _ as _);
                         wl_signal_add(&mut (*dev.as_ptr()).events.destroy as *mut _ as _,
                                       pointer.on_destroy_listener() as _);
                         (*data).data = Box::into_raw(pointer) as _;
@@ -222,6 +285,26 @@ wayland_listener!(InputManager, Box<InputManagerHandler>, [
                                       tablet_pad.on_destroy_listener() as _);
                         (*data).data = Box::into_raw(tablet_pad) as _;
                     }
+                },
+                WLR_INPUT_DEVICE_SWITCH => {
+                    let switch = match Switch::new_from_input_device(data) {
+                        Some(dev) => dev,
+                        None => {
+                            wlr_log!(WLR_ERROR, "Device {:#?} was not a switch", dev);
+                            abort()
+                        }
+                    };
+                    let switch_handle = switch.weak_reference();
+                    if let Some(switch_handler) = manager.switch_added(compositor.clone(),
+                                                                       switch_handle) {
+                        let mut switch = SwitchWrapper::new((switch, switch_handler));
+                        wl_signal_add(&mut (*dev.dev_union().lid_switch).events.toggle
+                                      as *mut _ as _,
+                                      switch.toggle_listener() as *mut _ as _);
+                        wl_signal_add(&mut (*dev.as_ptr()).events.destroy as *mut _ as _,
+                                      switch.on_destroy_listener() as _);
+                        (*data).data = Box::into_raw(switch) as _;
+                    }
                 }
             }
             manager.input_added(compositor, &mut dev)
@@ -240,6 +323,54 @@ wayland_listener!(InputManager, Box<InputManagerHandler>, [
     };
 ]);
 
+// The weak reference is to the liveness token of the manager, devices can
+// outlive it and must not call into it after it's been dropped.
+wayland_listener!(InputDeviceListener, (*mut InputManager, Weak<Cell<bool>>), [
+    destroy_listener => destroy_notify: |this: &mut InputDeviceListener,
+                                         data: *mut libc::c_void,|
+    unsafe {
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            let mut dev = InputDevice::from_ptr(data as *mut wlr_input_device);
+            (*manager).data.0.input_removed(compositor, &mut dev);
+        }
+        Box::from_raw(this);
+    };
+]);
+
+impl InputDeviceListener {
+    /// Get the manager that was told about the device, unless it's been
+    /// dropped.
+    fn manager(&self) -> Option<*mut InputManager> {
+        self.data.1.upgrade().map(|_| self.data.0)
+    }
+}
+
+impl Drop for InputDeviceListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+impl InputManager {
+    /// Get a weak reference to the liveness token of the manager, for
+    /// listeners that can outlive it.
+    pub(crate) fn liveliness(&self) -> Weak<Cell<bool>> {
+        Rc::downgrade(&self.data.1)
+    }
+}
+
+#[cfg(feature = "virtual-pointer")]
+impl InputManager {
+    /// Get the handler the input manager was set up with.
+    pub(crate) fn handler(&mut self) -> &mut InputManagerHandler {
+        &mut *self.data.0
+    }
+}
+
 pub(crate) unsafe fn add_keyboard(dev: &mut InputDevice) {
     // Set the XKB settings
     let rules = safe_as_cstring(env::var("XKB_DEFAULT_RULES").unwrap_or("".into()));
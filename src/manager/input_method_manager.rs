@@ -0,0 +1,352 @@
+//! Manager for the input method protocol.
+//!
+//! Input method editors (e.g for CJK input or emoji pickers) use this to
+//! compose text and send it to the focused text input of their seat (see
+//! `TextInputManagerHandler`).
+//!
+//! The text committed by the input method is forwarded to the text input
+//! automatically, the handler is only told about it.
+
+use std::{ptr, cell::{Cell, RefCell}, rc::{Rc, Weak}};
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wlr_input_method_manager_v2, wlr_input_method_v2,
+                  wlr_input_method_v2_send_activate, wlr_input_method_v2_send_content_type,
+                  wlr_input_method_v2_send_deactivate, wlr_input_method_v2_send_done,
+                  wlr_input_method_v2_send_surrounding_text,
+                  wlr_input_method_v2_send_text_change_cause, wlr_seat, wlr_text_input_v3,
+                  wlr_text_input_v3_send_commit_string,
+                  wlr_text_input_v3_send_delete_surrounding_text, wlr_text_input_v3_send_done,
+                  wlr_text_input_v3_send_preedit_string};
+
+use SeatHandle;
+use compositor::{compositor_handle, CompositorHandle};
+use errors::{HandleErr, HandleResult};
+use utils::c_to_rust_string;
+
+use super::text_input_manager::focused_text_input;
+
+thread_local! {
+    /// The input method global that is currently advertised, if there is one.
+    ///
+    /// Used to find the input method of a seat when one of its text inputs
+    /// changes.
+    static INPUT_METHOD_GLOBAL: RefCell<*mut wlr_input_method_manager_v2> =
+        RefCell::new(ptr::null_mut());
+}
+
+/// Get the global stored in `INPUT_METHOD_GLOBAL`, which is null if there is none.
+fn input_method_global() -> *mut wlr_input_method_manager_v2 {
+    INPUT_METHOD_GLOBAL.with(|global| *global.borrow())
+}
+
+/// An input method editor bound to a seat.
+#[derive(Debug)]
+pub struct InputMethod {
+    liveliness: Rc<Cell<bool>>,
+    input_method: *mut wlr_input_method_v2
+}
+
+impl InputMethod {
+    /// Get a handle to the seat the input method belongs to.
+    pub fn seat(&self) -> SeatHandle {
+        unsafe { SeatHandle::from_ptr((*self.input_method).seat) }
+    }
+
+    /// Determines if the input method is active, i.e if a text input of its
+    /// seat is enabled.
+    pub fn is_active(&self) -> bool {
+        unsafe { (*self.input_method).active }
+    }
+
+    /// Get the text that is being composed, with the cursor as byte offsets
+    /// into it.
+    ///
+    /// Returns `None` if there is none.
+    pub fn preedit(&self) -> Option<(String, i32, i32)> {
+        unsafe {
+            let preedit = &(*self.input_method).current.preedit;
+            c_to_rust_string(preedit.text).map(|text| {
+                                                   (text,
+                                                    preedit.cursor_begin,
+                                                    preedit.cursor_end)
+                                               })
+        }
+    }
+
+    /// Get the text that was last committed, if any.
+    pub fn commit_string(&self) -> Option<String> {
+        unsafe { c_to_rust_string((*self.input_method).current.commit_text) }
+    }
+
+    /// Creates a weak reference to an `InputMethod`.
+    pub fn weak_reference(&self) -> InputMethodHandle {
+        InputMethodHandle { handle: Rc::downgrade(&self.liveliness),
+                            input_method: self.input_method }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputMethodHandle {
+    handle: Weak<Cell<bool>>,
+    input_method: *mut wlr_input_method_v2
+}
+
+impl InputMethodHandle {
+    pub(crate) unsafe fn from_ptr(input_method: *mut wlr_input_method_v2) -> Self {
+        let listener = (*input_method).data as *mut InputMethodListener;
+        let handle = if listener.is_null() {
+            Weak::new()
+        } else {
+            Rc::downgrade(&(*listener).data.0)
+        };
+        InputMethodHandle { handle, input_method }
+    }
+
+    unsafe fn upgrade(&self) -> HandleResult<InputMethod> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            .and_then(|check| {
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                check.set(true);
+                Ok(InputMethod { liveliness: check,
+                                 input_method: self.input_method })
+            })
+    }
+
+    /// Run a function on the referenced `InputMethod`, if it still exists.
+    ///
+    /// Returns the result of the function, if successful.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `InputMethod` is already
+    /// borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut InputMethod) -> R
+    {
+        self.try_run(runner)
+    }
+}
+
+impl_handle!(InputMethodHandle => InputMethod);
+
+impl PartialEq for InputMethodHandle {
+    fn eq(&self, other: &InputMethodHandle) -> bool {
+        self.input_method == other.input_method
+    }
+}
+
+impl Eq for InputMethodHandle {}
+
+/// Handles input methods being created, committing text and being destroyed.
+pub trait InputMethodManagerHandler {
+    /// Called when a client creates a new input method for a seat.
+    ///
+    /// If a text input of the seat is already enabled the input method is
+    /// activated right away.
+    fn on_new_input_method(&mut self, CompositorHandle, InputMethodHandle) {}
+
+    /// Called when the input method commits text that is still being
+    /// composed, with the cursor as byte offsets into it.
+    ///
+    /// It has already been sent to the focused text input of the seat.
+    fn on_preedit(&mut self, CompositorHandle, InputMethodHandle, &str, i32, i32) {}
+
+    /// Called when the input method commits text to be inserted.
+    ///
+    /// It has already been sent to the focused text input of the seat.
+    fn on_commit_string(&mut self, CompositorHandle, InputMethodHandle, &str) {}
+
+    /// Called when an input method is about to be destroyed.
+    fn on_destroy_input_method(&mut self, CompositorHandle, InputMethodHandle) {}
+}
+
+wayland_listener!(InputMethodManager, (Box<InputMethodManagerHandler>, Rc<Cell<bool>>), [
+    input_method_listener => input_method_notify: |this: &mut InputMethodManager,
+                                                   data: *mut libc::c_void,|
+    unsafe {
+        let input_method_ptr = data as *mut wlr_input_method_v2;
+        let mut listener = InputMethodListener::new((Rc::new(Cell::new(false)),
+                                                     this as *mut InputMethodManager,
+                                                     Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*input_method_ptr).events.commit as *mut _ as _,
+                      listener.commit_listener() as _);
+        wl_signal_add(&mut (*input_method_ptr).events.destroy as *mut _ as _,
+                      listener.destroy_listener() as _);
+        (*input_method_ptr).data = Box::into_raw(listener) as *mut libc::c_void;
+        let text_input = focused_text_input((*input_method_ptr).seat);
+        if !text_input.is_null() {
+            activate_input_method((*input_method_ptr).seat, text_input);
+        }
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.0.on_new_input_method(compositor, InputMethodHandle::from_ptr(input_method_ptr));
+    };
+]);
+
+impl Drop for InputMethodManager {
+    fn drop(&mut self) {
+        unsafe {
+            INPUT_METHOD_GLOBAL.with(|global| *global.borrow_mut() = ptr::null_mut());
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.input_method_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+// The weak reference is to the liveness token of the manager, input methods
+// can outlive it and must not call into it after it's been dropped.
+wayland_listener!(InputMethodListener,
+                  (Rc<Cell<bool>>, *mut InputMethodManager, Weak<Cell<bool>>), [
+    commit_listener => commit_notify: |this: &mut InputMethodListener, data: *mut libc::c_void,|
+    unsafe {
+        let input_method_ptr = data as *mut wlr_input_method_v2;
+        let state = &(*input_method_ptr).current;
+        let text_input = focused_text_input((*input_method_ptr).seat);
+        if !text_input.is_null() {
+            if !state.preedit.text.is_null() {
+                wlr_text_input_v3_send_preedit_string(text_input,
+                                                      state.preedit.text,
+                                                      state.preedit.cursor_begin as u32,
+                                                      state.preedit.cursor_end as u32);
+            }
+            if !state.commit_text.is_null() {
+                wlr_text_input_v3_send_commit_string(text_input, state.commit_text);
+            }
+            if state.delete.before_length != 0 || state.delete.after_length != 0 {
+                wlr_text_input_v3_send_delete_surrounding_text(text_input,
+                                                               state.delete.before_length,
+                                                               state.delete.after_length);
+            }
+            wlr_text_input_v3_send_done(text_input);
+        }
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let input_method = InputMethodHandle::from_ptr(input_method_ptr);
+        if let Some(text) = c_to_rust_string(state.preedit.text) {
+            (*manager).data.0.on_preedit(compositor.clone(),
+                                         input_method.clone(),
+                                         &text,
+                                         state.preedit.cursor_begin,
+                                         state.preedit.cursor_end);
+        }
+        if let Some(text) = c_to_rust_string(state.commit_text) {
+            (*manager).data.0.on_commit_string(compositor, input_method, &text);
+        }
+    };
+    destroy_listener => destroy_notify: |this: &mut InputMethodListener,
+                                         data: *mut libc::c_void,|
+    unsafe {
+        let input_method_ptr = data as *mut wlr_input_method_v2;
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            let input_method = InputMethodHandle::from_ptr(input_method_ptr);
+            (*manager).data.0.on_destroy_input_method(compositor, input_method);
+        }
+        (*input_method_ptr).data = ptr::null_mut();
+        Box::from_raw(this);
+    };
+]);
+
+impl InputMethodListener {
+    /// Get the manager that made the input method, unless it's been dropped.
+    fn manager(&self) -> Option<*mut InputMethodManager> {
+        self.data.2.upgrade().map(|_| self.data.1)
+    }
+}
+
+impl Drop for InputMethodListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.commit_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+/// Use the global to find the input methods of seats.
+///
+/// It is unset when the `InputMethodManager` is dropped.
+pub(crate) unsafe fn set_input_method_global(global: *mut wlr_input_method_manager_v2) {
+    INPUT_METHOD_GLOBAL.with(|cell| *cell.borrow_mut() = global)
+}
+
+/// Get the input method of the seat, if there is one.
+unsafe fn seat_input_method(seat: *mut wlr_seat) -> *mut wlr_input_method_v2 {
+    let mut result = ptr::null_mut();
+    let global = input_method_global();
+    if global.is_null() {
+        return result
+    }
+    wl_list_for_each!((*global).input_methods,
+                      link,
+                      (input_method: wlr_input_method_v2) => {
+        if (*input_method).seat == seat {
+            result = input_method;
+        }
+    });
+    result
+}
+
+/// Send the state of the text input to the input method of the seat.
+unsafe fn send_state(input_method: *mut wlr_input_method_v2,
+                     text_input: *mut wlr_text_input_v3) {
+    let state = &(*text_input).current;
+    if !state.surrounding.text.is_null() {
+        wlr_input_method_v2_send_surrounding_text(input_method,
+                                                  state.surrounding.text,
+                                                  state.surrounding.cursor as u32,
+                                                  state.surrounding.anchor as u32);
+    }
+    wlr_input_method_v2_send_text_change_cause(input_method, state.text_change_cause);
+    wlr_input_method_v2_send_content_type(input_method,
+                                          state.content_type.hint,
+                                          state.content_type.purpose);
+    wlr_input_method_v2_send_done(input_method);
+}
+
+/// Activate the input method of the seat for the newly enabled text input.
+pub(crate) unsafe fn activate_input_method(seat: *mut wlr_seat,
+                                           text_input: *mut wlr_text_input_v3) {
+    let input_method = seat_input_method(seat);
+    if input_method.is_null() {
+        return
+    }
+    wlr_input_method_v2_send_activate(input_method);
+    send_state(input_method, text_input);
+}
+
+/// Deactivate the input method of the seat, if it is active.
+pub(crate) unsafe fn deactivate_input_method(seat: *mut wlr_seat) {
+    let input_method = seat_input_method(seat);
+    if input_method.is_null() || !(*input_method).active {
+        return
+    }
+    wlr_input_method_v2_send_deactivate(input_method);
+    wlr_input_method_v2_send_done(input_method);
+}
+
+/// Send the committed state of the text input to the input method of the
+/// seat, if it is active.
+pub(crate) unsafe fn send_text_input_state(seat: *mut wlr_seat,
+                                           text_input: *mut wlr_text_input_v3) {
+    let input_method = seat_input_method(seat);
+    if input_method.is_null() || !(*input_method).active {
+        return
+    }
+    send_state(input_method, text_input);
+}
@@ -0,0 +1,111 @@
+//! Handler for layer shell clients.
+
+use libc;
+
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::wlr_layer_surface;
+
+use {LayerShellSurface, LayerShellSurfaceHandle, SurfaceHandle};
+use compositor::{compositor_handle, CompositorHandle};
+use types::shell::LayerShellSurfaceState;
+
+/// Handles events from the client layer shells.
+pub trait LayerShellHandler {
+    /// Called when the surface commits new state.
+    ///
+    /// This is where the compositor should check the client pending state
+    /// (e.g the anchor and exclusive zone), arrange the surface on its output
+    /// and `configure` it with its new size.
+    fn on_commit(&mut self, CompositorHandle, SurfaceHandle, LayerShellSurfaceHandle) {}
+
+    /// Called when the surface is ready to be displayed.
+    fn on_map(&mut self, CompositorHandle, SurfaceHandle, LayerShellSurfaceHandle) {}
+
+    /// Called when the surface should no longer be displayed.
+    fn on_unmap(&mut self, CompositorHandle, SurfaceHandle, LayerShellSurfaceHandle) {}
+
+    /// Called when the layer shell is destroyed (e.g by the user)
+    fn destroyed(&mut self, CompositorHandle, LayerShellSurfaceHandle) {}
+}
+
+wayland_listener!(LayerShell, (LayerShellSurface, Option<Box<LayerShellHandler>>), [
+    destroy_listener => destroy_notify: |this: &mut LayerShell, data: *mut libc::c_void,| unsafe {
+        let (ref layer_surface, ref mut manager) = this.data;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        if let Some(ref mut manager) = manager.as_mut() {
+            manager.destroyed(compositor, layer_surface.weak_reference());
+        }
+        let layer_surface_ptr = data as *mut wlr_layer_surface;
+        let shell_state_ptr = (*layer_surface_ptr).data as *mut LayerShellSurfaceState;
+        Box::from_raw((*shell_state_ptr).shell);
+        Box::from_raw(shell_state_ptr);
+        (*layer_surface_ptr).data = ::std::ptr::null_mut();
+    };
+    commit_listener => commit_notify: |this: &mut LayerShell, _data: *mut libc::c_void,| unsafe {
+        let (ref mut layer_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ls, Some(manager)) => (ls, manager)
+        };
+        let surface = layer_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        manager.on_commit(compositor,
+                          surface,
+                          layer_surface.weak_reference());
+    };
+    map_listener => map_notify: |this: &mut LayerShell, _data: *mut libc::c_void,| unsafe {
+        let (ref mut layer_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ls, Some(manager)) => (ls, manager)
+        };
+        let surface = layer_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        manager.on_map(compositor,
+                       surface,
+                       layer_surface.weak_reference());
+    };
+    unmap_listener => unmap_notify: |this: &mut LayerShell, _data: *mut libc::c_void,| unsafe {
+        let (ref mut layer_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ls, Some(manager)) => (ls, manager)
+        };
+        let surface = layer_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        manager.on_unmap(compositor,
+                         surface,
+                         layer_surface.weak_reference());
+    };
+]);
+
+impl Drop for LayerShell {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.commit_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.map_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.unmap_listener()).link as *mut _ as _);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Manager for layer shell clients.
+
+use libc;
+use wayland_sys::server::signal::wl_signal_add;
+use wlroots_sys::wlr_layer_surface;
+
+use types::{shell::LayerShellSurfaceState, surface::InternalSurfaceState};
+use super::layer_shell_handler::LayerShell;
+use {LayerShellHandler, LayerShellSurface, LayerShellSurfaceHandle, SurfaceHandler};
+use compositor::{compositor_handle, CompositorHandle};
+
+pub trait LayerShellManagerHandler {
+    /// Callback that is triggered when a new layer shell surface appears.
+    ///
+    /// If the client didn't request an output the surface has none, and one
+    /// must be assigned here with `LayerShellSurface::set_output`.
+    fn new_surface(&mut self,
+                   CompositorHandle,
+                   LayerShellSurfaceHandle)
+                   -> (Option<Box<LayerShellHandler>>, Option<Box<SurfaceHandler>>);
+}
+
+wayland_listener!(LayerShellManager, Box<LayerShellManagerHandler>, [
+    add_listener => add_notify: |this: &mut LayerShellManager, data: *mut libc::c_void,|
+    unsafe {
+        let manager = &mut this.data;
+        let data = data as *mut wlr_layer_surface;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        wlr_log!(WLR_DEBUG, "New layer_shell_surface request {:p}", data);
+        let layer_surface = LayerShellSurface::new(data);
+
+        let (layer_surface_handler, surface_handler) =
+            manager.new_surface(compositor, layer_surface.weak_reference());
+
+        let mut layer_surface = LayerShell::new((layer_surface, layer_surface_handler));
+        let surface_state = (*(*data).surface).data as *mut InternalSurfaceState;
+        if let Some(surface_handler) = surface_handler {
+            (*(*surface_state).surface).data().1 = surface_handler;
+        }
+
+        wl_signal_add(&mut (*data).events.destroy as *mut _ as _,
+                      layer_surface.destroy_listener() as _);
+        wl_signal_add(&mut (*(*data).surface).events.commit as *mut _ as _,
+                      layer_surface.commit_listener() as _);
+        wl_signal_add(&mut (*data).events.map as *mut _ as _,
+                      layer_surface.map_listener() as _);
+        wl_signal_add(&mut (*data).events.unmap as *mut _ as _,
+                      layer_surface.unmap_listener() as _);
+        let shell_data = (*data).data as *mut LayerShellSurfaceState;
+        (*shell_data).shell = Box::into_raw(layer_surface);
+    };
+]);
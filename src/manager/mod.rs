@@ -1,27 +1,72 @@
+mod decoration_manager;
 mod drag_icon_handler;
+mod idle_inhibit_manager;
 mod input_manager;
+#[cfg(feature = "text-input")]
+mod input_method_manager;
 mod output_manager;
+mod pointer_constraints_manager;
+#[cfg(feature = "output-management")]
+mod output_management_manager;
 mod keyboard_handler;
+mod layer_shell_manager;
+mod layer_shell_handler;
 mod pointer_handler;
 mod touch_handler;
+mod switch_handler;
+mod session_handler;
 mod output_handler;
+mod output_damage_handler;
 mod xdg_shell_v6_manager;
 mod xdg_shell_v6_handler;
 mod xdg_shell_manager;
 mod xdg_shell_handler;
 mod tablet_pad_handler;
 mod tablet_tool_handler;
+#[cfg(feature = "text-input")]
+mod text_input_manager;
+mod virtual_keyboard_manager;
+#[cfg(feature = "virtual-pointer")]
+mod virtual_pointer_manager;
 
+pub use self::decoration_manager::{DecorationManager, DecorationManagerHandler, DecorationMode,
+                                   XdgToplevelDecoration, XdgToplevelDecorationHandle};
 pub use self::drag_icon_handler::{DragIconHandler, DragIconListener};
+pub use self::idle_inhibit_manager::{IdleInhibitManager, IdleInhibitManagerHandler, IdleInhibitor};
 pub use self::input_manager::{InputManager, InputManagerHandler};
+#[cfg(feature = "text-input")]
+pub use self::input_method_manager::{InputMethod, InputMethodHandle, InputMethodManager,
+                                     InputMethodManagerHandler};
+#[cfg(feature = "text-input")]
+pub(crate) use self::input_method_manager::set_input_method_global;
 pub use self::keyboard_handler::{KeyboardHandler, KeyboardWrapper};
+pub use self::layer_shell_handler::*;
+pub use self::layer_shell_manager::*;
+#[cfg(feature = "output-management")]
+pub use self::output_management_manager::{OutputConfiguration, OutputConfigurationHead,
+                                          OutputManagementManager,
+                                          OutputManagementManagerHandler};
 pub use self::output_handler::{OutputHandler, UserOutput};
+pub use self::output_damage_handler::{OutputDamageHandler, UserOutputDamage};
 pub use self::output_manager::{OutputBuilder, OutputBuilderResult, OutputManager,
                                OutputManagerHandler};
+pub use self::pointer_constraints_manager::{PointerConstraint, PointerConstraintHandle,
+                                            PointerConstraintType, PointerConstraintsManager,
+                                            PointerConstraintsManagerHandler};
 pub use self::pointer_handler::{PointerHandler, PointerWrapper};
 pub use self::tablet_pad_handler::{TabletPadHandler, TabletPadWrapper};
 pub use self::tablet_tool_handler::{TabletToolHandler, TabletToolWrapper};
+#[cfg(feature = "text-input")]
+pub use self::text_input_manager::{TextInput, TextInputHandle, TextInputManager,
+                                   TextInputManagerHandler};
+#[cfg(feature = "text-input")]
+pub(crate) use self::text_input_manager::{set_text_input_global, update_text_input_focus};
+pub use self::session_handler::{SessionHandler, SessionListener};
+pub use self::switch_handler::{SwitchHandler, SwitchWrapper};
 pub use self::touch_handler::{TouchHandler, TouchWrapper};
+pub use self::virtual_keyboard_manager::VirtualKeyboardManager;
+#[cfg(feature = "virtual-pointer")]
+pub use self::virtual_pointer_manager::VirtualPointerManager;
 pub use self::xdg_shell_v6_handler::*;
 pub use self::xdg_shell_v6_manager::*;
 pub use self::xdg_shell_handler::*;
@@ -0,0 +1,57 @@
+//! Handler for damage tracking on outputs.
+
+use std::mem::ManuallyDrop;
+
+use libc;
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::wlr_output_damage;
+
+use {OutputDamage, OutputHandle};
+use compositor::{compositor_handle, CompositorHandle};
+
+/// Handles the frames of an output that uses damage tracking.
+///
+/// Set it on an output with `Output::set_damage_handler`. It's dropped when
+/// it is replaced or the output is destroyed.
+pub trait OutputDamageHandler {
+    /// Called when the output needs a new frame according to damage tracking.
+    ///
+    /// Unlike `OutputHandler::on_frame` this is only triggered when something
+    /// on the output was damaged, so there is no need to schedule frames
+    /// manually.
+    fn on_frame(&mut self, CompositorHandle, OutputHandle, &mut OutputDamage) {}
+}
+
+wayland_listener!(UserOutputDamage, (OutputHandle, Box<OutputDamageHandler>), [
+    frame_listener => frame_notify: |this: &mut UserOutputDamage, data: *mut libc::c_void,|
+    unsafe {
+        let (ref output, ref mut handler) = this.data;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        // NOTE The damage is owned by the output, so it must not be
+        // destroyed when this goes out of scope.
+        let mut damage = ManuallyDrop::new(OutputDamage::from_ptr(data as *mut wlr_output_damage));
+
+        handler.on_frame(compositor, output.clone(), &mut damage);
+    };
+    destroy_listener => destroy_notify: |this: &mut UserOutputDamage, _data: *mut libc::c_void,|
+    unsafe {
+        let output = this.data.0.clone();
+        this.remove_listeners();
+        output.clear_damage_handler();
+    };
+]);
+
+impl UserOutputDamage {
+    /// Removes the listeners from the `wlr_output_damage` signals.
+    pub(crate) unsafe fn remove_listeners(&mut self) {
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*self.frame_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*self.destroy_listener()).link as *mut _ as _);
+    }
+}
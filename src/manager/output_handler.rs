@@ -2,6 +2,7 @@
 
 use {Output, OutputHandle, OutputState};
 use errors::HandleErr;
+use events::output_events::PresentEvent;
 use wayland_sys::server::WAYLAND_SERVER_HANDLE;
 use compositor::{compositor_handle, CompositorHandle};
 use libc;
@@ -12,6 +13,10 @@ pub trait OutputHandler {
     fn on_frame(&mut self, CompositorHandle, OutputHandle) {}
 
     /// Called every time the output mode changes.
+    ///
+    /// The new mode has already been applied, so `Output::current_mode`
+    /// returns it. This is the place to reallocate anything that depends on
+    /// the output resolution.
     fn on_mode_change(&mut self, CompositorHandle, OutputHandle) {}
 
     /// Called every time the output is enabled.
@@ -29,6 +34,15 @@ pub trait OutputHandler {
     /// Called every time the buffers need to be swapped on an output.
     fn needs_swap(&mut self, CompositorHandle, OutputHandle) {}
 
+    /// Called every time a buffer is presented on the output, with timing
+    /// information useful for frame pacing.
+    ///
+    /// If the presentation time extension is enabled (with the
+    /// `presentation-time` feature), the timings should be
+    /// forwarded to the surfaces that were shown with
+    /// `Presentation::sample_output_timings`.
+    fn on_present(&mut self, CompositorHandle, OutputHandle, &PresentEvent) {}
+
     /// Called when an output is destroyed (e.g. unplugged).
     fn destroyed(&mut self, CompositorHandle, OutputHandle) {}
 }
@@ -37,6 +51,10 @@ wayland_listener!(UserOutput, (Output, Box<OutputHandler>), [
     on_destroy_listener => on_destroy_notify: |this: &mut UserOutput, data: *mut libc::c_void,|
     unsafe {
         let output_ptr = data as *mut wlr_output;
+        let output_data = (*output_ptr).data as *mut OutputState;
+        if !output_data.is_null() {
+            (*output_data).destroyed = true;
+        }
         {
             let (ref mut output, ref mut manager) = this.data;
             let compositor = match compositor_handle() {
@@ -80,7 +98,9 @@ wayland_listener!(UserOutput, (Output, Box<OutputHandler>), [
         ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                       wl_list_remove,
                       &mut (*this.need_swap_listener()).link as *mut _ as _);
-        let output_data = (*output_ptr).data as *mut OutputState;
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.present_listener()).link as *mut _ as _);
         Box::from_raw((*output_data).output as *mut UserOutput);
     };
     frame_listener => frame_notify: |this: &mut UserOutput, _output: *mut libc::c_void,| unsafe {
@@ -152,4 +172,15 @@ wayland_listener!(UserOutput, (Output, Box<OutputHandler>), [
 
         manager.needs_swap(compositor, output.weak_reference());
     };
+    present_listener => present_notify: |this: &mut UserOutput, event: *mut libc::c_void,|
+    unsafe {
+        let (ref output, ref mut manager) = this.data;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let event = PresentEvent::from_ptr(event as _);
+
+        manager.on_present(compositor, output.weak_reference(), &event);
+    };
 ]);
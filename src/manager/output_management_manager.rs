@@ -0,0 +1,141 @@
+//! Manager for the output management protocol.
+//!
+//! Display configuration tools (e.g wlr-randr) use this to enable, move,
+//! scale, rotate or change the mode of outputs.
+
+use libc;
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::{wlr_output_configuration_head_v1, wlr_output_configuration_v1,
+                  wlr_output_configuration_v1_destroy, wlr_output_configuration_v1_send_failed,
+                  wlr_output_configuration_v1_send_succeeded};
+
+use {Origin, OutputHandle, Size, Transform};
+use compositor::{compositor_handle, CompositorHandle};
+
+/// The state a client wants a single output to be in.
+///
+/// The compositor applies it with the setters on `Output`.
+#[derive(Debug, Clone)]
+pub struct OutputConfigurationHead {
+    /// The output this state is for.
+    pub output: OutputHandle,
+    /// Whether the output should be enabled.
+    ///
+    /// The rest of the state should be ignored if it shouldn't be.
+    pub enabled: bool,
+    /// The size of the mode the output should use.
+    pub size: Size,
+    /// The refresh rate of the mode the output should use, in mHz.
+    ///
+    /// If this doesn't match one of `Output::modes` it should be set with
+    /// `Output::set_custom_mode`.
+    pub refresh: i32,
+    /// The position of the output in the output layout.
+    pub position: Origin,
+    /// The transform of the output.
+    pub transform: Transform,
+    /// The scale of the output.
+    pub scale: f64
+}
+
+/// A configuration of the outputs requested by a client.
+#[derive(Debug)]
+pub struct OutputConfiguration {
+    config: *mut wlr_output_configuration_v1
+}
+
+impl OutputConfiguration {
+    /// Get the requested state of each of the outputs.
+    pub fn heads(&self) -> Vec<OutputConfigurationHead> {
+        let mut result = Vec::new();
+        unsafe {
+            wl_list_for_each!((*self.config).heads,
+                              link,
+                              (head: wlr_output_configuration_head_v1) => {
+                let state = &(*head).state;
+                let (size, refresh) = if state.mode.is_null() {
+                    (Size::new(state.custom_mode.width, state.custom_mode.height),
+                     state.custom_mode.refresh)
+                } else {
+                    (Size::new((*state.mode).width, (*state.mode).height), (*state.mode).refresh)
+                };
+                result.push(OutputConfigurationHead { output: OutputHandle::from_ptr(state.output),
+                                                      enabled: state.enabled,
+                                                      size,
+                                                      refresh,
+                                                      position: Origin::new(state.x, state.y),
+                                                      transform: state.transform,
+                                                      scale: state.scale });
+            });
+        }
+        result
+    }
+
+    /// Tell the client whether the configuration succeeded and free it.
+    unsafe fn finish(self, succeeded: bool) {
+        if succeeded {
+            wlr_output_configuration_v1_send_succeeded(self.config)
+        } else {
+            wlr_output_configuration_v1_send_failed(self.config)
+        }
+        wlr_output_configuration_v1_destroy(self.config)
+    }
+}
+
+/// Handles configurations of the outputs requested by clients.
+///
+/// Clients only see the outputs passed to
+/// `Compositor::set_output_configuration`, which must be called whenever
+/// they change (including after applying a configuration).
+pub trait OutputManagementManagerHandler {
+    /// Called when a client wants the configuration to be applied.
+    ///
+    /// Return whether it was applied. If it wasn't, the previous
+    /// configuration should be restored before returning.
+    fn on_apply(&mut self, CompositorHandle, &OutputConfiguration) -> bool;
+
+    /// Called when a client wants to know whether the configuration could be
+    /// applied, without applying it.
+    ///
+    /// Return `false` to reject it. By default every configuration is
+    /// accepted.
+    fn on_test(&mut self, CompositorHandle, &OutputConfiguration) -> bool {
+        true
+    }
+}
+
+wayland_listener!(OutputManagementManager, Box<OutputManagementManagerHandler>, [
+    apply_listener => apply_notify: |this: &mut OutputManagementManager,
+                                     data: *mut libc::c_void,|
+    unsafe {
+        let config = OutputConfiguration { config: data as *mut wlr_output_configuration_v1 };
+        let succeeded = match compositor_handle() {
+            Some(compositor) => this.data.on_apply(compositor, &config),
+            None => false
+        };
+        config.finish(succeeded);
+    };
+    test_listener => test_notify: |this: &mut OutputManagementManager,
+                                   data: *mut libc::c_void,|
+    unsafe {
+        let config = OutputConfiguration { config: data as *mut wlr_output_configuration_v1 };
+        let succeeded = match compositor_handle() {
+            Some(compositor) => this.data.on_test(compositor, &config),
+            None => false
+        };
+        config.finish(succeeded);
+    };
+]);
+
+impl Drop for OutputManagementManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.apply_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.test_listener()).link as *mut _ as _);
+        }
+    }
+}
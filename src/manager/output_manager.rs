@@ -128,6 +128,8 @@ wayland_listener!(OutputManager, Box<OutputManagerHandler>, [
                           output.swap_buffers_listener() as _);
             wl_signal_add(&mut (*data).events.needs_swap as *mut _ as _,
                           output.need_swap_listener() as _);
+            wl_signal_add(&mut (*data).events.present as *mut _ as _,
+                          output.present_listener() as _);
             wl_signal_add(&mut (*data).events.destroy as *mut _ as _,
                           output.on_destroy_listener() as _);
             let output_data = (*data).data as *mut OutputState;
@@ -0,0 +1,247 @@
+//! Manager for pointer constraints.
+//!
+//! Clients such as games or remote desktop viewers use these to lock the
+//! pointer in place or to confine it to a region of one of their surfaces.
+
+use std::{cell::Cell, rc::{Rc, Weak}};
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wlr_pointer_constraint_v1, wlr_pointer_constraint_v1_send_activated,
+                  wlr_pointer_constraint_v1_send_deactivated, wlr_pointer_constraint_v1_type::*};
+use wlroots_sys::wlr_pointer_constraint_v1_state_field::*;
+
+use {PixmanRegion, SurfaceHandle};
+use compositor::{compositor_handle, CompositorHandle};
+use errors::{HandleErr, HandleResult};
+
+/// The ways the pointer can be constrained.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PointerConstraintType {
+    /// The pointer must not move at all.
+    Locked,
+    /// The pointer must stay within the region of the constraint.
+    Confined
+}
+
+/// A constraint a client wants placed on the pointer of a seat while it's
+/// focused on one of its surfaces.
+///
+/// The constraint does nothing until the compositor activates it with
+/// `send_activated`, which should happen once the surface has pointer
+/// focus. After that the compositor must respect it, e.g by moving the
+/// cursor with `Cursor::warp_constrained`.
+#[derive(Debug)]
+pub struct PointerConstraint {
+    liveliness: Rc<Cell<bool>>,
+    constraint: *mut wlr_pointer_constraint_v1
+}
+
+#[derive(Debug, Clone)]
+pub struct PointerConstraintHandle {
+    handle: Weak<Cell<bool>>,
+    constraint: *mut wlr_pointer_constraint_v1
+}
+
+impl PointerConstraint {
+    /// Get a handle to the surface the constraint applies to.
+    pub fn surface(&self) -> SurfaceHandle {
+        unsafe { SurfaceHandle::from_ptr((*self.constraint).surface) }
+    }
+
+    /// Get how the pointer is constrained.
+    pub fn constraint_type(&self) -> PointerConstraintType {
+        unsafe {
+            match (*self.constraint).type_ {
+                WLR_POINTER_CONSTRAINT_V1_LOCKED => PointerConstraintType::Locked,
+                WLR_POINTER_CONSTRAINT_V1_CONFINED => PointerConstraintType::Confined
+            }
+        }
+    }
+
+    /// Get the region of the surface the pointer is constrained to, in
+    /// surface-local coordinates.
+    pub fn region(&self) -> PixmanRegion {
+        unsafe { PixmanRegion::from_raw_copy(&(*self.constraint).region) }
+    }
+
+    /// Get where the client would like the cursor to be shown once a lock is
+    /// released, in surface-local coordinates.
+    pub fn cursor_hint(&self) -> Option<(f64, f64)> {
+        unsafe {
+            let current = &(*self.constraint).current;
+            if current.committed & WLR_POINTER_CONSTRAINT_V1_STATE_CURSOR_HINT as u32 == 0 {
+                None
+            } else {
+                Some((current.cursor_hint.x, current.cursor_hint.y))
+            }
+        }
+    }
+
+    /// Tell the client the constraint is now being enforced.
+    pub fn send_activated(&mut self) {
+        unsafe { wlr_pointer_constraint_v1_send_activated(self.constraint) }
+    }
+
+    /// Tell the client the constraint is no longer being enforced.
+    ///
+    /// Constraints with a oneshot lifetime are destroyed by this.
+    pub fn send_deactivated(&mut self) {
+        unsafe { wlr_pointer_constraint_v1_send_deactivated(self.constraint) }
+    }
+
+    /// Creates a weak reference to a `PointerConstraint`.
+    pub fn weak_reference(&self) -> PointerConstraintHandle {
+        PointerConstraintHandle { handle: Rc::downgrade(&self.liveliness),
+                                  constraint: self.constraint }
+    }
+}
+
+impl PointerConstraintHandle {
+    pub(crate) unsafe fn from_ptr(constraint: *mut wlr_pointer_constraint_v1) -> Self {
+        let listener = (*constraint).data as *mut PointerConstraintListener;
+        let handle = if listener.is_null() {
+            Weak::new()
+        } else {
+            Rc::downgrade(&(*listener).data.0)
+        };
+        PointerConstraintHandle { handle, constraint }
+    }
+
+    unsafe fn upgrade(&self) -> HandleResult<PointerConstraint> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            .and_then(|check| {
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                check.set(true);
+                Ok(PointerConstraint { liveliness: check,
+                                       constraint: self.constraint })
+            })
+    }
+
+    /// Run a function on the referenced `PointerConstraint`, if it still
+    /// exists.
+    ///
+    /// Returns the result of the function, if successful.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `PointerConstraint` is
+    /// already borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut PointerConstraint) -> R
+    {
+        self.try_run(runner)
+    }
+}
+
+impl_handle!(PointerConstraintHandle => PointerConstraint);
+
+impl PartialEq for PointerConstraintHandle {
+    fn eq(&self, other: &PointerConstraintHandle) -> bool {
+        self.constraint == other.constraint
+    }
+}
+
+impl Eq for PointerConstraintHandle {}
+
+/// Handles pointer constraints being created, changed and destroyed by
+/// clients.
+pub trait PointerConstraintsManagerHandler {
+    /// Called when a client creates a new pointer constraint.
+    ///
+    /// If the surface already has pointer focus the constraint should be
+    /// activated now.
+    fn on_new_constraint(&mut self, CompositorHandle, PointerConstraintHandle) {}
+
+    /// Called when the region of a constraint changes.
+    fn on_set_region(&mut self, CompositorHandle, PointerConstraintHandle) {}
+
+    /// Called when a pointer constraint is about to be destroyed.
+    fn on_destroy_constraint(&mut self, CompositorHandle, PointerConstraintHandle) {}
+}
+
+wayland_listener!(PointerConstraintsManager,
+                  (Box<PointerConstraintsManagerHandler>, Rc<Cell<bool>>), [
+    new_constraint_listener => new_constraint_notify: |this: &mut PointerConstraintsManager,
+                                                       data: *mut libc::c_void,|
+    unsafe {
+        let constraint_ptr = data as *mut wlr_pointer_constraint_v1;
+        let mut listener =
+            PointerConstraintListener::new((Rc::new(Cell::new(false)),
+                                            this as *mut PointerConstraintsManager,
+                                            Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*constraint_ptr).events.set_region as *mut _ as _,
+                      listener.set_region_listener() as _);
+        wl_signal_add(&mut (*constraint_ptr).events.destroy as *mut _ as _,
+                      listener.destroy_listener() as _);
+        (*constraint_ptr).data = Box::into_raw(listener) as *mut libc::c_void;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let constraint = PointerConstraintHandle::from_ptr(constraint_ptr);
+        this.data.0.on_new_constraint(compositor, constraint);
+    };
+]);
+
+impl Drop for PointerConstraintsManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.new_constraint_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+// The weak reference is to the liveness token of the manager, constraints can
+// outlive it and must not call into it after it's been dropped.
+wayland_listener!(PointerConstraintListener,
+                  (Rc<Cell<bool>>, *mut PointerConstraintsManager, Weak<Cell<bool>>), [
+    set_region_listener => set_region_notify: |this: &mut PointerConstraintListener,
+                                               data: *mut libc::c_void,|
+    unsafe {
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let constraint = PointerConstraintHandle::from_ptr(data as *mut wlr_pointer_constraint_v1);
+        (*manager).data.0.on_set_region(compositor, constraint);
+    };
+    destroy_listener => destroy_notify: |this: &mut PointerConstraintListener,
+                                         data: *mut libc::c_void,|
+    unsafe {
+        let constraint_ptr = data as *mut wlr_pointer_constraint_v1;
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            let constraint = PointerConstraintHandle::from_ptr(constraint_ptr);
+            (*manager).data.0.on_destroy_constraint(compositor, constraint);
+        }
+        (*constraint_ptr).data = ::std::ptr::null_mut();
+        Box::from_raw(this);
+    };
+]);
+
+impl PointerConstraintListener {
+    /// Get the manager that made the constraint, unless it's been dropped.
+    fn manager(&self) -> Option<*mut PointerConstraintsManager> {
+        self.data.2.upgrade().map(|_| self.data.1)
+    }
+}
+
+impl Drop for PointerConstraintListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.set_region_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
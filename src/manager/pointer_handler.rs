@@ -6,7 +6,9 @@ use wayland_sys::server::WAYLAND_SERVER_HANDLE;
 
 use {Pointer, PointerHandle};
 use compositor::{compositor_handle, CompositorHandle};
-use events::pointer_events::{AbsoluteMotionEvent, AxisEvent, ButtonEvent, MotionEvent};
+use events::pointer_events::{AbsoluteMotionEvent, AxisEvent, ButtonEvent, MotionEvent,
+                             PinchBeginEvent, PinchEndEvent, PinchUpdateEvent, SwipeBeginEvent,
+                             SwipeEndEvent, SwipeUpdateEvent};
 
 use wlroots_sys::{wlr_event_pointer_axis, wlr_event_pointer_button, wlr_event_pointer_motion};
 
@@ -22,6 +24,32 @@ pub trait PointerHandler {
     /// Callback that is triggered when an axis event fires.
     fn on_axis(&mut self, CompositorHandle, PointerHandle, &AxisEvent) {}
 
+    /// Callback that is triggered when a swipe gesture begins on the pointer,
+    /// e.g when three fingers start moving across a touchpad.
+    fn on_swipe_begin(&mut self, CompositorHandle, PointerHandle, &SwipeBeginEvent) {}
+
+    /// Callback that is triggered when the fingers of a swipe gesture move.
+    fn on_swipe_update(&mut self, CompositorHandle, PointerHandle, &SwipeUpdateEvent) {}
+
+    /// Callback that is triggered when a swipe gesture ends.
+    fn on_swipe_end(&mut self, CompositorHandle, PointerHandle, &SwipeEndEvent) {}
+
+    /// Callback that is triggered when a pinch gesture begins on the pointer.
+    fn on_pinch_begin(&mut self, CompositorHandle, PointerHandle, &PinchBeginEvent) {}
+
+    /// Callback that is triggered when the fingers of a pinch gesture move.
+    fn on_pinch_update(&mut self, CompositorHandle, PointerHandle, &PinchUpdateEvent) {}
+
+    /// Callback that is triggered when a pinch gesture ends.
+    fn on_pinch_end(&mut self, CompositorHandle, PointerHandle, &PinchEndEvent) {}
+
+    /// Callback that is triggered at the end of a group of pointer events.
+    ///
+    /// Events between frames (e.g a motion and an axis event) logically
+    /// happened at the same time. Usually this should be forwarded to
+    /// clients with `Seat::pointer_notify_frame`.
+    fn on_frame(&mut self, CompositorHandle, PointerHandle) {}
+
     /// Callback that is triggered when the pointer is destroyed.
     fn destroyed(&mut self, CompositorHandle, PointerHandle) {}
 }
@@ -53,6 +81,27 @@ wayland_listener!(PointerWrapper, (Pointer, Box<PointerHandler>), [
         ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                       wl_list_remove,
                       &mut (*this.axis_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.swipe_begin_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.swipe_update_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.swipe_end_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.pinch_begin_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.pinch_update_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.pinch_end_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.frame_listener()).link as *mut _ as _);
         Box::from_raw((*input_device_ptr).data as *mut PointerWrapper);
     };
     button_listener => key_notify: |this: &mut PointerWrapper, data: *mut libc::c_void,| unsafe {
@@ -97,4 +146,86 @@ wayland_listener!(PointerWrapper, (Pointer, Box<PointerHandler>), [
 
         this.data.1.on_axis(compositor, pointer.weak_reference(), &event);
     };
+    swipe_begin_listener => swipe_begin_notify: |this: &mut PointerWrapper,
+                                                 data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = SwipeBeginEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_swipe_begin(compositor, pointer.weak_reference(), &event);
+    };
+    swipe_update_listener => swipe_update_notify: |this: &mut PointerWrapper,
+                                                   data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = SwipeUpdateEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_swipe_update(compositor, pointer.weak_reference(), &event);
+    };
+    swipe_end_listener => swipe_end_notify: |this: &mut PointerWrapper,
+                                             data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = SwipeEndEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_swipe_end(compositor, pointer.weak_reference(), &event);
+    };
+    pinch_begin_listener => pinch_begin_notify: |this: &mut PointerWrapper,
+                                                 data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = PinchBeginEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_pinch_begin(compositor, pointer.weak_reference(), &event);
+    };
+    pinch_update_listener => pinch_update_notify: |this: &mut PointerWrapper,
+                                                   data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = PinchUpdateEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_pinch_update(compositor, pointer.weak_reference(), &event);
+    };
+    pinch_end_listener => pinch_end_notify: |this: &mut PointerWrapper,
+                                             data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let event = PinchEndEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_pinch_end(compositor, pointer.weak_reference(), &event);
+    };
+    frame_listener => frame_notify: |this: &mut PointerWrapper, _data: *mut libc::c_void,|
+    unsafe {
+        let pointer = &mut this.data.0;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        this.data.1.on_frame(compositor, pointer.weak_reference());
+    };
 ]);
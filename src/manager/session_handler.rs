@@ -0,0 +1,41 @@
+//! Handler for the session of the backend becoming active or inactive.
+
+use libc;
+
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::wlr_session;
+
+use compositor::{compositor_handle, CompositorHandle};
+
+/// Handles the session becoming active or inactive, e.g when switching
+/// virtual terminals.
+pub trait SessionHandler {
+    /// Called when the session becomes active (i.e the compositor's virtual
+    /// terminal is switched back to) or inactive.
+    ///
+    /// While the session is inactive the compositor has lost DRM master, so
+    /// it should stop rendering until it becomes active again.
+    fn on_active(&mut self, CompositorHandle, bool) {}
+}
+
+wayland_listener!(SessionListener, Box<SessionHandler>, [
+    active_listener => active_notify: |this: &mut SessionListener, data: *mut libc::c_void,|
+    unsafe {
+        let session = data as *mut wlr_session;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.on_active(compositor, (*session).active);
+    };
+]);
+
+impl Drop for SessionListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.active_listener()).link as *mut _ as _);
+        }
+    }
+}
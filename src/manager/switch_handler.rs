@@ -0,0 +1,56 @@
+//! Handler for switch devices, such as the lid of a laptop.
+
+use libc;
+use wlroots_sys::wlr_input_device;
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+
+use compositor::{compositor_handle, CompositorHandle};
+use events::switch_events::SwitchEvent;
+use types::input::{Switch, SwitchHandle};
+
+/// Handles the events of a single switch device.
+///
+/// Return one from `InputManagerHandler::switch_added` to receive the events
+/// of a newly added switch.
+pub trait SwitchHandler {
+    /// Callback that is triggered when the switch is flipped, e.g. when the
+    /// lid of a laptop is opened or closed.
+    fn on_toggle(&mut self, CompositorHandle, SwitchHandle, &SwitchEvent) {}
+
+    /// Callback that is triggered when the switch is destroyed.
+    fn destroyed(&mut self, CompositorHandle, SwitchHandle) {}
+}
+
+wayland_listener!(SwitchWrapper, (Switch, Box<SwitchHandler>), [
+    on_destroy_listener => on_destroy_notify: |this: &mut SwitchWrapper, data: *mut libc::c_void,|
+    unsafe {
+        let input_device_ptr = data as *mut wlr_input_device;
+        {
+            let (ref mut switch, ref mut switch_handler) = this.data;
+            let compositor = match compositor_handle() {
+                Some(handle) => handle,
+                None => return
+            };
+            switch_handler.destroyed(compositor, switch.weak_reference());
+        }
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.on_destroy_listener()).link as *mut _ as _);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_list_remove,
+                      &mut (*this.toggle_listener()).link as *mut _ as _);
+        Box::from_raw((*input_device_ptr).data as *mut SwitchWrapper);
+    };
+    toggle_listener => toggle_notify: |this: &mut SwitchWrapper, data: *mut libc::c_void,| unsafe {
+        let (ref switch, ref mut handler) = this.data;
+        let event = SwitchEvent::from_ptr(data as *mut _);
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        handler.on_toggle(compositor,
+                          switch.weak_reference(),
+                          &event);
+    };
+]);
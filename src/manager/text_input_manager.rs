@@ -0,0 +1,417 @@
+//! Manager for the text input protocol.
+//!
+//! Clients use text inputs to receive text from an input method (e.g for
+//! CJK input or an emoji picker) instead of raw key events.
+//!
+//! The text inputs of a seat automatically follow its keyboard focus, and
+//! their state is forwarded to the input method of the seat (see
+//! `InputMethodManagerHandler`).
+
+use std::{ptr, cell::{Cell, RefCell}, rc::{Rc, Weak}};
+
+use libc;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wlr_seat, wlr_surface, wlr_text_input_manager_v3, wlr_text_input_v3,
+                  wlr_text_input_v3_send_commit_string,
+                  wlr_text_input_v3_send_delete_surrounding_text, wlr_text_input_v3_send_done,
+                  wlr_text_input_v3_send_enter, wlr_text_input_v3_send_leave,
+                  wlr_text_input_v3_send_preedit_string};
+
+use {Area, Origin, SeatHandle, Size, SurfaceHandle};
+use compositor::{compositor_handle, CompositorHandle};
+use errors::{HandleErr, HandleResult};
+use utils::{c_to_rust_string, safe_as_cstring};
+
+use super::input_method_manager::{activate_input_method, deactivate_input_method,
+                                  send_text_input_state};
+
+thread_local! {
+    /// The text input global that is currently advertised, if there is one.
+    ///
+    /// Used to move the text inputs of a seat along with its keyboard focus.
+    static TEXT_INPUT_GLOBAL: RefCell<*mut wlr_text_input_manager_v3> =
+        RefCell::new(ptr::null_mut());
+}
+
+/// Get the global stored in `TEXT_INPUT_GLOBAL`, which is null if there is none.
+fn text_input_global() -> *mut wlr_text_input_manager_v3 {
+    TEXT_INPUT_GLOBAL.with(|global| *global.borrow())
+}
+
+/// A text input of a client, bound to a seat.
+///
+/// It receives text for the surface of the client that has the keyboard focus
+/// of the seat, once the client enables it.
+#[derive(Debug)]
+pub struct TextInput {
+    liveliness: Rc<Cell<bool>>,
+    text_input: *mut wlr_text_input_v3
+}
+
+impl TextInput {
+    /// Get a handle to the seat the text input belongs to.
+    pub fn seat(&self) -> SeatHandle {
+        unsafe { SeatHandle::from_ptr((*self.text_input).seat) }
+    }
+
+    /// Get a handle to the surface the text input is sending text to, if
+    /// it has been entered.
+    pub fn focused_surface(&self) -> Option<SurfaceHandle> {
+        unsafe {
+            let surface = (*self.text_input).focused_surface;
+            if surface.is_null() {
+                None
+            } else {
+                Some(SurfaceHandle::from_ptr(surface))
+            }
+        }
+    }
+
+    /// Determines if the client has enabled the text input.
+    pub fn is_enabled(&self) -> bool {
+        unsafe { (*self.text_input).current_enabled }
+    }
+
+    /// Get the text around the cursor, with the cursor and anchor (i.e the
+    /// other end of the selection) as byte offsets into it.
+    ///
+    /// Returns `None` if the client didn't provide it.
+    pub fn surrounding_text(&self) -> Option<(String, i32, i32)> {
+        unsafe { surrounding_text(self.text_input) }
+    }
+
+    /// Get the area of the cursor, in surface-local coordinates.
+    ///
+    /// This is where a candidate window of the input method should be shown.
+    pub fn cursor_rectangle(&self) -> Area {
+        unsafe {
+            let rect = &(*self.text_input).current.cursor_rectangle;
+            Area::new(Origin::new(rect.x, rect.y), Size::new(rect.width, rect.height))
+        }
+    }
+
+    /// Send text that hasn't been committed yet (e.g the syllables of a word
+    /// that is still being composed).
+    ///
+    /// The cursor is given as byte offsets into the text. It only takes
+    /// effect with `send_done`.
+    pub fn send_preedit_string(&mut self, text: &str, cursor_begin: u32, cursor_end: u32) {
+        let text = safe_as_cstring(text);
+        unsafe {
+            wlr_text_input_v3_send_preedit_string(self.text_input,
+                                                  text.as_ptr(),
+                                                  cursor_begin,
+                                                  cursor_end)
+        }
+    }
+
+    /// Send text to be inserted at the cursor.
+    ///
+    /// It only takes effect with `send_done`.
+    pub fn send_commit_string(&mut self, text: &str) {
+        let text = safe_as_cstring(text);
+        unsafe { wlr_text_input_v3_send_commit_string(self.text_input, text.as_ptr()) }
+    }
+
+    /// Ask the client to delete text around the cursor, in bytes.
+    ///
+    /// It only takes effect with `send_done`.
+    pub fn send_delete_surrounding_text(&mut self, before_length: u32, after_length: u32) {
+        unsafe {
+            wlr_text_input_v3_send_delete_surrounding_text(self.text_input,
+                                                           before_length,
+                                                           after_length)
+        }
+    }
+
+    /// Apply the changes sent since the last call.
+    pub fn send_done(&mut self) {
+        unsafe { wlr_text_input_v3_send_done(self.text_input) }
+    }
+
+    /// Creates a weak reference to a `TextInput`.
+    pub fn weak_reference(&self) -> TextInputHandle {
+        TextInputHandle { handle: Rc::downgrade(&self.liveliness),
+                          text_input: self.text_input }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TextInputHandle {
+    handle: Weak<Cell<bool>>,
+    text_input: *mut wlr_text_input_v3
+}
+
+impl TextInputHandle {
+    pub(crate) unsafe fn from_ptr(text_input: *mut wlr_text_input_v3) -> Self {
+        let listener = (*text_input).data as *mut TextInputListener;
+        let handle = if listener.is_null() {
+            Weak::new()
+        } else {
+            Rc::downgrade(&(*listener).data.0)
+        };
+        TextInputHandle { handle, text_input }
+    }
+
+    unsafe fn upgrade(&self) -> HandleResult<TextInput> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            .and_then(|check| {
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                check.set(true);
+                Ok(TextInput { liveliness: check,
+                               text_input: self.text_input })
+            })
+    }
+
+    /// Run a function on the referenced `TextInput`, if it still exists.
+    ///
+    /// Returns the result of the function, if successful.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `TextInput` is already
+    /// borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut TextInput) -> R
+    {
+        self.try_run(runner)
+    }
+}
+
+impl_handle!(TextInputHandle => TextInput);
+
+impl PartialEq for TextInputHandle {
+    fn eq(&self, other: &TextInputHandle) -> bool {
+        self.text_input == other.text_input
+    }
+}
+
+impl Eq for TextInputHandle {}
+
+/// Get the text around the cursor of the text input, see
+/// `TextInput::surrounding_text`.
+unsafe fn surrounding_text(text_input: *mut wlr_text_input_v3) -> Option<(String, i32, i32)> {
+    let surrounding = &(*text_input).current.surrounding;
+    c_to_rust_string(surrounding.text).map(|text| (text, surrounding.cursor, surrounding.anchor))
+}
+
+/// Handles text inputs being created, changed and destroyed by clients.
+pub trait TextInputManagerHandler {
+    /// Called when a client creates a new text input.
+    fn on_new_text_input(&mut self, CompositorHandle, TextInputHandle) {}
+
+    /// Called when a client enables a text input, e.g because a text field
+    /// was focused.
+    ///
+    /// The input method of the seat has already been activated.
+    fn on_enable(&mut self, CompositorHandle, TextInputHandle) {}
+
+    /// Called when a client commits the text around the cursor, with the
+    /// cursor and anchor as byte offsets into it.
+    ///
+    /// It has already been sent to the input method of the seat.
+    fn on_surrounding_text(&mut self, CompositorHandle, TextInputHandle, &str, i32, i32) {}
+
+    /// Called when a client disables a text input.
+    fn on_disable(&mut self, CompositorHandle, TextInputHandle) {}
+
+    /// Called when a text input is about to be destroyed.
+    fn on_destroy_text_input(&mut self, CompositorHandle, TextInputHandle) {}
+}
+
+wayland_listener!(TextInputManager, (Box<TextInputManagerHandler>, Rc<Cell<bool>>), [
+    text_input_listener => text_input_notify: |this: &mut TextInputManager,
+                                               data: *mut libc::c_void,|
+    unsafe {
+        let text_input_ptr = data as *mut wlr_text_input_v3;
+        let mut listener = TextInputListener::new((Rc::new(Cell::new(false)),
+                                                   this as *mut TextInputManager,
+                                                   Rc::downgrade(&this.data.1)));
+        wl_signal_add(&mut (*text_input_ptr).events.enable as *mut _ as _,
+                      listener.enable_listener() as _);
+        wl_signal_add(&mut (*text_input_ptr).events.commit as *mut _ as _,
+                      listener.commit_listener() as _);
+        wl_signal_add(&mut (*text_input_ptr).events.disable as *mut _ as _,
+                      listener.disable_listener() as _);
+        wl_signal_add(&mut (*text_input_ptr).events.destroy as *mut _ as _,
+                      listener.destroy_listener() as _);
+        (*text_input_ptr).data = Box::into_raw(listener) as *mut libc::c_void;
+        // The client may already have keyboard focus.
+        let seat = (*text_input_ptr).seat;
+        let focused_surface = (*seat).keyboard_state.focused_surface;
+        if !focused_surface.is_null() && same_client(text_input_ptr, focused_surface) {
+            wlr_text_input_v3_send_enter(text_input_ptr, focused_surface);
+        }
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        this.data.0.on_new_text_input(compositor, TextInputHandle::from_ptr(text_input_ptr));
+    };
+]);
+
+impl Drop for TextInputManager {
+    fn drop(&mut self) {
+        unsafe {
+            TEXT_INPUT_GLOBAL.with(|global| *global.borrow_mut() = ptr::null_mut());
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.text_input_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+// The weak reference is to the liveness token of the manager, text inputs can
+// outlive it and must not call into it after it's been dropped.
+wayland_listener!(TextInputListener,
+                  (Rc<Cell<bool>>, *mut TextInputManager, Weak<Cell<bool>>), [
+    enable_listener => enable_notify: |this: &mut TextInputListener, data: *mut libc::c_void,|
+    unsafe {
+        let text_input_ptr = data as *mut wlr_text_input_v3;
+        activate_input_method((*text_input_ptr).seat, text_input_ptr);
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        (*manager).data.0.on_enable(compositor, TextInputHandle::from_ptr(text_input_ptr));
+    };
+    commit_listener => commit_notify: |this: &mut TextInputListener, data: *mut libc::c_void,|
+    unsafe {
+        let text_input_ptr = data as *mut wlr_text_input_v3;
+        if !(*text_input_ptr).current_enabled {
+            return
+        }
+        send_text_input_state((*text_input_ptr).seat, text_input_ptr);
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let text_input = TextInputHandle::from_ptr(text_input_ptr);
+        if let Some((text, cursor, anchor)) = surrounding_text(text_input_ptr) {
+            (*manager).data.0.on_surrounding_text(compositor, text_input, &text, cursor, anchor);
+        }
+    };
+    disable_listener => disable_notify: |this: &mut TextInputListener, data: *mut libc::c_void,|
+    unsafe {
+        let text_input_ptr = data as *mut wlr_text_input_v3;
+        deactivate_input_method((*text_input_ptr).seat);
+        let manager = match this.manager() {
+            Some(manager) => manager,
+            None => return
+        };
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        (*manager).data.0.on_disable(compositor, TextInputHandle::from_ptr(text_input_ptr));
+    };
+    destroy_listener => destroy_notify: |this: &mut TextInputListener, data: *mut libc::c_void,|
+    unsafe {
+        let text_input_ptr = data as *mut wlr_text_input_v3;
+        if (*text_input_ptr).current_enabled {
+            deactivate_input_method((*text_input_ptr).seat);
+        }
+        if let (Some(manager), Some(compositor)) = (this.manager(), compositor_handle()) {
+            let text_input = TextInputHandle::from_ptr(text_input_ptr);
+            (*manager).data.0.on_destroy_text_input(compositor, text_input);
+        }
+        (*text_input_ptr).data = ptr::null_mut();
+        Box::from_raw(this);
+    };
+]);
+
+impl TextInputListener {
+    /// Get the manager that made the text input, unless it's been dropped.
+    fn manager(&self) -> Option<*mut TextInputManager> {
+        self.data.2.upgrade().map(|_| self.data.1)
+    }
+}
+
+impl Drop for TextInputListener {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.enable_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.commit_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.disable_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.destroy_listener()).link as *mut _ as _);
+        }
+    }
+}
+
+/// Use the global to move text inputs along with the keyboard focus.
+///
+/// It is unset when the `TextInputManager` is dropped.
+pub(crate) unsafe fn set_text_input_global(global: *mut wlr_text_input_manager_v3) {
+    TEXT_INPUT_GLOBAL.with(|cell| *cell.borrow_mut() = global)
+}
+
+/// Determines if the text input and the surface belong to the same client.
+unsafe fn same_client(text_input: *mut wlr_text_input_v3, surface: *mut wlr_surface) -> bool {
+    let text_input_client =
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_client, (*text_input).resource as _);
+    let surface_client =
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_client, (*surface).resource as _);
+    text_input_client == surface_client
+}
+
+/// Get the text input of the seat that is enabled on the surface with
+/// keyboard focus, if there is one.
+pub(crate) unsafe fn focused_text_input(seat: *mut wlr_seat) -> *mut wlr_text_input_v3 {
+    let mut result = ptr::null_mut();
+    let global = text_input_global();
+    if global.is_null() {
+        return result
+    }
+    wl_list_for_each!((*global).text_inputs,
+                      link,
+                      (text_input: wlr_text_input_v3) => {
+        if (*text_input).seat == seat
+            && !(*text_input).focused_surface.is_null()
+            && (*text_input).current_enabled {
+            result = text_input;
+        }
+    });
+    result
+}
+
+/// Move the text inputs of the seat to the surface that now has its keyboard
+/// focus.
+pub(crate) unsafe fn update_text_input_focus(seat: *mut wlr_seat) {
+    let global = text_input_global();
+    if global.is_null() {
+        return
+    }
+    let focused_surface = (*seat).keyboard_state.focused_surface;
+    wl_list_for_each!((*global).text_inputs,
+                      link,
+                      (text_input: wlr_text_input_v3) => {
+        if (*text_input).seat == seat && (*text_input).focused_surface != focused_surface {
+            if !(*text_input).focused_surface.is_null() {
+                if (*text_input).current_enabled {
+                    deactivate_input_method(seat);
+                }
+                wlr_text_input_v3_send_leave(text_input);
+            }
+            if !focused_surface.is_null() && same_client(text_input, focused_surface) {
+                wlr_text_input_v3_send_enter(text_input, focused_surface);
+            }
+        }
+    });
+}
@@ -8,6 +8,12 @@ use compositor::{compositor_handle, CompositorHandle};
 use events::touch_events::{CancelEvent, DownEvent, MotionEvent, UpEvent};
 use types::input::{Touch, TouchHandle};
 
+/// Handles the events of a single touch device (e.g. a touchscreen).
+///
+/// Return one from `InputManagerHandler::touch_added` to receive the events
+/// of a newly added touch device.
+///
+/// Each finger is tracked by its own touch id, which is available on every event.
 pub trait TouchHandler {
     /// Callback that is triggered when the user starts touching the
     /// screen/input device.
@@ -17,11 +23,12 @@ pub trait TouchHandler {
     /// screen/input device.
     fn on_up(&mut self, CompositorHandle, TouchHandle, &UpEvent) {}
 
-    /// Callback that is triggered when the user moves his fingers along the
+    /// Callback that is triggered when the user moves their fingers along the
     /// screen/input device.
     fn on_motion(&mut self, CompositorHandle, TouchHandle, &MotionEvent) {}
 
-    /// Callback triggered when the touch is canceled.
+    /// Callback triggered when the touch is canceled, e.g. because the
+    /// touch sequence was taken over as a gesture.
     fn on_cancel(&mut self, CompositorHandle, TouchHandle, &CancelEvent) {}
 
     /// Callback that is triggered when the touch is destroyed.
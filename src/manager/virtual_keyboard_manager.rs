@@ -0,0 +1,48 @@
+//! Manager for virtual keyboards.
+//!
+//! On-screen keyboards, remote input and automation tools use these to
+//! inject key events as if they came from a physical keyboard.
+
+use std::{cell::Cell, rc::Weak};
+
+use libc;
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::wlr_virtual_keyboard_v1;
+
+use super::InputManager;
+
+// The weak reference is to the liveness token of the input manager, the
+// global can outlive it and must not call into it after it's been dropped.
+wayland_listener!(VirtualKeyboardManager, (*mut InputManager, Weak<Cell<bool>>), [
+    new_virtual_keyboard_listener => new_virtual_keyboard_notify:
+    |this: &mut VirtualKeyboardManager, data: *mut libc::c_void,| unsafe {
+        let virtual_keyboard = data as *mut wlr_virtual_keyboard_v1;
+        let input_manager = match this.input_manager() {
+            Some(input_manager) => input_manager,
+            None => return
+        };
+        wlr_log!(WLR_DEBUG, "New virtual keyboard {:p}", virtual_keyboard);
+        // Virtual keyboards are handed to the input manager like any other
+        // keyboard, so their keys go through the same `KeyboardHandler`.
+        InputManager::add_notify((*input_manager).add_listener(),
+                                 &mut (*virtual_keyboard).input_device as *mut _ as _);
+    };
+]);
+
+impl VirtualKeyboardManager {
+    /// Get the input manager virtual keyboards are handed to, unless it's
+    /// been dropped.
+    fn input_manager(&self) -> Option<*mut InputManager> {
+        self.data.1.upgrade().map(|_| self.data.0)
+    }
+}
+
+impl Drop for VirtualKeyboardManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.new_virtual_keyboard_listener()).link as *mut _ as _);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Manager for virtual pointers.
+//!
+//! Remote input and automation tools (e.g ydotool) use these to inject
+//! pointer motion, buttons and scrolling as if they came from a physical
+//! pointer.
+
+use std::{cell::Cell, rc::Weak};
+
+use libc;
+use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wlroots_sys::wlr_virtual_pointer_v1_new_pointer_event;
+
+use OutputHandle;
+use compositor::compositor_handle;
+use types::input::PointerHandle;
+
+use super::InputManager;
+
+// The weak reference is to the liveness token of the input manager, the
+// global can outlive it and must not call into it after it's been dropped.
+wayland_listener!(VirtualPointerManager, (*mut InputManager, Weak<Cell<bool>>), [
+    new_virtual_pointer_listener => new_virtual_pointer_notify:
+    |this: &mut VirtualPointerManager, data: *mut libc::c_void,| unsafe {
+        let event = data as *mut wlr_virtual_pointer_v1_new_pointer_event;
+        let virtual_pointer = (*event).new_pointer;
+        let input_manager = match this.input_manager() {
+            Some(input_manager) => input_manager,
+            None => return
+        };
+        let device = &mut (*virtual_pointer).input_device;
+        wlr_log!(WLR_DEBUG, "New virtual pointer {:p}", virtual_pointer);
+        // Virtual pointers are handed to the input manager like any other
+        // pointer, so their events go through the same `PointerHandler`.
+        InputManager::add_notify((*input_manager).add_listener(), device as *mut _ as _);
+        // The device data is only set if `pointer_added` returned a handler.
+        if device.data.is_null() {
+            return
+        }
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let pointer = PointerHandle::from_ptr(device.__bindgen_anon_1.pointer);
+        let output = if (*event).suggested_output.is_null() {
+            None
+        } else {
+            Some(OutputHandle::from_ptr((*event).suggested_output))
+        };
+        (*input_manager).handler().virtual_pointer_added(compositor, pointer, output);
+    };
+]);
+
+impl VirtualPointerManager {
+    /// Get the input manager virtual pointers are handed to, unless it's
+    /// been dropped.
+    fn input_manager(&self) -> Option<*mut InputManager> {
+        self.data.1.upgrade().map(|_| self.data.0)
+    }
+}
+
+impl Drop for VirtualPointerManager {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*self.new_virtual_pointer_listener()).link as *mut _ as _);
+        }
+    }
+}
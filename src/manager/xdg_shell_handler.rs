@@ -16,7 +16,6 @@ pub trait XdgShellHandler {
     fn on_commit(&mut self, CompositorHandle, SurfaceHandle, XdgShellSurfaceHandle) {}
 
     /// Called when the wayland shell is destroyed (e.g by the user)
-
     fn destroyed(&mut self, CompositorHandle, XdgShellSurfaceHandle) {}
 
     /// Called when the ping request timed out.
@@ -64,6 +63,14 @@ pub trait XdgShellHandler {
                                 XdgShellSurfaceHandle,
                                 &ShowWindowMenuEvent) {
     }
+
+    /// Called when the surface is ready to be mapped. It should be added to the list of views at
+    /// this time.
+    fn map_request(&mut self, CompositorHandle, SurfaceHandle, XdgShellSurfaceHandle) {}
+
+    /// Called when the surface should be unmapped. It should be removed from the list of views at
+    /// this time, but may be remapped at a later time.
+    fn unmap_request(&mut self, CompositorHandle, SurfaceHandle, XdgShellSurfaceHandle) {}
 }
 
 wayland_listener!(XdgShell, (XdgShellSurface, Option<Box<XdgShellHandler>>), [
@@ -95,6 +102,36 @@ wayland_listener!(XdgShell, (XdgShellSurface, Option<Box<XdgShellHandler>>), [
                           surface,
                           shell_surface.weak_reference());
     };
+    map_listener => map_notify: |this: &mut XdgShell, _data: *mut libc::c_void,| unsafe {
+        let (ref mut shell_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ss, Some(manager)) => (ss, manager)
+        };
+        let surface = shell_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        manager.map_request(compositor,
+                            surface,
+                            shell_surface.weak_reference());
+    };
+    unmap_listener => unmap_notify: |this: &mut XdgShell, _data: *mut libc::c_void,| unsafe {
+        let (ref mut shell_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ss, Some(manager)) => (ss, manager)
+        };
+        let surface = shell_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+
+        manager.unmap_request(compositor,
+                              surface,
+                              shell_surface.weak_reference());
+    };
     ping_timeout_listener => ping_timeout_notify: |this: &mut XdgShell,
                                                    _data: *mut libc::c_void,|
     unsafe {
@@ -248,6 +285,12 @@ impl Drop for XdgShell {
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                         wl_list_remove,
                         &mut (*self.ping_timeout_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                        wl_list_remove,
+                        &mut (*self.map_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                        wl_list_remove,
+                        &mut (*self.unmap_listener()).link as *mut _ as _);
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                         wl_list_remove,
                         &mut (*self.new_popup_listener()).link as *mut _ as _);
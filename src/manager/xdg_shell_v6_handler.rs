@@ -75,9 +75,9 @@ pub trait XdgV6ShellHandler {
     /// Called when the surface should be unmapped. It should be removed from the list of views at
     /// this time, but may be remapped at a later time.
     fn unmap_request(&mut self,
-                   CompositorHandle,
-                   SurfaceHandle,
-                   XdgV6ShellSurfaceHandle) {
+                     CompositorHandle,
+                     SurfaceHandle,
+                     XdgV6ShellSurfaceHandle) {
     }
 }
 
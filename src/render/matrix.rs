@@ -1,4 +1,6 @@
-//! TODO Documentation
+//! Matrix math for rendering, over the `wlr_matrix_*` functions.
+//!
+//! Matrices are 3x3 and stored row major in a `[f32; 9]`.
 
 use Area;
 use wlroots_sys::{wl_output_transform, wlr_matrix_identity, wlr_matrix_multiply,
@@ -67,6 +69,7 @@ pub fn matrix_projection(mut matrix: [f32; 9],
     matrix
 }
 
+/// Transpose the matrix.
 pub fn matrix_transpose(matrix: [f32; 9]) -> [f32; 9] {
     let mut result = [0.0; 9];
     unsafe {
@@ -75,6 +78,8 @@ pub fn matrix_transpose(matrix: [f32; 9]) -> [f32; 9] {
     result
 }
 
+/// Get the matrix that renders a box with the given area, transform and
+/// rotation (in radians) using the projection matrix of an output.
 pub fn project_box(area: Area,
                    transform: wl_output_transform,
                    rotation: f32,
@@ -90,3 +95,62 @@ pub fn project_box(area: Area,
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: [f32; 9] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+
+    fn assert_close(actual: [f32; 9], expected: [f32; 9]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn identity() {
+        let mut matrix = [5.0; 9];
+        matrix_identity(&mut matrix);
+        assert_eq!(matrix, IDENTITY);
+        assert_eq!(matrix_transpose(IDENTITY), IDENTITY);
+        assert_close(matrix_rotate(IDENTITY, 0.0), IDENTITY);
+        assert_close(matrix_transform(IDENTITY, wl_output_transform::WL_OUTPUT_TRANSFORM_NORMAL),
+                     IDENTITY);
+    }
+
+    #[test]
+    fn multiplying_by_identity() {
+        let matrix = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        assert_eq!(matrix_multiply(IDENTITY, matrix), matrix);
+        assert_eq!(matrix_multiply(matrix, IDENTITY), matrix);
+    }
+
+    #[test]
+    fn composing_translations_and_scales() {
+        assert_eq!(matrix_translate(3.0, 4.0),
+                   [1.0, 0.0, 3.0, 0.0, 1.0, 4.0, 0.0, 0.0, 1.0]);
+        assert_eq!(matrix_scale(2.0, 5.0),
+                   [2.0, 0.0, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(matrix_multiply(matrix_translate(1.0, 2.0), matrix_translate(3.0, 4.0)),
+                   matrix_translate(4.0, 6.0));
+        assert_eq!(matrix_multiply(matrix_scale(2.0, 3.0), matrix_scale(4.0, 5.0)),
+                   matrix_scale(8.0, 15.0));
+        // Scaling after translating scales the translation as well.
+        assert_eq!(matrix_multiply(matrix_scale(2.0, 2.0), matrix_translate(3.0, 4.0)),
+                   [2.0, 0.0, 6.0, 0.0, 2.0, 8.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn transposing() {
+        let matrix = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        assert_eq!(matrix_transpose(matrix), [1.0, 4.0, 7.0, 2.0, 5.0, 8.0, 3.0, 6.0, 9.0]);
+        assert_eq!(matrix_transpose(matrix_transpose(matrix)), matrix);
+    }
+
+    #[test]
+    fn rotating_a_full_turn() {
+        let matrix = matrix_translate(3.0, 4.0);
+        assert_close(matrix_rotate(matrix, 2.0 * ::std::f32::consts::PI), matrix);
+    }
+}
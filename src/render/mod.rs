@@ -2,8 +2,10 @@ mod renderer;
 mod texture;
 mod matrix;
 mod image;
+mod occlusion;
 
 pub use self::image::*;
 pub use self::matrix::*;
+pub use self::occlusion::OcclusionTracker;
 pub use self::renderer::{GenericRenderer, Renderer};
 pub use self::texture::{Texture, TextureFormat};
@@ -0,0 +1,89 @@
+//! Helper for skipping the parts of surfaces that are hidden behind opaque
+//! surfaces stacked above them.
+
+use {Area, PixmanRegion};
+
+/// Tracks which parts of the screen are covered by opaque surfaces.
+///
+/// Surfaces must be passed to `visible_region` from the top of the stack to
+/// the bottom. Each call returns the part of the surface that is not covered
+/// by anything passed in before it, which can then be used to skip the
+/// surface entirely (if the region is empty) or to scissor the render.
+#[derive(Debug, Default)]
+pub struct OcclusionTracker {
+    /// Everything covered by an opaque surface so far, in layout coordinates.
+    occluded: PixmanRegion
+}
+
+impl OcclusionTracker {
+    /// Make a new tracker with nothing occluded.
+    pub fn new() -> Self {
+        OcclusionTracker::default()
+    }
+
+    /// Get the still visible region of a surface occupying `area`.
+    ///
+    /// `opaque` is the opaque region of the surface, in surface local
+    /// coordinates. It is added to the occluded region so that it hides the
+    /// surfaces passed in afterwards.
+    ///
+    /// The returned region is in layout coordinates.
+    pub fn visible_region(&mut self, area: Area, opaque: &PixmanRegion) -> PixmanRegion {
+        let bounds = PixmanRegion::from_area(area);
+        let mut visible = bounds.clone();
+        visible.subtract(&self.occluded);
+
+        let mut opaque = opaque.clone();
+        opaque.translate(area.origin.x, area.origin.y);
+        // A surface can't occlude anything outside of its own bounds.
+        opaque.intersect(&bounds);
+        self.occluded.union(&opaque);
+        visible
+    }
+
+    /// Forget about all previously occluded regions, e.g. at the start of a
+    /// new frame.
+    pub fn reset(&mut self) {
+        self.occluded = PixmanRegion::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Origin, Size};
+
+    fn area(x: i32, y: i32, width: i32, height: i32) -> Area {
+        Area::new(Origin::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn fully_covered_output() {
+        let output = area(0, 0, 1920, 1080);
+        let mut tracker = OcclusionTracker::new();
+        // A fullscreen opaque surface on top, opaque region in local coordinates.
+        let opaque = PixmanRegion::from_area(area(0, 0, 1920, 1080));
+        let fullscreen = tracker.visible_region(output, &opaque);
+        assert_eq!(fullscreen.rectangles(), vec![output]);
+        // Nothing below it is visible.
+        let below = tracker.visible_region(area(100, 100, 800, 600), &PixmanRegion::new());
+        assert!(below.is_empty());
+        let background = tracker.visible_region(output, &opaque);
+        assert!(background.is_empty());
+    }
+
+    #[test]
+    fn opaque_region_is_local_and_clipped() {
+        let mut tracker = OcclusionTracker::new();
+        // Opaque region sticks out of the surface, only the overlap counts.
+        let top = tracker.visible_region(area(100, 100, 50, 50),
+                                         &PixmanRegion::from_area(area(0, 0, 100, 100)));
+        assert_eq!(top.rectangles(), vec![area(100, 100, 50, 50)]);
+        let below = tracker.visible_region(area(100, 100, 100, 50), &PixmanRegion::new());
+        assert_eq!(below.rectangles(), vec![area(150, 100, 50, 50)]);
+
+        tracker.reset();
+        let below = tracker.visible_region(area(100, 100, 100, 50), &PixmanRegion::new());
+        assert_eq!(below.rectangles(), vec![area(100, 100, 100, 50)]);
+    }
+}
@@ -1,16 +1,16 @@
 //! TODO Documentation
 
-use std::time::Duration;
+use std::{ptr, time::Duration};
 
-use libc::{c_float, c_int, c_void};
+use libc::{c_float, c_int};
 
-use {Area, Output, PixmanRegion};
+use {Area, Origin, Output, PixmanRegion, Size};
 use render::Texture;
 use wlroots_sys::{wl_shm_format, wlr_backend, wlr_backend_get_renderer,
                   wlr_render_ellipse_with_matrix, wlr_render_quad_with_matrix, wlr_render_rect,
                   wlr_render_texture, wlr_render_texture_with_matrix, wlr_renderer,
                   wlr_renderer_begin, wlr_renderer_clear, wlr_renderer_destroy, wlr_renderer_end,
-                  wlr_texture_from_pixels, wlr_texture_destroy};
+                  wlr_renderer_scissor, wlr_texture_destroy};
 
 /// A generic interface for rendering to the screen.
 ///
@@ -72,6 +72,8 @@ impl GenericRenderer {
     }
 
     /// Create a texture using this renderer.
+    ///
+    /// See `Texture::from_pixels`.
     pub fn create_texture_from_pixels(&mut self,
                                       format: wl_shm_format,
                                       stride: u32,
@@ -80,12 +82,7 @@ impl GenericRenderer {
                                       data: &[u8])
                                       -> Option<Texture<'static>> {
         unsafe {
-            create_texture_from_pixels(self.renderer,
-                                       format,
-                                       stride,
-                                       width,
-                                       height,
-                                       data.as_ptr() as _)
+            Texture::from_raw_pixels(self.renderer, format, stride, width, height, data)
         }
     }
 
@@ -102,6 +99,8 @@ impl Drop for GenericRenderer {
 
 impl<'output> Renderer<'output> {
     /// Create a texture using this renderer.
+    ///
+    /// See `Texture::from_pixels`.
     pub fn create_texture_from_pixels(&mut self,
                                       format: wl_shm_format,
                                       stride: u32,
@@ -110,19 +109,52 @@ impl<'output> Renderer<'output> {
                                       data: &[u8])
                                       -> Option<Texture<'static>> {
         unsafe {
-            create_texture_from_pixels(self.renderer,
-                                       format,
-                                       stride,
-                                       width,
-                                       height,
-                                       data.as_ptr() as _)
+            Texture::from_raw_pixels(self.renderer, format, stride, width, height, data)
         }
     }
 
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_renderer {
+        self.renderer
+    }
+
+    /// Fills the output with the color, in RGBA format.
+    ///
+    /// This should be done at the start of a frame, since the buffer may
+    /// still hold the contents of an older frame. Only the area set with
+    /// `scissor` is cleared, so with damage tracking this can be used to
+    /// clear just the damaged parts.
+    ///
+    /// To fill only part of the output use `render_colored_rect`.
     pub fn clear(&mut self, float: [f32; 4]) {
         unsafe { wlr_renderer_clear(self.renderer, float.as_ptr()) }
     }
 
+    /// Restricts all rendering to the area, or lifts the restriction if
+    /// `None` is passed.
+    ///
+    /// The area is in output pixels, not layout coordinates, and is clamped
+    /// to the bounds of the output. This is usually used to only redraw the
+    /// damaged parts of the output.
+    pub fn scissor<T>(&mut self, area: T)
+        where T: Into<Option<Area>>
+    {
+        unsafe {
+            match area.into() {
+                None => wlr_renderer_scissor(self.renderer, ptr::null_mut()),
+                Some(area) => {
+                    let (width, height) = self.output.size();
+                    let x1 = area.origin.x.max(0);
+                    let y1 = area.origin.y.max(0);
+                    let x2 = (area.origin.x + area.size.width).min(width);
+                    let y2 = (area.origin.y + area.size.height).min(height);
+                    let area = Area::new(Origin::new(x1, y1),
+                                         Size::new((x2 - x1).max(0), (y2 - y1).max(0)));
+                    wlr_renderer_scissor(self.renderer, &mut area.into())
+                }
+            }
+        }
+    }
+
     /// Renders the requseted texture.
     pub fn render_texture(&mut self,
                           texture: &Texture,
@@ -173,7 +205,12 @@ impl<'output> Renderer<'output> {
         unsafe { wlr_render_ellipse_with_matrix(self.renderer, color.as_ptr(), matrix.as_ptr()) }
     }
 
-    /// Renders a solid rectangle in the specified color.
+    /// Renders a solid rectangle in the specified color, in RGBA format.
+    ///
+    /// The area is in output coordinates and the matrix is the projection
+    /// of the output, which is usually `output.transform_matrix()`.
+    ///
+    /// This is useful for backgrounds, borders and other decorations.
     pub fn render_colored_rect(&mut self, area: Area, color: [f32; 4], matrix: [f32; 9]) {
         unsafe { wlr_render_rect(self.renderer, &area.into(), color.as_ptr(), matrix.as_ptr()) }
     }
@@ -191,19 +228,3 @@ impl<'output> Drop for Renderer<'output> {
         }
     }
 }
-
-unsafe fn create_texture_from_pixels(renderer: *mut wlr_renderer,
-                                     format: wl_shm_format,
-                                     stride: u32,
-                                     width: u32,
-                                     height: u32,
-                                     // TODO Slice of u8? It's a void*, hmm
-                                     data: *const c_void)
-                                     -> Option<Texture<'static>> {
-    let texture = wlr_texture_from_pixels(renderer, format, stride, width, height, data);
-    if texture.is_null() {
-        None
-    } else {
-        Some(Texture::from_ptr(texture))
-    }
-}
@@ -1,7 +1,10 @@
 use std::marker::PhantomData;
 
-use libc::c_int;
-use wlroots_sys::{wl_shm_format, wlr_texture, wlr_texture_get_size};
+use libc::{c_int, c_void};
+use wlroots_sys::{wl_shm_format, wlr_renderer, wlr_texture, wlr_texture_from_pixels,
+                  wlr_texture_get_size, wlr_texture_is_opaque};
+
+use render::Renderer;
 
 /// Wrapper around wl_shm_format, to make it easier and nicer to type.
 #[repr(u32)]
@@ -78,7 +81,7 @@ impl Into<wl_shm_format> for TextureFormat {
 #[derive(Debug, Eq, PartialEq, Hash)]
 /// A wrapper for a wlr_texture.
 ///
-/// For textures created from `GenericRenderer::create_texture_from_pixels`, the lifetime
+/// For textures created from `Texture::from_pixels`, the lifetime
 /// will be `'static` because the memory will be owned by the user.
 pub struct Texture<'surface> {
     texture: *mut wlr_texture,
@@ -86,6 +89,63 @@ pub struct Texture<'surface> {
 }
 
 impl <'surface> Texture<'surface> {
+    /// Create a texture from raw pixel data, e.g for server side decorations.
+    ///
+    /// `stride` is the number of bytes in a row of `data`. Returns `None` if
+    /// `format` isn't a single plane RGB format, if the stride is smaller
+    /// than `width` pixels of that format, if `data` is too small for the
+    /// given stride and height, or if wlroots can't create the texture.
+    pub fn from_pixels(renderer: &Renderer,
+                       format: wl_shm_format,
+                       stride: u32,
+                       width: u32,
+                       height: u32,
+                       data: &[u8])
+                       -> Option<Texture<'static>> {
+        unsafe { Texture::from_raw_pixels(renderer.as_ptr(), format, stride, width, height, data) }
+    }
+
+    pub(crate) unsafe fn from_raw_pixels(renderer: *mut wlr_renderer,
+                                         format: wl_shm_format,
+                                         stride: u32,
+                                         width: u32,
+                                         height: u32,
+                                         data: &[u8])
+                                         -> Option<Texture<'static>> {
+        // NOTE wlroots trusts the stride and height, so make sure it won't read
+        // past the end of the buffer.
+        let bytes_per_pixel = match bytes_per_pixel(format) {
+            Some(bytes_per_pixel) => bytes_per_pixel,
+            None => {
+                wlr_log!(WLR_ERROR, "Can't create a texture from {:?} pixels", format);
+                return None
+            }
+        };
+        if !pixels_fit(bytes_per_pixel, stride, width, height, data.len()) {
+            wlr_log!(WLR_ERROR,
+                     "Texture data is {} bytes with stride {}, expected at least {} bytes \
+                      and a stride of at least {} for {}x{}",
+                     data.len(),
+                     stride,
+                     stride as u64 * height as u64,
+                     width as u64 * bytes_per_pixel as u64,
+                     width,
+                     height);
+            return None
+        }
+        let texture = wlr_texture_from_pixels(renderer,
+                                              format,
+                                              stride,
+                                              width,
+                                              height,
+                                              data.as_ptr() as *const c_void);
+        if texture.is_null() {
+            None
+        } else {
+            Some(Texture::from_ptr(texture))
+        }
+    }
+
     pub(crate) unsafe fn from_ptr<'unbound>(texture: *mut wlr_texture) -> Texture<'unbound> {
         Texture { texture, phantom: PhantomData }
     }
@@ -104,4 +164,101 @@ impl <'surface> Texture<'surface> {
             (width, height)
         }
     }
+
+    /// Determines if the texture is known to be fully opaque.
+    ///
+    /// Opaque textures can hide whatever is drawn beneath them, so that
+    /// doesn't need to be rendered.
+    pub fn is_opaque(&self) -> bool {
+        unsafe { wlr_texture_is_opaque(self.texture) }
+    }
+}
+
+/// Gets the size of a pixel in the format, if it's a format with a single
+/// plane of whole-byte pixels.
+fn bytes_per_pixel(format: wl_shm_format) -> Option<u32> {
+    use wlroots_sys::wl_shm_format::*;
+    match format {
+        WL_SHM_FORMAT_C8 | WL_SHM_FORMAT_RGB332 | WL_SHM_FORMAT_BGR233 => Some(1),
+        WL_SHM_FORMAT_XRGB4444 | WL_SHM_FORMAT_XBGR4444 | WL_SHM_FORMAT_RGBX4444 |
+        WL_SHM_FORMAT_BGRX4444 | WL_SHM_FORMAT_ARGB4444 | WL_SHM_FORMAT_ABGR4444 |
+        WL_SHM_FORMAT_RGBA4444 | WL_SHM_FORMAT_BGRA4444 | WL_SHM_FORMAT_XRGB1555 |
+        WL_SHM_FORMAT_XBGR1555 | WL_SHM_FORMAT_RGBX5551 | WL_SHM_FORMAT_BGRX5551 |
+        WL_SHM_FORMAT_ARGB1555 | WL_SHM_FORMAT_ABGR1555 | WL_SHM_FORMAT_RGBA5551 |
+        WL_SHM_FORMAT_BGRA5551 | WL_SHM_FORMAT_RGB565 | WL_SHM_FORMAT_BGR565 => Some(2),
+        WL_SHM_FORMAT_RGB888 | WL_SHM_FORMAT_BGR888 => Some(3),
+        WL_SHM_FORMAT_ARGB8888 | WL_SHM_FORMAT_XRGB8888 | WL_SHM_FORMAT_XBGR8888 |
+        WL_SHM_FORMAT_RGBX8888 | WL_SHM_FORMAT_BGRX8888 | WL_SHM_FORMAT_ABGR8888 |
+        WL_SHM_FORMAT_RGBA8888 | WL_SHM_FORMAT_BGRA8888 | WL_SHM_FORMAT_XRGB2101010 |
+        WL_SHM_FORMAT_XBGR2101010 | WL_SHM_FORMAT_RGBX1010102 | WL_SHM_FORMAT_BGRX1010102 |
+        WL_SHM_FORMAT_ARGB2101010 | WL_SHM_FORMAT_ABGR2101010 | WL_SHM_FORMAT_RGBA1010102 |
+        WL_SHM_FORMAT_BGRA1010102 => Some(4),
+        // YUV formats pack several pixels together or use several planes.
+        _ => None
+    }
+}
+
+/// Checks that a buffer of `len` bytes holds `height` rows of `stride` bytes,
+/// each of which fits `width` pixels of `bytes_per_pixel` bytes.
+fn pixels_fit(bytes_per_pixel: u32, stride: u32, width: u32, height: u32, len: usize) -> bool {
+    stride as u64 >= width as u64 * bytes_per_pixel as u64 &&
+    len as u64 >= stride as u64 * height as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wlroots_sys::wl_shm_format::*;
+
+    #[test]
+    fn accepts_tightly_packed_and_padded_rows() {
+        assert!(pixels_fit(4, 40, 10, 5, 200));
+        assert!(pixels_fit(4, 64, 10, 5, 320));
+        assert!(pixels_fit(4, 40, 10, 5, 400));
+        assert!(pixels_fit(4, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn rejects_short_stride() {
+        assert!(!pixels_fit(4, 39, 10, 5, 200));
+        assert!(!pixels_fit(4, 10, 10, 5, 200));
+        assert!(!pixels_fit(4, 0, 1, 1, 4));
+    }
+
+    #[test]
+    fn rejects_short_data() {
+        assert!(!pixels_fit(4, 40, 10, 5, 199));
+        assert!(!pixels_fit(4, 40, 10, 5, 0));
+        assert!(!pixels_fit(4, 64, 10, 5, 300));
+    }
+
+    #[test]
+    fn does_not_overflow() {
+        assert!(!pixels_fit(4, u32::max_value(), 1, u32::max_value(), usize::max_value() / 2));
+        assert!(!pixels_fit(4, u32::max_value(), u32::max_value(), 1, 0));
+    }
+
+    #[test]
+    fn stride_depends_on_the_pixel_size() {
+        assert!(pixels_fit(2, 20, 10, 5, 100));
+        assert!(!pixels_fit(4, 20, 10, 5, 100));
+        assert!(pixels_fit(3, 30, 10, 1, 30));
+        assert!(!pixels_fit(3, 29, 10, 1, 30));
+    }
+
+    #[test]
+    fn pixel_sizes_of_formats() {
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_ARGB8888), Some(4));
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_ABGR2101010), Some(4));
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_RGB888), Some(3));
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_RGB565), Some(2));
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_C8), Some(1));
+    }
+
+    #[test]
+    fn rejects_yuv_formats() {
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_YUYV), None);
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_NV12), None);
+        assert_eq!(bytes_per_pixel(WL_SHM_FORMAT_YUV420), None);
+    }
 }
@@ -1,11 +1,14 @@
 //! Wrapper for the `wlr_box` type.
 //! Note that we renamed it to `Area` to avoid conflicts with Rust's Box.
 
+use std::{cmp, ops::{Add, Mul, Sub}};
+
 use libc::{c_double, c_float, c_int};
 
-use wlroots_sys::{wl_output_transform, wlr_box, wlr_box_closest_point, wlr_box_contains_point,
-                  wlr_box_empty, wlr_box_intersection, wlr_box_rotated_bounds, wlr_box_transform};
+use wlroots_sys::{wl_output_transform, wlr_box, wlr_box_closest_point, wlr_box_rotated_bounds,
+                  wlr_box_transform};
 
+#[deprecated(note = "`Area::intersection` returns an `Option<Area>` instead")]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 /// Result of applying an intersection of two `Area`s.
 pub enum IntersectionResult {
@@ -15,6 +18,16 @@ pub enum IntersectionResult {
     NoIntersection
 }
 
+#[allow(deprecated)]
+impl From<Option<Area>> for IntersectionResult {
+    fn from(intersection: Option<Area>) -> Self {
+        match intersection {
+            Some(area) => IntersectionResult::Intersection(area),
+            None => IntersectionResult::NoIntersection
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub struct Origin {
     pub x: c_int,
@@ -27,6 +40,22 @@ impl Origin {
     }
 }
 
+impl Add for Origin {
+    type Output = Origin;
+
+    fn add(self, other: Origin) -> Origin {
+        Origin::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Origin {
+    type Output = Origin;
+
+    fn sub(self, other: Origin) -> Origin {
+        Origin::new(self.x - other.x, self.y - other.y)
+    }
+}
+
 impl Into<Area> for Origin {
     fn into(self) -> Area {
         Area::new(self, Size::default())
@@ -45,6 +74,19 @@ impl Size {
     }
 }
 
+/// Scales the size, e.g by the scale of an output.
+///
+/// The scaled width and height are rounded to the nearest integer, halfway
+/// cases are rounded away from zero.
+impl Mul<f32> for Size {
+    type Output = Size;
+
+    fn mul(self, scale: f32) -> Size {
+        Size::new((self.width as f32 * scale).round() as c_int,
+                  (self.height as f32 * scale).round() as c_int)
+    }
+}
+
 impl Into<Area> for Size {
     fn into(self) -> Area {
         Area::new(Origin::default(), self)
@@ -106,26 +148,60 @@ impl Area {
     }
 
     /// Gets the intersection of the two areas.
-    pub fn intersection(self, other_box: Area) -> IntersectionResult {
-        unsafe {
-            let res = Area::default();
-            let is_empty = wlr_box_intersection(&self.into(), &other_box.into(), &mut res.into());
-            if is_empty {
-                IntersectionResult::NoIntersection
-            } else {
-                IntersectionResult::Intersection(res)
-            }
+    ///
+    /// Returns `None` if they don't overlap.
+    pub fn intersection(&self, other: &Area) -> Option<Area> {
+        if self.is_empty() || other.is_empty() {
+            return None
         }
+        let left = cmp::max(self.origin.x, other.origin.x);
+        let top = cmp::max(self.origin.y, other.origin.y);
+        let right = cmp::min(self.right(), other.right());
+        let bottom = cmp::min(self.bottom(), other.bottom());
+        if right <= left || bottom <= top {
+            return None
+        }
+        Some(Area::new(Origin::new(left, top), Size::new(right - left, bottom - top)))
     }
 
     /// Determines if the box contains the given point.
-    pub fn contains_point(self, x: c_double, y: c_double) -> bool {
-        unsafe { wlr_box_contains_point(&mut self.into(), x, y) }
+    ///
+    /// Takes either integer or floating point coordinates. The right and
+    /// bottom edges are not part of the box, so adjacent boxes never both
+    /// contain a point.
+    pub fn contains_point<T: Into<c_double>>(&self, x: T, y: T) -> bool {
+        let (x, y) = (x.into(), y.into());
+        !self.is_empty() && x >= self.origin.x as c_double && x < self.right() as c_double &&
+        y >= self.origin.y as c_double && y < self.bottom() as c_double
+    }
+
+    /// Determines if the other box lies entirely within this one.
+    ///
+    /// Empty boxes are not contained in anything.
+    pub fn contains(&self, other: &Area) -> bool {
+        !self.is_empty() && !other.is_empty() && other.origin.x >= self.origin.x &&
+        other.origin.y >= self.origin.y && other.right() <= self.right() &&
+        other.bottom() <= self.bottom()
     }
 
     /// Determines if the box is empty (e.g if the bounds give it an area of 0).
     pub fn is_empty(self) -> bool {
-        unsafe { wlr_box_empty(&mut self.into()) }
+        self.size.width <= 0 || self.size.height <= 0
+    }
+
+    /// The x coordinate just past the right edge of the box.
+    ///
+    /// Saturates instead of overflowing for boxes at the edge of the
+    /// coordinate space.
+    fn right(&self) -> c_int {
+        self.origin.x.saturating_add(self.size.width)
+    }
+
+    /// The y coordinate just past the bottom edge of the box.
+    ///
+    /// Saturates like `right`.
+    fn bottom(&self) -> c_int {
+        self.origin.y.saturating_add(self.size.height)
     }
 
     /// Transforms the box coordinates and bounds according to the
@@ -149,3 +225,158 @@ impl Area {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: c_int, y: c_int, width: c_int, height: c_int) -> Area {
+        Area::new(Origin::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn intersection_of_overlapping_areas() {
+        let a = area(0, 0, 100, 100);
+        let b = area(50, 25, 100, 100);
+        assert_eq!(a.intersection(&b), Some(area(50, 25, 50, 75)));
+        assert_eq!(b.intersection(&a), Some(area(50, 25, 50, 75)));
+    }
+
+    #[test]
+    fn intersection_with_a_contained_area() {
+        let outer = area(-10, -10, 100, 100);
+        let inner = area(10, 10, 5, 5);
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert_eq!(outer.intersection(&outer), Some(outer));
+    }
+
+    #[test]
+    fn no_intersection_of_disjoint_or_touching_areas() {
+        let a = area(0, 0, 10, 10);
+        assert_eq!(a.intersection(&area(20, 20, 10, 10)), None);
+        assert_eq!(a.intersection(&area(10, 0, 10, 10)), None);
+        assert_eq!(a.intersection(&area(0, 10, 10, 10)), None);
+    }
+
+    #[test]
+    fn no_intersection_with_empty_areas() {
+        let a = area(0, 0, 10, 10);
+        assert_eq!(a.intersection(&area(5, 5, 0, 5)), None);
+        assert_eq!(a.intersection(&area(5, 5, 5, -1)), None);
+        assert_eq!(area(5, 5, 0, 0).intersection(&a), None);
+    }
+
+    #[test]
+    fn contains_areas() {
+        let outer = area(0, 0, 100, 100);
+        assert!(outer.contains(&outer));
+        assert!(outer.contains(&area(10, 10, 90, 90)));
+        assert!(!outer.contains(&area(10, 10, 91, 90)));
+        assert!(!outer.contains(&area(-1, 0, 10, 10)));
+        assert!(!outer.contains(&area(10, 10, 0, 0)));
+        assert!(!area(0, 0, 0, 0).contains(&area(0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn empty_areas() {
+        assert!(area(0, 0, 0, 10).is_empty());
+        assert!(area(0, 0, 10, 0).is_empty());
+        assert!(area(0, 0, -5, 10).is_empty());
+        assert!(!area(0, 0, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn contains_points_on_the_top_left_edges() {
+        let a = area(10, 20, 30, 40);
+        assert!(a.contains_point(10, 20));
+        assert!(a.contains_point(10, 59));
+        assert!(a.contains_point(39, 20));
+        assert!(a.contains_point(39, 59));
+        assert!(a.contains_point(25, 40));
+    }
+
+    #[test]
+    fn excludes_points_on_the_bottom_right_edges() {
+        let a = area(10, 20, 30, 40);
+        assert!(!a.contains_point(40, 30));
+        assert!(!a.contains_point(20, 60));
+        assert!(!a.contains_point(40, 60));
+        assert!(!a.contains_point(9, 30));
+        assert!(!a.contains_point(20, 19));
+    }
+
+    #[test]
+    fn adjacent_areas_never_share_a_point() {
+        let left = area(0, 0, 10, 10);
+        let right = area(10, 0, 10, 10);
+        for y in 0..10 {
+            for x in 0..20 {
+                assert!(left.contains_point(x, y) != right.contains_point(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn contains_floating_point_coordinates() {
+        let a = area(10, 20, 30, 40);
+        assert!(a.contains_point(10.0, 20.0));
+        assert!(a.contains_point(39.99, 59.99));
+        assert!(!a.contains_point(40.0, 30.0));
+        assert!(!a.contains_point(9.99, 30.0));
+        assert!(a.contains_point(25.5f32, 40.5f32));
+    }
+
+    #[test]
+    fn edges_saturate_instead_of_overflowing() {
+        let max = c_int::max_value();
+        let a = area(max - 10, max - 10, 100, 100);
+        assert_eq!(a.right(), max);
+        assert_eq!(a.bottom(), max);
+        assert!(a.contains_point(max - 1, max - 1));
+        assert_eq!(a.intersection(&area(max - 5, max - 5, 100, 100)),
+                   Some(area(max - 5, max - 5, 5, 5)));
+    }
+
+    #[test]
+    fn empty_areas_contain_no_points() {
+        assert!(!area(10, 10, 0, 10).contains_point(10, 10));
+        assert!(!area(10, 10, 10, 0).contains_point(10, 10));
+        assert!(!area(0, 0, -10, -10).contains_point(-5, -5));
+    }
+
+    #[test]
+    fn contains_points_near_the_integer_limits() {
+        let a = area(c_int::min_value(), c_int::min_value(), 10, 10);
+        assert!(a.contains_point(c_int::min_value(), c_int::min_value()));
+        assert!(!a.contains_point(c_int::min_value() + 10, c_int::min_value()));
+    }
+
+    #[test]
+    fn origin_arithmetic() {
+        let origin = Origin::new(10, -20);
+        assert_eq!(origin + Origin::new(5, 5), Origin::new(15, -15));
+        assert_eq!(origin - Origin::new(5, 5), Origin::new(5, -25));
+        assert_eq!(origin + Origin::default(), origin);
+        assert_eq!(origin - origin, Origin::default());
+    }
+
+    #[test]
+    fn size_scaling_rounds_to_nearest() {
+        assert_eq!(Size::new(100, 50) * 2.0, Size::new(200, 100));
+        assert_eq!(Size::new(100, 50) * 1.0, Size::new(100, 50));
+        assert_eq!(Size::new(3, 5) * 1.5, Size::new(5, 8));
+        assert_eq!(Size::new(1, 3) * 0.5, Size::new(1, 2));
+        assert_eq!(Size::new(10, 10) * 0.04, Size::new(0, 0));
+        assert_eq!(Size::new(10, 10) * 0.0, Size::new(0, 0));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn intersection_result_from_option() {
+        let a = area(0, 0, 10, 10);
+        assert_eq!(IntersectionResult::from(a.intersection(&a)),
+                   IntersectionResult::Intersection(a));
+        assert_eq!(IntersectionResult::from(a.intersection(&area(20, 20, 1, 1))),
+                   IntersectionResult::NoIntersection);
+    }
+}
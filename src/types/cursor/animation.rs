@@ -0,0 +1,118 @@
+//! Timer driven animation of xcursor images on a `Cursor`.
+
+use std::ptr;
+
+use libc::{self, c_int};
+use wayland_sys::server::{wl_event_source, WAYLAND_SERVER_HANDLE};
+use wlroots_sys::{wlr_cursor, wlr_cursor_set_image};
+
+use compositor::COMPOSITOR_PTR;
+use XCursor;
+
+/// An owned copy of a single image of an animated xcursor.
+///
+/// The pixels are copied out of the theme so the animation doesn't depend
+/// on the lifetime of the `XCursorTheme` it came from.
+#[derive(Debug)]
+struct Frame {
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+    delay: u32,
+    buffer: Vec<u8>
+}
+
+/// Cycles a `Cursor` through the images of an `XCursor` using a timer
+/// on the compositor's event loop.
+///
+/// The timer is removed when this is dropped.
+#[derive(Debug)]
+pub(crate) struct CursorAnimation {
+    cursor: *mut wlr_cursor,
+    frames: Vec<Frame>,
+    current: usize,
+    timer: *mut wl_event_source
+}
+
+impl CursorAnimation {
+    /// Starts animating the cursor through the images of the xcursor.
+    ///
+    /// Returns `None` if the xcursor isn't animated or if there isn't a
+    /// running compositor to drive the timer. The first frame is not
+    /// shown, that is the responsibility of the caller.
+    pub(crate) unsafe fn start(cursor: *mut wlr_cursor, xcursor: &XCursor) -> Option<Box<Self>> {
+        let frames: Vec<Frame> = xcursor.images()
+                                        .into_iter()
+                                        .map(|image| {
+                                                 Frame { width: image.width,
+                                                         height: image.height,
+                                                         hotspot_x: image.hotspot_x,
+                                                         hotspot_y: image.hotspot_y,
+                                                         delay: image.delay,
+                                                         buffer: image.buffer.to_vec() }
+                                             })
+                                        .collect();
+        if frames.len() < 2 || frames[0].delay == 0 || COMPOSITOR_PTR.is_null() {
+            return None
+        }
+        let event_loop = (*COMPOSITOR_PTR).event_loop;
+        let mut animation = Box::new(CursorAnimation { cursor,
+                                                       frames,
+                                                       current: 0,
+                                                       timer: ptr::null_mut() });
+        // NOTE The box is never moved out of, so this pointer stays valid
+        // until the animation is dropped, at which point the timer is removed.
+        let data = &mut *animation as *mut CursorAnimation as *mut libc::c_void;
+        animation.timer = ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                                        wl_event_loop_add_timer,
+                                        event_loop,
+                                        animation_notify,
+                                        data);
+        if animation.timer.is_null() {
+            wlr_log!(WLR_ERROR, "Could not create the cursor animation timer");
+            return None
+        }
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_event_source_timer_update,
+                      animation.timer,
+                      animation.frames[0].delay as c_int);
+        Some(animation)
+    }
+
+    /// Moves on to the next frame, showing it and scheduling the one after.
+    unsafe fn advance(&mut self) {
+        self.current = (self.current + 1) % self.frames.len();
+        let frame = &self.frames[self.current];
+        wlr_cursor_set_image(self.cursor,
+                             frame.buffer.as_ptr(),
+                             (frame.width * 4) as i32,
+                             frame.width,
+                             frame.height,
+                             frame.hotspot_x as _,
+                             frame.hotspot_y as _,
+                             1.0);
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                      wl_event_source_timer_update,
+                      self.timer,
+                      frame.delay as c_int);
+    }
+}
+
+impl Drop for CursorAnimation {
+    fn drop(&mut self) {
+        unsafe {
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_source_remove, self.timer);
+        }
+    }
+}
+
+/// Called by the event loop when it's time to show the next frame.
+///
+/// The timer is removed when the animation is dropped, so the data pointer
+/// is always valid here.
+unsafe extern "C" fn animation_notify(data: *mut libc::c_void) -> c_int {
+    let animation = &mut *(data as *mut CursorAnimation);
+    animation.advance();
+    0
+}
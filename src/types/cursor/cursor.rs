@@ -2,7 +2,7 @@
 
 use std::{fmt, panic, ptr, cell::Cell, rc::{Rc, Weak}};
 
-use libc;
+use libc::{self, c_int};
 use wayland_sys::server::WAYLAND_SERVER_HANDLE;
 use wayland_sys::server::signal::wl_signal_add;
 use wlroots_sys::{wlr_cursor, wlr_cursor_absolute_to_layout_coords,
@@ -11,13 +11,15 @@ use wlroots_sys::{wlr_cursor, wlr_cursor_absolute_to_layout_coords,
                   wlr_cursor_map_input_to_output, wlr_cursor_map_input_to_region,
                   wlr_cursor_map_to_output, wlr_cursor_map_to_region, wlr_cursor_move,
                   wlr_cursor_set_image, wlr_cursor_set_surface, wlr_cursor_warp,
-                  wlr_cursor_warp_absolute};
+                  wlr_cursor_warp_absolute, wlr_cursor_warp_closest};
 
-use {Area, InputDevice, Output, OutputHandle, OutputLayout, OutputLayoutHandle, Surface,
+use {Area, InputDevice, Output, OutputHandle, OutputLayout, OutputLayoutHandle, PixmanRegion,
+     PointerConstraintHandle, PointerConstraintType, Surface, SurfaceHandle, XCursor,
      XCursorImage};
 use compositor::{compositor_handle, CompositorHandle};
 use errors::{HandleErr, HandleResult};
 use events::{pointer_events, tablet_tool_events, touch_events};
+use super::animation::CursorAnimation;
 
 #[derive(Debug)]
 pub struct CursorState {
@@ -28,7 +30,9 @@ pub struct CursorState {
     /// they cannot be upgraded.
     counter: Rc<Cell<bool>>,
     /// A raw pointer to the Cursor on the heap
-    cursor: *mut Cursor
+    cursor: *mut Cursor,
+    /// The animation started by `Cursor::set_xcursor`, if one is running.
+    animation: Option<Box<CursorAnimation>>
 }
 
 #[derive(Debug, Clone)]
@@ -37,10 +41,17 @@ pub struct CursorHandle {
     handle: Weak<Cell<bool>>
 }
 
+/// Handles the events emitted by a `Cursor`.
+///
+/// These are aggregated over every input device attached with
+/// `Cursor::attach_input_device`, so a compositor can drive the cursor from
+/// here instead of installing a handler on each pointer, touch or tablet device.
 pub trait CursorHandler {
     /// Callback that is triggered when the cursor moves.
     fn on_pointer_motion(&mut self, CompositorHandle, CursorHandle, &pointer_events::MotionEvent) {}
 
+    /// Callback that is triggered when an absolute pointer (e.g. a virtual
+    /// machine's pointer) moves the cursor.
     fn on_pointer_motion_absolute(&mut self,
                                   CompositorHandle,
                                   CursorHandle,
@@ -50,34 +61,43 @@ pub trait CursorHandler {
     /// Callback that is triggered when the buttons on the pointer are pressed.
     fn on_pointer_button(&mut self, CompositorHandle, CursorHandle, &pointer_events::ButtonEvent) {}
 
+    /// Callback that is triggered when an axis (e.g. a scroll wheel) changes.
     fn on_pointer_axis(&mut self, CompositorHandle, CursorHandle, &pointer_events::AxisEvent) {}
 
+    /// Callback that is triggered when a touch point is lifted.
     fn on_touch_up(&mut self, CompositorHandle, CursorHandle, &touch_events::UpEvent) {}
 
+    /// Callback that is triggered when a new touch point is placed.
     fn on_touch_down(&mut self, CompositorHandle, CursorHandle, &touch_events::DownEvent) {}
 
+    /// Callback that is triggered when a touch point moves.
     fn on_touch_motion(&mut self, CompositorHandle, CursorHandle, &touch_events::MotionEvent) {}
 
+    /// Callback that is triggered when a touch point is cancelled.
     fn on_touch_cancel(&mut self, CompositorHandle, CursorHandle, &touch_events::CancelEvent) {}
 
+    /// Callback that is triggered when a tablet tool's axes change.
     fn on_tablet_tool_axis(&mut self,
                            CompositorHandle,
                            CursorHandle,
                            &tablet_tool_events::AxisEvent) {
     }
 
+    /// Callback that is triggered when a tablet tool enters or leaves proximity.
     fn on_tablet_tool_proximity(&mut self,
                                 CompositorHandle,
                                 CursorHandle,
                                 &tablet_tool_events::ProximityEvent) {
     }
 
+    /// Callback that is triggered when a tablet tool's tip touches or leaves the tablet.
     fn on_tablet_tool_tip(&mut self,
                           CompositorHandle,
                           CursorHandle,
                           &tablet_tool_events::TipEvent) {
     }
 
+    /// Callback that is triggered when a button on a tablet tool is pressed.
     fn on_tablet_tool_button(&mut self,
                              CompositorHandle,
                              CursorHandle,
@@ -327,7 +347,8 @@ impl Cursor {
             let handle = Rc::downgrade(&counter);
             let state = Box::new(CursorState { counter,
                                                cursor: Box::into_raw(cursor),
-                                               output_layout: None });
+                                               output_layout: None,
+                                               animation: None });
             (*cursor_ptr).data = Box::into_raw(state) as *mut libc::c_void;
             CursorHandle { cursor: cursor_ptr,
                            handle }
@@ -401,6 +422,41 @@ impl Cursor {
         }
     }
 
+    /// Warp the cursor to the given x and y in layout coordinates, respecting
+    /// an active pointer constraint.
+    ///
+    /// `surface_origin` is the position of the constrained surface in layout
+    /// coordinates. A locked pointer never moves, and a confined one only
+    /// moves to points inside the region of the constraint. If the
+    /// constraint no longer exists this is the same as `warp`.
+    ///
+    /// Returns true when the mouse warp was successful.
+    pub fn warp_constrained<'this, O>(&'this mut self,
+                                      dev: O,
+                                      x: f64,
+                                      y: f64,
+                                      constraint: &PointerConstraintHandle,
+                                      surface_origin: (f64, f64))
+                                      -> bool
+        where O: Into<Option<&'this InputDevice>>
+    {
+        let allowed = constraint.run(|constraint| {
+                                         let region = constraint.region();
+                                         constraint_allows(constraint.constraint_type(),
+                                                           &region,
+                                                           (x, y),
+                                                           surface_origin)
+                                     })
+                                .unwrap_or(true);
+        allowed && self.warp(dev, x, y)
+    }
+
+    /// Warp the cursor to the given absolute coordinates, where `x_mm` and `y_mm`
+    /// are normalized to the range `[0, 1]` over the mapped region.
+    ///
+    /// This is what touchscreens and tablets report. If `dev` is given its
+    /// mapping constraints (see `map_input_to_output`) are respected, otherwise
+    /// the cursor's own mapping is used.
     pub fn warp_absolute<'this, O>(&'this mut self, dev: O, x_mm: f64, y_mm: f64)
         where O: Into<Option<&'this InputDevice>>
     {
@@ -430,6 +486,7 @@ impl Cursor {
     //TODO USE IMAGE
     /// Sets the image of the cursor to the image.
     pub fn set_cursor_image(&mut self, image: &XCursorImage) {
+        self.stop_animation();
         unsafe {
             // NOTE Rationale for why lifetime isn't attached:
             //
@@ -447,6 +504,42 @@ impl Cursor {
         }
     }
 
+    /// Set the cursor image to the given xcursor.
+    ///
+    /// If the xcursor is animated (e.g. a "watch" cursor) the cursor will cycle
+    /// through its images, waiting each image's `delay` in between, until a
+    /// different image or surface is set on this cursor.
+    ///
+    /// Animation requires a running `Compositor`, without one only the first
+    /// image is shown.
+    pub fn set_xcursor(&mut self, xcursor: &XCursor) {
+        let images = xcursor.images();
+        let first = match images.first() {
+            Some(image) => image,
+            None => {
+                wlr_log!(WLR_ERROR, "Tried to set an xcursor without any images");
+                return
+            }
+        };
+        self.set_cursor_image(first);
+        unsafe {
+            let state = (*self.data.0).data as *mut CursorState;
+            (*state).animation = CursorAnimation::start(self.data.0, xcursor);
+        }
+    }
+
+    /// Stops the animation started by `set_xcursor`, if there is one.
+    ///
+    /// The cursor keeps showing whichever image was current.
+    pub(crate) fn stop_animation(&self) {
+        unsafe {
+            let state = (*self.data.0).data as *mut CursorState;
+            if !state.is_null() {
+                (*state).animation = None;
+            }
+        }
+    }
+
     /// Set the cursor surface. The surface can be committed to update the cursor
     /// image. The surface position is substracted from the hotspot.
     ///
@@ -454,6 +547,7 @@ impl Cursor {
     pub fn set_surface<'this, O>(&'this mut self, surface: O, hotspot_x: i32, hotspot_y: i32)
         where O: Into<Option<&'this Surface>>
     {
+        self.stop_animation();
         unsafe {
             let surface_ptr = surface.into()
                                      .map(|surface| surface.as_ptr())
@@ -462,6 +556,37 @@ impl Cursor {
         }
     }
 
+    /// Set the cursor surface from a handle, e.g. one provided by a client
+    /// through `wl_pointer.set_cursor`.
+    ///
+    /// Passing `None` hides the cursor entirely.
+    ///
+    /// Unlike `set_surface` this does not borrow the surface, so it can be
+    /// called from within a callback that is already using it.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` if the surface has been destroyed,
+    /// in which case the cursor image is left unchanged.
+    pub fn set_surface_handle(&mut self,
+                              surface: Option<&SurfaceHandle>,
+                              hotspot_x: i32,
+                              hotspot_y: i32)
+                              -> HandleResult<()> {
+        unsafe {
+            let surface_ptr = match surface {
+                None => ptr::null_mut(),
+                Some(surface) => {
+                    if !surface.is_alive() {
+                        return Err(HandleErr::AlreadyDropped)
+                    }
+                    surface.as_ptr()
+                }
+            };
+            self.stop_animation();
+            wlr_cursor_set_surface(self.data.0, surface_ptr, hotspot_x, hotspot_y);
+            Ok(())
+        }
+    }
+
     /// Attaches this input device to this cursor. The input device must be one of:
     ///
     /// - WLR_INPUT_DEVICE_POINTER
@@ -486,6 +611,9 @@ impl Cursor {
 
     /// Attaches this cursor to the given output, which must be among the outputs in
     /// the current output_layout for this cursor.
+    ///
+    /// Passing `None` unmaps the cursor from any specific output, so it can move
+    /// across the whole layout again.
     pub fn map_to_output<'a, T: Into<Option<&'a mut Output>>>(&mut self, output: T) {
         self.assert_layout();
         match output.into() {
@@ -500,6 +628,24 @@ impl Cursor {
         }
     }
 
+    /// Same as `map_to_output`, but takes a handle so the output doesn't
+    /// need to be borrowed.
+    ///
+    /// Passing `None` unmaps the cursor from any specific output.
+    pub fn map_to_output_handle(&mut self, output: Option<&OutputHandle>) {
+        self.assert_layout();
+        match output {
+            None => unsafe { wlr_cursor_map_to_output(self.data.0, ptr::null_mut()) },
+            Some(output) => {
+                if !self.output_in_output_layout(output.clone()) {
+                    wlr_log!(WLR_ERROR, "Tried to map output not in the OutputLayout");
+                    return
+                }
+                unsafe { wlr_cursor_map_to_output(self.data.0, output.as_ptr()) }
+            }
+        }
+    }
+
     /// Maps all input from a specific input device to a given output.
     ///
     /// The input device must be attached to this cursor
@@ -571,6 +717,23 @@ impl Cursor {
         }
     }
 
+    /// Warps the cursor to the closest point it's allowed to be at, e.g after
+    /// an output in its layout changed size.
+    ///
+    /// Its mapping to an output or region is left as is.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` if it isn't attached to an output
+    /// layout.
+    pub(crate) fn clamp_to_layout(&mut self) -> HandleResult<()> {
+        match self.data.2.clone().map(|layout| layout.run(|_| ())) {
+            Some(Ok(())) | Some(Err(HandleErr::AlreadyBorrowed)) => {}
+            None | Some(Err(_)) => return Err(HandleErr::AlreadyDropped)
+        }
+        let (x, y) = self.coords();
+        unsafe { wlr_cursor_warp_closest(self.data.0, ptr::null_mut(), x, y) }
+        Ok(())
+    }
+
     /// Determines if we are within a valid layout.
     fn assert_layout(&self) {
         match self.data.2.clone().map(|layout| layout.run(|_| ())) {
@@ -737,8 +900,63 @@ impl CursorHandle {
     }
 }
 
+impl_handle!(CursorHandle => Box<Cursor>);
+
 impl Default for CursorHandle {
     fn default() -> Self {
         CursorHandle::new()
     }
 }
+
+/// Determines if a constraint lets the pointer move to `point`, where the
+/// point and the origin of the constrained surface are in layout coordinates.
+fn constraint_allows(constraint_type: PointerConstraintType,
+                     region: &PixmanRegion,
+                     point: (f64, f64),
+                     surface_origin: (f64, f64))
+                     -> bool {
+    match constraint_type {
+        PointerConstraintType::Locked => false,
+        PointerConstraintType::Confined => {
+            let (sx, sy) = (point.0 - surface_origin.0, point.1 - surface_origin.1);
+            region.contains_point(sx.floor() as c_int, sy.floor() as c_int)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Origin, Size};
+
+    fn region() -> PixmanRegion {
+        PixmanRegion::from_area(Area::new(Origin::new(10, 10), Size::new(20, 20)))
+    }
+
+    #[test]
+    fn locked_pointers_never_move() {
+        for &point in &[(0.0, 0.0), (15.0, 15.0), (115.0, 215.0)] {
+            assert!(!constraint_allows(PointerConstraintType::Locked,
+                                       &region(),
+                                       point,
+                                       (100.0, 200.0)));
+        }
+    }
+
+    #[test]
+    fn confined_pointers_stay_in_the_region() {
+        let allows = |x, y| {
+            constraint_allows(PointerConstraintType::Confined,
+                              &region(),
+                              (x, y),
+                              (100.0, 200.0))
+        };
+        // The region is relative to the surface at (100, 200).
+        assert!(allows(110.0, 210.0));
+        assert!(allows(129.5, 229.5));
+        assert!(!allows(15.0, 15.0));
+        assert!(!allows(130.0, 215.0));
+        // Partial pixels belong to the pixel they are in.
+        assert!(!allows(109.5, 215.0));
+    }
+}
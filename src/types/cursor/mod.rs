@@ -1,3 +1,4 @@
+mod animation;
 mod cursor;
 mod xcursor;
 mod xcursor_manager;
@@ -8,7 +8,7 @@ use libc::{c_int, c_uint};
 use wlroots_sys::{wlr_xcursor, wlr_xcursor_frame, wlr_xcursor_image, wlr_xcursor_theme,
                   wlr_xcursor_theme_destroy, wlr_xcursor_theme_get_cursor, wlr_xcursor_theme_load};
 
-use utils::{c_to_rust_string, safe_as_cstring};
+use utils::{c_to_rust_string, safe_as_cstring, ToMS};
 
 #[derive(Debug)]
 pub struct XCursorTheme {
@@ -27,10 +27,91 @@ pub struct XCursorImage<'cursor> {
     pub height: u32,
     pub hotspot_x: u32,
     pub hotspot_y: u32,
+    /// How long this image is shown before moving on to the next one,
+    /// in milliseconds. This is `0` for cursors that aren't animated.
     pub delay: u32,
     pub buffer: &'cursor [u8]
 }
 
+/// The common cursor shapes provided by xcursor themes.
+///
+/// Themes don't agree on the names of their cursors, so each shape is looked
+/// up by its traditional X cursor name first and then by its common aliases.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CursorShape {
+    /// The default arrow.
+    LeftPtr,
+    /// The text selection I-beam.
+    Text,
+    /// A pointing hand, e.g. for links.
+    Pointer,
+    /// An open hand, for something that can be grabbed.
+    Grab,
+    /// A closed hand, for something that is being dragged.
+    Grabbing,
+    /// The program is busy and can't be interacted with.
+    Watch,
+    /// The program is busy but can still be interacted with.
+    Progress,
+    /// Precise selection, e.g. when taking a screenshot.
+    Crosshair,
+    /// Something is being moved.
+    Move,
+    /// Help is available for what is under the cursor.
+    Help,
+    /// The requested action can't be done here.
+    NotAllowed,
+    /// Resizing from the top edge.
+    TopSide,
+    /// Resizing from the bottom edge.
+    BottomSide,
+    /// Resizing from the left edge.
+    LeftSide,
+    /// Resizing from the right edge.
+    RightSide,
+    /// Resizing from the top left corner.
+    TopLeftCorner,
+    /// Resizing from the top right corner.
+    TopRightCorner,
+    /// Resizing from the bottom left corner.
+    BottomLeftCorner,
+    /// Resizing from the bottom right corner.
+    BottomRightCorner
+}
+
+impl CursorShape {
+    /// Get the traditional X cursor name of this shape.
+    pub fn as_name(self) -> &'static str {
+        self.names()[0]
+    }
+
+    /// The traditional X cursor name followed by the common aliases for it.
+    fn names(self) -> &'static [&'static str] {
+        use self::CursorShape::*;
+        match self {
+            LeftPtr => &["left_ptr", "default", "arrow"],
+            Text => &["xterm", "text", "ibeam"],
+            Pointer => &["hand2", "pointer", "hand1"],
+            Grab => &["grab", "openhand", "hand1"],
+            Grabbing => &["grabbing", "closedhand", "fleur"],
+            Watch => &["watch", "wait"],
+            Progress => &["left_ptr_watch", "progress"],
+            Crosshair => &["crosshair", "cross"],
+            Move => &["fleur", "move", "all-scroll"],
+            Help => &["question_arrow", "help"],
+            NotAllowed => &["crossed_circle", "not-allowed"],
+            TopSide => &["top_side", "n-resize"],
+            BottomSide => &["bottom_side", "s-resize"],
+            LeftSide => &["left_side", "w-resize"],
+            RightSide => &["right_side", "e-resize"],
+            TopLeftCorner => &["top_left_corner", "nw-resize"],
+            TopRightCorner => &["top_right_corner", "ne-resize"],
+            BottomLeftCorner => &["bottom_left_corner", "sw-resize"],
+            BottomRightCorner => &["bottom_right_corner", "se-resize"]
+        }
+    }
+}
+
 impl XCursorTheme {
     pub(crate) unsafe fn new(theme: *mut wlr_xcursor_theme) -> XCursorTheme {
         XCursorTheme { theme }
@@ -95,6 +176,15 @@ impl XCursorTheme {
                            phantom: PhantomData })
         }
     }
+
+    /// Get the cursor for the given shape, if the theme has it under any
+    /// of the names it is commonly known by.
+    pub fn get_shape<'theme>(&'theme self, shape: CursorShape) -> Option<XCursor<'theme>> {
+        shape.names()
+             .iter()
+             .filter_map(|&name| self.get_cursor(name.into()))
+             .next()
+    }
 }
 
 impl Drop for XCursorTheme {
@@ -111,11 +201,13 @@ impl<'theme> XCursor<'theme> {
                   phantom: PhantomData }
     }
 
-    pub fn frame(&mut self, duration: Duration) -> c_int {
-        unsafe {
-            // TODO Is the correct unit of time?
-            wlr_xcursor_frame(self.xcursor, duration.subsec_nanos())
-        }
+    /// Get the index into `images` of the image that should be shown once
+    /// `elapsed` time has passed since the animation started.
+    ///
+    /// This lets a compositor animate the cursor from its own render loop.
+    /// For cursors that aren't animated this is always `0`.
+    pub fn frame(&self, elapsed: Duration) -> usize {
+        unsafe { wlr_xcursor_frame(self.xcursor, elapsed.to_ms()) as usize }
     }
 
     pub fn images<'cursor>(&'cursor self) -> Vec<XCursorImage<'cursor>> {
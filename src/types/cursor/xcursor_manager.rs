@@ -115,6 +115,7 @@ impl XCursorManager {
     /// it.
     pub fn set_cursor_image(&mut self, name: String, cursor: &Cursor) {
         let name_str = safe_as_cstring(name);
+        cursor.stop_animation();
         unsafe {
             wlr_xcursor_manager_set_cursor_image(self.manager, name_str.as_ptr(), cursor.as_ptr());
         }
@@ -8,7 +8,7 @@ use wlroots_sys::{wl_data_device_manager_dnd_action, wlr_data_offer, wlr_data_so
 #[derive(Debug)]
 pub struct DataOffer<'source> {
     offer: *mut wlr_data_offer,
-    phantom: PhantomData<&'source DataSource>
+    phantom: PhantomData<&'source DataSource<'source>>
 }
 
 impl<'source> DataOffer<'source> {
@@ -25,16 +25,31 @@ impl<'source> DataOffer<'source> {
     }
 }
 
+/// Data offered by a client, e.g as the selection of a seat.
+///
+/// The source is owned by the client, which can destroy it as soon as control
+/// returns to the event loop. So this only lives as long as whatever it was
+/// borrowed from, e.g the `Seat` whose selection it is.
 #[derive(Debug)]
-pub struct DataSource {
-    source: *mut wlr_data_source
+pub struct DataSource<'source> {
+    source: *mut wlr_data_source,
+    phantom: PhantomData<&'source ()>
 }
 
 // TODO Be able to set the function pointers?
 
-impl DataSource {
+impl<'source> DataSource<'source> {
+    pub(crate) unsafe fn from_ptr(source: *mut wlr_data_source) -> Self {
+        DataSource { source,
+                     phantom: PhantomData }
+    }
+
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_data_source {
+        self.source
+    }
+
     /// Get the data offer from this source.
-    pub fn offer<'source>(&'source mut self) -> DataOffer<'source> {
+    pub fn offer<'offer>(&'offer mut self) -> DataOffer<'offer> {
         unsafe {
             DataOffer { offer: (*self.source).offer,
                         phantom: PhantomData }
@@ -0,0 +1,63 @@
+//! A drag-and-drop operation started by a client.
+
+use wlroots_sys::wlr_drag;
+
+use {DataSource, DragIconHandle, SurfaceHandle};
+
+/// An in progress drag-and-drop operation.
+///
+/// The icon being dragged, if there is one, can be rendered like any other
+/// surface through `DragIcon::surface`.
+#[derive(Debug)]
+pub struct Drag {
+    drag: *mut wlr_drag
+}
+
+impl Drag {
+    pub(crate) unsafe fn from_ptr(drag: *mut wlr_drag) -> Self {
+        Drag { drag }
+    }
+
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_drag {
+        self.drag
+    }
+
+    /// Get the icon that is being dragged, if the client provided one.
+    pub fn icon(&self) -> Option<DragIconHandle> {
+        unsafe {
+            let icon = (*self.drag).icon;
+            if icon.is_null() || (*icon).data.is_null() {
+                None
+            } else {
+                Some(DragIconHandle::from_ptr(icon))
+            }
+        }
+    }
+
+    /// Get the surface the drag is currently over, if there is one.
+    pub fn focus(&self) -> Option<SurfaceHandle> {
+        unsafe {
+            let surface = (*self.drag).focus;
+            if surface.is_null() {
+                None
+            } else {
+                Some(SurfaceHandle::from_ptr(surface))
+            }
+        }
+    }
+
+    /// Get the source of the data being dragged.
+    ///
+    /// This is `None` for drags that don't carry any data, e.g. when a
+    /// client drags within its own surfaces.
+    pub fn source(&self) -> Option<DataSource> {
+        unsafe {
+            let source = (*self.drag).source;
+            if source.is_null() {
+                None
+            } else {
+                Some(DataSource::from_ptr(source))
+            }
+        }
+    }
+}
@@ -1,4 +1,8 @@
-//! TODO Documentation
+//! The `wl_data_device_manager` global, which lets clients use the
+//! clipboard and DnD.
+//!
+//! It's created by the compositor when `CompositorBuilder::data_device` is
+//! enabled.
 
 use wlroots_sys::{wl_display, wlr_data_device_manager, wlr_data_device_manager_create,
                   wlr_data_device_manager_destroy};
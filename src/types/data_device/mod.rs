@@ -1,5 +1,9 @@
 mod manager;
 mod data_source;
+mod drag;
+mod primary_selection;
 
 pub use self::data_source::*;
+pub use self::drag::*;
 pub use self::manager::*;
+pub use self::primary_selection::*;
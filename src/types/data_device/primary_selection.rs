@@ -0,0 +1,47 @@
+//! Sources for the primary selection, which is used for middle-click paste.
+
+use std::{marker::PhantomData, mem, slice};
+
+use libc::c_char;
+use wlroots_sys::wlr_primary_selection_source;
+
+use utils::c_to_rust_string;
+
+/// The source of the primary selection of a seat.
+///
+/// This is a separate channel from the regular clipboard (see `DataSource`),
+/// usually filled by selecting text and pasted with a middle click.
+///
+/// Like a `DataSource` it's owned by the client, so it only lives as long as
+/// whatever it was borrowed from.
+#[derive(Debug)]
+pub struct PrimarySelectionSource<'source> {
+    source: *mut wlr_primary_selection_source,
+    phantom: PhantomData<&'source ()>
+}
+
+impl<'source> PrimarySelectionSource<'source> {
+    pub(crate) unsafe fn from_ptr(source: *mut wlr_primary_selection_source) -> Self {
+        PrimarySelectionSource { source,
+                                 phantom: PhantomData }
+    }
+
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_primary_selection_source {
+        self.source
+    }
+
+    /// Get the mime types the selection is offered in.
+    pub fn mime_types(&self) -> Vec<String> {
+        unsafe {
+            let mime_types = &(*self.source).mime_types;
+            if mime_types.data.is_null() {
+                return Vec::new()
+            }
+            let length = mime_types.size / mem::size_of::<*mut c_char>();
+            let mime_types = slice::from_raw_parts(mime_types.data as *const *mut c_char, length);
+            mime_types.iter()
+                      .filter_map(|&mime_type| c_to_rust_string(mime_type))
+                      .collect()
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Configuration options for libinput backed input devices.
+
+use std::marker::PhantomData;
+
+use libc::c_double;
+use wlroots_sys::{libinput_config_click_method, libinput_config_dwt_state,
+                  libinput_config_scroll_method, libinput_config_status,
+                  libinput_config_tap_state, libinput_device,
+                  libinput_device_config_accel_is_available, libinput_device_config_accel_set_speed,
+                  libinput_device_config_click_get_methods, libinput_device_config_click_set_method,
+                  libinput_device_config_dwt_is_available, libinput_device_config_dwt_set_enabled,
+                  libinput_device_config_left_handed_is_available,
+                  libinput_device_config_left_handed_set,
+                  libinput_device_config_scroll_get_methods,
+                  libinput_device_config_scroll_has_natural_scroll,
+                  libinput_device_config_scroll_set_method,
+                  libinput_device_config_scroll_set_natural_scroll_enabled,
+                  libinput_device_config_tap_get_finger_count,
+                  libinput_device_config_tap_set_enabled};
+
+use InputDevice;
+use errors::InputConfigError;
+
+/// How a touchpad (or other device) generates scroll events.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ScrollMethod {
+    /// Never send scroll events.
+    NoScroll,
+    /// Scroll when two fingers move on the touchpad.
+    TwoFinger,
+    /// Scroll when a finger moves along the edge of the touchpad.
+    Edge,
+    /// Scroll when the device moves while a designated button is held down.
+    OnButtonDown
+}
+
+/// How a touchpad generates button events from clicks.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ClickMethod {
+    /// Never send button events from clicks.
+    None,
+    /// Which button is pressed depends on the area of the touchpad clicked.
+    ButtonAreas,
+    /// Which button is pressed depends on how many fingers are on the touchpad.
+    ClickFinger
+}
+
+impl Into<libinput_config_scroll_method> for ScrollMethod {
+    fn into(self) -> libinput_config_scroll_method {
+        use self::libinput_config_scroll_method::*;
+        match self {
+            ScrollMethod::NoScroll => LIBINPUT_CONFIG_SCROLL_NO_SCROLL,
+            ScrollMethod::TwoFinger => LIBINPUT_CONFIG_SCROLL_2FG,
+            ScrollMethod::Edge => LIBINPUT_CONFIG_SCROLL_EDGE,
+            ScrollMethod::OnButtonDown => LIBINPUT_CONFIG_SCROLL_ON_BUTTON_DOWN
+        }
+    }
+}
+
+impl Into<libinput_config_click_method> for ClickMethod {
+    fn into(self) -> libinput_config_click_method {
+        use self::libinput_config_click_method::*;
+        match self {
+            ClickMethod::None => LIBINPUT_CONFIG_CLICK_METHOD_NONE,
+            ClickMethod::ButtonAreas => LIBINPUT_CONFIG_CLICK_METHOD_BUTTON_AREAS,
+            ClickMethod::ClickFinger => LIBINPUT_CONFIG_CLICK_METHOD_CLICKFINGER
+        }
+    }
+}
+
+/// Converts the status returned by a libinput config call into a `Result`.
+pub(crate) fn config_result(status: libinput_config_status) -> Result<(), InputConfigError> {
+    use self::libinput_config_status::*;
+    match status {
+        LIBINPUT_CONFIG_STATUS_SUCCESS => Ok(()),
+        LIBINPUT_CONFIG_STATUS_UNSUPPORTED => Err(InputConfigError::Unsupported),
+        LIBINPUT_CONFIG_STATUS_INVALID => Err(InputConfigError::Invalid)
+    }
+}
+
+/// Checks that a scroll or click method is in the bitmask of methods the
+/// device supports. Turning the method off (0) is always supported.
+fn check_method(supported: u32, method: u32) -> Result<(), InputConfigError> {
+    if method != 0 && supported & method == 0 {
+        Err(InputConfigError::Unsupported)
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that a pointer acceleration speed is within the range libinput
+/// accepts.
+fn check_accel_speed(speed: c_double) -> Result<(), InputConfigError> {
+    if speed >= -1.0 && speed <= 1.0 {
+        Ok(())
+    } else {
+        Err(InputConfigError::Invalid)
+    }
+}
+
+/// The libinput device backing an `InputDevice`, used to configure it.
+///
+/// Created with `InputDevice::libinput_handle`. Settings take effect right
+/// away and are kept until the device is removed.
+#[derive(Debug)]
+pub struct LibinputDevice<'device> {
+    device: *mut libinput_device,
+    phantom: PhantomData<&'device mut InputDevice>
+}
+
+impl<'device> LibinputDevice<'device> {
+    /// NOTE `device` must be the libinput device of an input device that
+    /// outlives `'device`.
+    pub(crate) unsafe fn from_ptr(device: *mut libinput_device) -> Self {
+        LibinputDevice { device,
+                         phantom: PhantomData }
+    }
+
+    /// Set the pointer acceleration, from -1.0 (slowest) to 1.0 (fastest).
+    ///
+    /// Fails with `Unsupported` if the device has no pointer acceleration
+    /// (e.g a touch screen), or with `Invalid` if the speed is out of range.
+    pub fn set_accel_speed(&mut self, speed: c_double) -> Result<(), InputConfigError> {
+        unsafe {
+            if libinput_device_config_accel_is_available(self.device) == 0 {
+                return Err(InputConfigError::Unsupported)
+            }
+            check_accel_speed(speed)?;
+            config_result(libinput_device_config_accel_set_speed(self.device, speed))
+        }
+    }
+
+    /// Set whether tapping a touchpad generates button events.
+    pub fn set_tap_enabled(&mut self, enabled: bool) -> Result<(), InputConfigError> {
+        use self::libinput_config_tap_state::*;
+        unsafe {
+            if libinput_device_config_tap_get_finger_count(self.device) == 0 {
+                return Err(InputConfigError::Unsupported)
+            }
+            let state = if enabled {
+                LIBINPUT_CONFIG_TAP_ENABLED
+            } else {
+                LIBINPUT_CONFIG_TAP_DISABLED
+            };
+            config_result(libinput_device_config_tap_set_enabled(self.device, state))
+        }
+    }
+
+    /// Set whether scrolling is inverted, so the content moves with the
+    /// fingers like on a touch screen.
+    pub fn set_natural_scroll(&mut self, enabled: bool) -> Result<(), InputConfigError> {
+        unsafe {
+            if libinput_device_config_scroll_has_natural_scroll(self.device) == 0 {
+                return Err(InputConfigError::Unsupported)
+            }
+            config_result(libinput_device_config_scroll_set_natural_scroll_enabled(self.device,
+                                                                                  enabled as _))
+        }
+    }
+
+    /// Set whether the buttons are swapped for left handed use.
+    ///
+    /// Fails if the device can't be switched to left handed mode (e.g a
+    /// keyboard).
+    pub fn set_left_handed(&mut self, enabled: bool) -> Result<(), InputConfigError> {
+        unsafe {
+            if libinput_device_config_left_handed_is_available(self.device) == 0 {
+                return Err(InputConfigError::Unsupported)
+            }
+            config_result(libinput_device_config_left_handed_set(self.device, enabled as _))
+        }
+    }
+
+    /// Set whether a touchpad is disabled while the user is typing.
+    ///
+    /// Fails if the device isn't a touchpad that supports this.
+    pub fn set_disable_while_typing(&mut self, enabled: bool) -> Result<(), InputConfigError> {
+        use self::libinput_config_dwt_state::*;
+        let state = if enabled {
+            LIBINPUT_CONFIG_DWT_ENABLED
+        } else {
+            LIBINPUT_CONFIG_DWT_DISABLED
+        };
+        unsafe {
+            if libinput_device_config_dwt_is_available(self.device) == 0 {
+                return Err(InputConfigError::Unsupported)
+            }
+            config_result(libinput_device_config_dwt_set_enabled(self.device, state))
+        }
+    }
+
+    /// Set how this device generates scroll events.
+    ///
+    /// Fails if the device doesn't support the scroll method (e.g two finger
+    /// scrolling on a mouse).
+    pub fn set_scroll_method(&mut self, method: ScrollMethod) -> Result<(), InputConfigError> {
+        unsafe {
+            let method: libinput_config_scroll_method = method.into();
+            check_method(libinput_device_config_scroll_get_methods(self.device), method as u32)?;
+            config_result(libinput_device_config_scroll_set_method(self.device, method))
+        }
+    }
+
+    /// Set how this device generates button events from clicks.
+    ///
+    /// Fails if the device doesn't support the click method.
+    pub fn set_click_method(&mut self, method: ClickMethod) -> Result<(), InputConfigError> {
+        unsafe {
+            let method: libinput_config_click_method = method.into();
+            check_method(libinput_device_config_click_get_methods(self.device), method as u32)?;
+            config_result(libinput_device_config_click_set_method(self.device, method))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The methods a typical clickpad reports as supported.
+    fn trackpad() -> (u32, u32) {
+        use self::libinput_config_click_method::*;
+        use self::libinput_config_scroll_method::*;
+        (LIBINPUT_CONFIG_SCROLL_2FG as u32 | LIBINPUT_CONFIG_SCROLL_EDGE as u32,
+         LIBINPUT_CONFIG_CLICK_METHOD_BUTTON_AREAS as u32
+         | LIBINPUT_CONFIG_CLICK_METHOD_CLICKFINGER as u32)
+    }
+
+    fn scroll(method: ScrollMethod) -> u32 {
+        let method: libinput_config_scroll_method = method.into();
+        method as u32
+    }
+
+    fn click(method: ClickMethod) -> u32 {
+        let method: libinput_config_click_method = method.into();
+        method as u32
+    }
+
+    #[test]
+    fn trackpad_accepts_its_scroll_methods() {
+        let (scroll_methods, _) = trackpad();
+        assert_eq!(check_method(scroll_methods, scroll(ScrollMethod::TwoFinger)), Ok(()));
+        assert_eq!(check_method(scroll_methods, scroll(ScrollMethod::Edge)), Ok(()));
+        assert_eq!(check_method(scroll_methods, scroll(ScrollMethod::NoScroll)), Ok(()));
+        assert_eq!(check_method(scroll_methods, scroll(ScrollMethod::OnButtonDown)),
+                   Err(InputConfigError::Unsupported));
+    }
+
+    #[test]
+    fn trackpad_accepts_its_click_methods() {
+        let (_, click_methods) = trackpad();
+        assert_eq!(check_method(click_methods, click(ClickMethod::ButtonAreas)), Ok(()));
+        assert_eq!(check_method(click_methods, click(ClickMethod::ClickFinger)), Ok(()));
+        assert_eq!(check_method(click_methods, click(ClickMethod::None)), Ok(()));
+    }
+
+    #[test]
+    fn mouse_only_turns_methods_off() {
+        assert_eq!(check_method(0, scroll(ScrollMethod::TwoFinger)),
+                   Err(InputConfigError::Unsupported));
+        assert_eq!(check_method(0, click(ClickMethod::ClickFinger)),
+                   Err(InputConfigError::Unsupported));
+        assert_eq!(check_method(0, scroll(ScrollMethod::NoScroll)), Ok(()));
+        assert_eq!(check_method(0, click(ClickMethod::None)), Ok(()));
+    }
+
+    #[test]
+    fn accel_speed_must_be_in_range() {
+        assert_eq!(check_accel_speed(-1.0), Ok(()));
+        assert_eq!(check_accel_speed(0.0), Ok(()));
+        assert_eq!(check_accel_speed(0.5), Ok(()));
+        assert_eq!(check_accel_speed(1.0), Ok(()));
+        assert_eq!(check_accel_speed(-1.01), Err(InputConfigError::Invalid));
+        assert_eq!(check_accel_speed(2.0), Err(InputConfigError::Invalid));
+        assert_eq!(check_accel_speed(::std::f64::NAN), Err(InputConfigError::Invalid));
+        assert_eq!(check_accel_speed(::std::f64::INFINITY), Err(InputConfigError::Invalid));
+    }
+
+    #[test]
+    fn config_status_maps_to_result() {
+        use self::libinput_config_status::*;
+        assert_eq!(config_result(LIBINPUT_CONFIG_STATUS_SUCCESS), Ok(()));
+        assert_eq!(config_result(LIBINPUT_CONFIG_STATUS_UNSUPPORTED),
+                   Err(InputConfigError::Unsupported));
+        assert_eq!(config_result(LIBINPUT_CONFIG_STATUS_INVALID),
+                   Err(InputConfigError::Invalid));
+    }
+}
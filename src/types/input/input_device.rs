@@ -1,12 +1,16 @@
 use std::{cell::Cell, rc::Weak};
 
 use libc::{c_double, c_uint};
-use wlroots_sys::{wlr_input_device, wlr_input_device_pointer, wlr_input_device_type,
-                  wlr_input_device_type::*};
+use wlroots_sys::{wlr_input_device, wlr_input_device_is_libinput, wlr_input_device_pointer,
+                  wlr_input_device_type, wlr_input_device_type::*,
+                  wlr_libinput_get_device_handle};
 
+use errors::InputConfigError;
+use super::input_config::LibinputDevice;
 use utils::c_to_rust_string;
 
-use {KeyboardHandle, PointerHandle, TouchHandle, TabletPadHandle, TabletToolHandle};
+use {ClickMethod, KeyboardHandle, PointerHandle, ScrollMethod, SwitchHandle, TouchHandle,
+     TabletPadHandle, TabletToolHandle};
 
 /// A handle to an input device.
 pub enum InputHandle {
@@ -14,7 +18,32 @@ pub enum InputHandle {
     Pointer(PointerHandle),
     Touch(TouchHandle),
     TabletPad(TabletPadHandle),
-    TabletTool(TabletToolHandle)
+    TabletTool(TabletToolHandle),
+    Switch(SwitchHandle)
+}
+
+/// The kind of an input device.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InputDeviceType {
+    Keyboard,
+    Pointer,
+    Touch,
+    TabletTool,
+    TabletPad,
+    Switch
+}
+
+impl From<wlr_input_device_type> for InputDeviceType {
+    fn from(dev_type: wlr_input_device_type) -> Self {
+        match dev_type {
+            WLR_INPUT_DEVICE_KEYBOARD => InputDeviceType::Keyboard,
+            WLR_INPUT_DEVICE_POINTER => InputDeviceType::Pointer,
+            WLR_INPUT_DEVICE_TOUCH => InputDeviceType::Touch,
+            WLR_INPUT_DEVICE_TABLET_TOOL => InputDeviceType::TabletTool,
+            WLR_INPUT_DEVICE_TABLET_PAD => InputDeviceType::TabletPad,
+            WLR_INPUT_DEVICE_SWITCH => InputDeviceType::Switch
+        }
+    }
 }
 
 pub(crate) struct InputState {
@@ -42,18 +71,23 @@ impl InputDevice {
         InputDevice { device: self.device }
     }
 
+    /// Get the USB vendor id of the device, or 0 if it doesn't have one.
     pub fn vendor(&self) -> c_uint {
         unsafe { (*self.device).vendor }
     }
 
+    /// Get the USB product id of the device, or 0 if it doesn't have one.
     pub fn product(&self) -> c_uint {
         unsafe { (*self.device).product }
     }
 
+    /// Get the name of the device, e.g to apply configuration by name.
     pub fn name(&self) -> Option<String> {
         unsafe { c_to_rust_string((*self.device).name) }
     }
 
+    /// Get the name of the output the device is mapped to, if it's mapped to
+    /// one (e.g a touch screen).
     pub fn output_name(&self) -> Option<String> {
         unsafe { c_to_rust_string((*self.device).output_name) }
     }
@@ -70,6 +104,13 @@ impl InputDevice {
         unsafe { (*self.device).type_ }
     }
 
+    /// Get the kind of the device.
+    ///
+    /// This is the same as `dev_type`, without the raw wlroots enum.
+    pub fn device_type(&self) -> InputDeviceType {
+        self.dev_type().into()
+    }
+
     /// Get a handle to the backing input device.
     pub fn device(&self) -> InputHandle {
         unsafe {
@@ -94,7 +135,49 @@ impl InputDevice {
                     let tablet_pad_ptr = (*self.device).__bindgen_anon_1.tablet_pad;
                     InputHandle::TabletPad(TabletPadHandle::from_ptr(tablet_pad_ptr))
                 },
+                WLR_INPUT_DEVICE_SWITCH => {
+                    let switch_ptr = (*self.device).__bindgen_anon_1.lid_switch;
+                    InputHandle::Switch(SwitchHandle::from_ptr(switch_ptr))
+                },
+            }
+        }
+    }
+
+    /// Set how this device generates scroll events.
+    ///
+    /// Fails if the device isn't managed by libinput or doesn't support
+    /// the scroll method (e.g two finger scrolling on a mouse).
+    pub fn set_scroll_method(&mut self, method: ScrollMethod) -> Result<(), InputConfigError> {
+        self.libinput_handle()
+            .ok_or(InputConfigError::NotLibinput)?
+            .set_scroll_method(method)
+    }
+
+    /// Set how this device generates button events from clicks.
+    ///
+    /// Fails if the device isn't managed by libinput or doesn't support
+    /// the click method.
+    pub fn set_click_method(&mut self, method: ClickMethod) -> Result<(), InputConfigError> {
+        self.libinput_handle()
+            .ok_or(InputConfigError::NotLibinput)?
+            .set_click_method(method)
+    }
+
+    /// Get the libinput device backing this device, to configure e.g
+    /// acceleration or tap to click.
+    ///
+    /// Returns `None` if the device isn't managed by libinput (e.g on the
+    /// Wayland, X11 and headless backends).
+    pub fn libinput_handle(&mut self) -> Option<LibinputDevice> {
+        unsafe {
+            if !wlr_input_device_is_libinput(self.device) {
+                return None
+            }
+            let handle = wlr_libinput_get_device_handle(self.device);
+            if handle.is_null() {
+                return None
             }
+            Some(LibinputDevice::from_ptr(handle))
         }
     }
 
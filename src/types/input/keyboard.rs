@@ -3,7 +3,8 @@ use std::{fmt, panic, ptr, cell::Cell, rc::{Rc, Weak}};
 
 use errors::{HandleErr, HandleResult};
 use wlroots_sys::{wlr_input_device, wlr_keyboard, wlr_keyboard_get_modifiers, wlr_keyboard_led,
-                  wlr_keyboard_led_update, wlr_keyboard_modifier, wlr_keyboard_set_keymap};
+                  wlr_keyboard_led_update, wlr_keyboard_modifier, wlr_keyboard_set_keymap,
+                  wlr_keyboard_set_repeat_info};
 pub use wlroots_sys::{wlr_key_state, wlr_keyboard_modifiers};
 
 use xkbcommon::xkb::{self, Keycode, Keymap, LedIndex, ModIndex};
@@ -96,6 +97,9 @@ impl Keyboard {
     }
 
     /// Set the keymap for this Keyboard.
+    ///
+    /// This allows each keyboard to have its own layout. Keyboards start out
+    /// with the keymap described by the `XKB_DEFAULT_*` environment variables.
     pub fn set_keymap(&mut self, keymap: &Keymap) {
         unsafe {
             // NOTE wlr_keyboard_set_keymap updates the reference count,
@@ -165,20 +169,48 @@ impl Keyboard {
         }
     }
 
-    /// Update the LED lights using the provided bitmap.
+    /// Set how keys repeat when held down on this keyboard.
     ///
-    /// 1 means one, 0 means off.
-    pub fn update_led(&mut self, leds: KeyboardLed) {
+    /// `rate` is the number of repeats per second and `delay` is how long,
+    /// in milliseconds, a key has to be held before it starts repeating.
+    /// A rate of `0` disables key repeat.
+    pub fn set_repeat_info(&mut self, rate: i32, delay: i32) {
+        unsafe { wlr_keyboard_set_repeat_info(self.keyboard, rate, delay) }
+    }
+
+    /// Turn on exactly the given LED lights, turning off the rest, e.g to keep
+    /// the caps lock light in sync with the modifiers reported by XKB.
+    pub fn set_leds(&mut self, leds: KeyboardLed) {
         unsafe {
             wlr_keyboard_led_update(self.keyboard, leds.bits() as u32);
         }
     }
 
-    /// Get the modifiers that are currently pressed on the keyboard.
-    pub fn get_modifiers(&self) -> KeyboardModifier {
+    /// Update the LED lights using the provided bitmap.
+    ///
+    /// 1 means on, 0 means off.
+    #[deprecated(note = "use `Keyboard::set_leds` instead")]
+    pub fn update_led(&mut self, leds: KeyboardLed) {
+        self.set_leds(leds)
+    }
+
+    /// Get the LED lights that are currently on.
+    pub fn leds(&self) -> KeyboardLed {
+        unsafe { KeyboardLed::from_bits_truncate((*self.keyboard).leds) }
+    }
+
+    /// Get the modifiers that are currently active on the keyboard, including
+    /// latched and locked modifiers (e.g caps lock).
+    pub fn modifiers(&self) -> KeyboardModifier {
         unsafe { KeyboardModifier::from_bits_truncate(wlr_keyboard_get_modifiers(self.keyboard)) }
     }
 
+    /// Get the modifiers that are currently active on the keyboard.
+    #[deprecated(note = "use `Keyboard::modifiers` instead")]
+    pub fn get_modifiers(&self) -> KeyboardModifier {
+        self.modifiers()
+    }
+
     /// Get the modifier masks for each group.
     pub fn get_modifier_masks(&self) -> wlr_keyboard_modifiers {
         unsafe { (*self.keyboard).modifiers }
@@ -326,6 +358,8 @@ impl KeyboardHandle {
     }
 }
 
+impl_handle!(KeyboardHandle => Keyboard);
+
 impl Default for KeyboardHandle {
     fn default() -> Self {
         KeyboardHandle::new()
@@ -1,13 +1,17 @@
+mod input_config;
 mod input_device;
 mod keyboard;
 mod pointer;
+mod switch;
 mod touch;
 mod tablet_tool;
 mod tablet_pad;
 
+pub use self::input_config::{ClickMethod, LibinputDevice, ScrollMethod};
 pub use self::input_device::*;
 pub use self::keyboard::*;
 pub use self::pointer::*;
+pub use self::switch::*;
 pub use self::tablet_pad::*;
 pub use self::tablet_tool::*;
 pub use self::touch::*;
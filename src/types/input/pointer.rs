@@ -228,6 +228,8 @@ impl PointerHandle {
     }
 }
 
+impl_handle!(PointerHandle => Pointer);
+
 impl Default for PointerHandle {
     fn default() -> Self {
         PointerHandle::new()
@@ -0,0 +1,235 @@
+//! Switch devices, such as the lid of a laptop or its tablet mode switch.
+
+use std::{ptr, cell::Cell, rc::{Rc, Weak}};
+
+use errors::{HandleErr, HandleResult};
+use wlroots_sys::{wlr_input_device, wlr_switch};
+
+use super::input_device::{InputDevice, InputState};
+
+#[derive(Debug)]
+pub struct Switch {
+    /// The structure that ensures weak handles to this structure are still alive.
+    ///
+    /// They contain weak handles, and will safely not use dead memory when this
+    /// is freed by wlroots.
+    ///
+    /// If this is `None`, then this is from an upgraded `SwitchHandle`, and
+    /// the operations are **unchecked**.
+    /// This is means safe operations might fail, but only if you use the unsafe
+    /// marked function `upgrade` on a `SwitchHandle`.
+    liveliness: Rc<Cell<bool>>,
+    /// The device that refers to this switch.
+    device: InputDevice,
+    /// The underlying switch data.
+    switch: *mut wlr_switch
+}
+
+#[derive(Debug)]
+pub struct SwitchHandle {
+    /// The Rc that ensures that this handle is still alive.
+    ///
+    /// When wlroots deallocates the switch associated with this handle,
+    /// this can no longer be used.
+    handle: Weak<Cell<bool>>,
+    /// The device that refers to this switch.
+    device: InputDevice,
+    /// The underlying switch data.
+    switch: *mut wlr_switch
+}
+
+impl Switch {
+    /// Tries to convert an input device to a Switch.
+    ///
+    /// Returns none if it is of a different input variant.
+    ///
+    /// # Safety
+    /// This creates a totally new Switch (e.g with its own reference count)
+    /// so only do this once per `wlr_input_device`!
+    pub(crate) unsafe fn new_from_input_device(device: *mut wlr_input_device) -> Option<Self> {
+        use wlroots_sys::wlr_input_device_type::*;
+        match (*device).type_ {
+            WLR_INPUT_DEVICE_SWITCH => {
+                let switch = (*device).__bindgen_anon_1.lid_switch;
+                let liveliness = Rc::new(Cell::new(false));
+                let handle = Rc::downgrade(&liveliness);
+                let state = Box::new(InputState { handle,
+                                                  device: InputDevice::from_ptr(device) });
+                (*switch).data = Box::into_raw(state) as *mut _;
+                Some(Switch { liveliness,
+                              device: InputDevice::from_ptr(device),
+                              switch })
+            }
+            _ => None
+        }
+    }
+
+    /// Creates an unbound `Switch` from a `SwitchHandle`
+    unsafe fn from_handle(handle: &SwitchHandle) -> HandleResult<Self> {
+        let liveliness = handle.handle
+                               .upgrade()
+                               .ok_or_else(|| HandleErr::AlreadyDropped)?;
+        Ok(Switch { liveliness,
+                    device: handle.input_device()?.clone(),
+                    switch: handle.as_ptr() })
+    }
+
+    /// Gets the wlr_input_device associated with this `Switch`.
+    pub fn input_device(&self) -> &InputDevice {
+        &self.device
+    }
+
+    /// Gets the wlr_switch associated with this `Switch`.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_switch {
+        self.switch
+    }
+
+    /// Creates a weak reference to a `Switch`.
+    ///
+    /// # Panics
+    /// If this `Switch` is a previously upgraded `SwitchHandle`,
+    /// then this function will panic.
+    pub fn weak_reference(&self) -> SwitchHandle {
+        SwitchHandle { handle: Rc::downgrade(&self.liveliness),
+                       // NOTE Rationale for cloning:
+                       // We can't use the switch handle unless the switch is alive,
+                       // which means the device pointer is still alive.
+                       device: unsafe { self.device.clone() },
+                       switch: self.switch }
+    }
+}
+impl Drop for Switch {
+    fn drop(&mut self) {
+        if Rc::strong_count(&self.liveliness) == 1 {
+            wlr_log!(WLR_DEBUG, "Dropped Switch {:p}", self.switch);
+            unsafe {
+                let _ = Box::from_raw((*self.switch).data as *mut InputState);
+            }
+            let weak_count = Rc::weak_count(&self.liveliness);
+            if weak_count > 0 {
+                wlr_log!(WLR_DEBUG,
+                         "Still {} weak pointers to Switch {:p}",
+                         weak_count,
+                         self.switch);
+            }
+        }
+    }
+}
+
+impl SwitchHandle {
+    /// Constructs a new SwitchHandle that is always invalid. Calling `run` on this
+    /// will always fail.
+    ///
+    /// This is useful for pre-filling a value before it's provided by the server, or
+    /// for mocking/testing.
+    pub fn new() -> Self {
+        unsafe {
+            SwitchHandle { handle: Weak::new(),
+                           // NOTE Rationale for null pointer here:
+                           // It's never used, because you can never upgrade it,
+                           // so no way to dereference it and trigger UB.
+                           device: InputDevice::from_ptr(ptr::null_mut()),
+                           switch: ptr::null_mut() }
+        }
+    }
+
+    /// Creates an SwitchHandle from the raw pointer, using the saved
+    /// user data to recreate the memory model.
+    ///
+    /// # Panics
+    /// Panics if the wlr_switch wasn't allocated using `new_from_input_device`.
+    pub(crate) unsafe fn from_ptr(switch: *mut wlr_switch) -> Self {
+        if (*switch).data.is_null() {
+            panic!("Tried to get handle to switch that wasn't set up properly");
+        }
+        let data = Box::from_raw((*switch).data as *mut InputState);
+        let handle = data.handle.clone();
+        let device = data.device.clone();
+        (*switch).data = Box::into_raw(data) as *mut _;
+        SwitchHandle { handle,
+                       switch,
+                       device }
+    }
+
+    /// Upgrades the switch handle to a reference to the backing `Switch`.
+    ///
+    /// # Unsafety
+    /// This function is unsafe, because it creates an unbound `Switch`
+    /// which may live forever..
+    /// But no switch lives forever and might be disconnected at any time.
+    pub unsafe fn upgrade(&self) -> HandleResult<Switch> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            // NOTE
+            // We drop the Rc here because having two would allow a dangling
+            // switch to exist!
+            .and_then(|check| {
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                let switch = Switch::from_handle(self)?;
+                check.set(true);
+                Ok(switch)
+            })
+    }
+
+    /// Run a function on the referenced `Switch`, if it still exists
+    ///
+    /// Returns the result of the function, if successful
+    ///
+    /// # Safety
+    /// By enforcing a rather harsh limit on the lifetime of the switch
+    /// to a short lived scope of an anonymous function,
+    /// this function ensures the `Switch` does not live longer
+    /// than it exists.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `Switch` is already
+    /// borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&Switch) -> R
+    {
+        self.try_run(|switch| runner(switch))
+    }
+
+    /// Gets the wlr_input_device associated with this SwitchHandle.
+    pub fn input_device(&self) -> HandleResult<&InputDevice> {
+        match self.handle.upgrade() {
+            Some(_) => Ok(&self.device),
+            None => Err(HandleErr::AlreadyDropped)
+        }
+    }
+
+    /// Gets the `wlr_switch` associated with this `SwitchHandle`.
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_switch {
+        self.switch
+    }
+}
+
+impl_handle!(SwitchHandle => Switch);
+
+impl Default for SwitchHandle {
+    fn default() -> Self {
+        SwitchHandle::new()
+    }
+}
+
+impl Clone for SwitchHandle {
+    fn clone(&self) -> Self {
+        SwitchHandle { switch: self.switch,
+                       handle: self.handle.clone(),
+                       // NOTE Rationale for unsafe clone:
+                       //
+                       // You can only access it after a call to `upgrade`,
+                       // and that implicitly checks that it is valid.
+                       device: unsafe { self.device.clone() } }
+    }
+}
+
+impl PartialEq for SwitchHandle {
+    fn eq(&self, other: &SwitchHandle) -> bool {
+        self.switch == other.switch
+    }
+}
+
+impl Eq for SwitchHandle {}
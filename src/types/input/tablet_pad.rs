@@ -1,11 +1,43 @@
 //! TODO Documentation
-use std::{panic, ptr, cell::Cell, rc::{Rc, Weak}};
+use std::{panic, ptr, slice, cell::Cell, rc::{Rc, Weak}};
+
+use libc::c_uint;
 
 use errors::{HandleErr, HandleResult};
-use wlroots_sys::{wlr_input_device, wlr_tablet_pad};
+use wlroots_sys::{wlr_input_device, wlr_tablet_pad, wlr_tablet_pad_group};
 
 use super::input_device::{InputDevice, InputState};
 
+/// A group of controls on a `TabletPad` that share a mode.
+///
+/// The values are indices of the controls on the pad, e.g a value of `2`
+/// in `buttons` refers to the third button of the pad.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TabletPadGroup {
+    pub buttons: Vec<u32>,
+    pub rings: Vec<u32>,
+    pub strips: Vec<u32>,
+    /// The number of modes this group can be switched between.
+    pub mode_count: u32
+}
+
+impl TabletPadGroup {
+    unsafe fn from_ptr(group: *mut wlr_tablet_pad_group) -> Self {
+        TabletPadGroup { buttons: indices((*group).buttons, (*group).button_count),
+                         rings: indices((*group).rings, (*group).ring_count),
+                         strips: indices((*group).strips, (*group).strip_count),
+                         mode_count: (*group).mode_count as u32 }
+    }
+}
+
+/// Copies a C array of control indices, which may be null if it's empty.
+unsafe fn indices(ptr: *mut c_uint, length: usize) -> Vec<u32> {
+    if ptr.is_null() {
+        return Vec::new()
+    }
+    slice::from_raw_parts(ptr, length).iter().map(|&index| index as u32).collect()
+}
+
 #[derive(Debug)]
 pub struct TabletPad {
     /// The structure that ensures weak handles to this structure are still alive.
@@ -76,7 +108,34 @@ impl TabletPad {
         &self.device
     }
 
-    // TODO Real functions
+    /// Get the number of buttons on this pad.
+    pub fn button_count(&self) -> usize {
+        unsafe { (*self.pad).button_count as usize }
+    }
+
+    /// Get the number of rings on this pad.
+    pub fn ring_count(&self) -> usize {
+        unsafe { (*self.pad).ring_count as usize }
+    }
+
+    /// Get the number of strips on this pad.
+    pub fn strip_count(&self) -> usize {
+        unsafe { (*self.pad).strip_count as usize }
+    }
+
+    /// Get the groups of controls on this pad.
+    ///
+    /// Pads with multiple modes (e.g a button that switches what a ring does)
+    /// split their buttons, rings and strips into groups, each with their own mode.
+    pub fn groups(&self) -> Vec<TabletPadGroup> {
+        let mut result = Vec::new();
+        unsafe {
+            wl_list_for_each!((*self.pad).groups, link, (group: wlr_tablet_pad_group) => {
+                result.push(TabletPadGroup::from_ptr(group));
+            });
+        }
+        result
+    }
 
     /// Creates a weak reference to a `TabletPad`.
     ///
@@ -194,7 +253,7 @@ impl TabletPadHandle {
                                       // Sanity check that it hasn't been tampered with.
                                       if !check.get() {
                                           wlr_log!(WLR_ERROR,
-                                                   "After running tablet tool callback, mutable \
+                                                   "After running tablet pad callback, mutable \
                                                     lock was false for: {:?}",
                                                    pad);
                                           panic!("Lock in incorrect state!");
@@ -221,6 +280,8 @@ impl TabletPadHandle {
     }
 }
 
+impl_handle!(TabletPadHandle => TabletPad);
+
 impl Default for TabletPadHandle {
     fn default() -> Self {
         TabletPadHandle::new()
@@ -221,6 +221,8 @@ impl TabletToolHandle {
     }
 }
 
+impl_handle!(TabletToolHandle => TabletTool);
+
 impl Default for TabletToolHandle {
     fn default() -> Self {
         TabletToolHandle::new()
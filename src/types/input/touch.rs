@@ -227,6 +227,8 @@ impl TouchHandle {
     }
 }
 
+impl_handle!(TouchHandle => Touch);
+
 impl Default for TouchHandle {
     fn default() -> Self {
         TouchHandle::new()
@@ -1,6 +1,6 @@
 //! TODO Documentation
 
-use std::{panic, ptr};
+use std::{fmt, panic, ptr};
 use std::cell::Cell;
 use std::ffi::CStr;
 use std::mem::ManuallyDrop;
@@ -8,8 +8,12 @@ use std::rc::{Rc, Weak};
 use std::time::Duration;
 
 use libc::{c_float, c_int, clock_t};
-use wayland_sys::server::WAYLAND_SERVER_HANDLE;
+use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
+#[cfg(feature = "adaptive-sync")]
+use wlroots_sys::{wlr_output_adaptive_sync_status, wlr_output_adaptive_sync_status::*,
+                  wlr_output_enable_adaptive_sync};
 use wlroots_sys::{timespec, wl_list, wl_output_subpixel, wl_output_transform, wlr_output,
+                  wlr_output_cursor, wlr_output_cursor_create, wlr_output_cursor_set_image,
                   wlr_output_damage, wlr_output_effective_resolution, wlr_output_enable,
                   wlr_output_get_gamma_size, wlr_output_make_current, wlr_output_mode,
                   wlr_output_schedule_frame, wlr_output_set_custom_mode,
@@ -17,21 +21,69 @@ use wlroots_sys::{timespec, wl_list, wl_output_subpixel, wl_output_transform, wl
                   wlr_output_set_position, wlr_output_set_scale, wlr_output_set_transform,
                   wlr_output_swap_buffers, wlr_output_transformed_resolution};
 
-use manager::UserOutput;
-use errors::{HandleErr, HandleResult};
+use manager::{OutputDamageHandler, UserOutput, UserOutputDamage};
+use errors::{AcquireError, GammaError, HandleErr, HandleResult, ModeSetError};
 use utils::c_to_rust_string;
-use {OutputLayoutHandle, OutputMode};
+use {OutputLayoutHandle, OutputMode, OutputModes};
 
 pub type Subpixel = wl_output_subpixel;
 pub type Transform = wl_output_transform;
+#[cfg(feature = "adaptive-sync")]
+pub type AdaptiveSyncStatus = wlr_output_adaptive_sync_status;
 
-use {Origin, OutputDamage, PixmanRegion, Size, Surface, SurfaceHandle};
+/// How the contents of an output are rotated and flipped, see
+/// `Output::set_orientation`.
+///
+/// Rotations are counter-clockwise, e.g a monitor turned on its side
+/// clockwise needs `Rotate90`. Flipping is around the vertical axis and
+/// happens before the rotation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Orientation {
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flipped,
+    FlippedRotate90,
+    FlippedRotate180,
+    FlippedRotate270
+}
+
+impl From<Orientation> for Transform {
+    fn from(orientation: Orientation) -> Transform {
+        use self::Orientation::*;
+        use wlroots_sys::wl_output_transform::*;
+        match orientation {
+            Normal => WL_OUTPUT_TRANSFORM_NORMAL,
+            Rotate90 => WL_OUTPUT_TRANSFORM_90,
+            Rotate180 => WL_OUTPUT_TRANSFORM_180,
+            Rotate270 => WL_OUTPUT_TRANSFORM_270,
+            Flipped => WL_OUTPUT_TRANSFORM_FLIPPED,
+            FlippedRotate90 => WL_OUTPUT_TRANSFORM_FLIPPED_90,
+            FlippedRotate180 => WL_OUTPUT_TRANSFORM_FLIPPED_180,
+            FlippedRotate270 => WL_OUTPUT_TRANSFORM_FLIPPED_270
+        }
+    }
+}
+
+use {Area, Cursor, Origin, OutputDamage, PixmanRegion, Size, Surface, SurfaceHandle};
+use render::Image;
 
 pub(crate) struct OutputState {
     pub(crate) output: *mut UserOutput,
     handle: Weak<Cell<bool>>,
     damage: *mut wlr_output_damage,
-    layout_handle: Option<OutputLayoutHandle>
+    layout_handle: Option<OutputLayoutHandle>,
+    /// Set once the output has started being destroyed.
+    pub(crate) destroyed: bool,
+    /// The cursor set with `Output::set_hardware_cursor`, once there is one.
+    cursor: *mut wlr_output_cursor,
+    /// Whether the last attempt to use a hardware cursor failed.
+    hardware_cursor_failed: bool,
+    /// Listener for the damage tracking events, if a handler was set.
+    damage_handler: *mut UserOutputDamage,
+    /// Whether the layout this output is in positioned it automatically.
+    pub(crate) auto_configured: bool
 }
 
 #[derive(Debug)]
@@ -94,7 +146,12 @@ impl Output {
         let state = Box::new(OutputState { output: ptr::null_mut(),
                                            handle,
                                            damage: damage.as_ptr(),
-                                           layout_handle: None });
+                                           layout_handle: None,
+                                           destroyed: false,
+                                           cursor: ptr::null_mut(),
+                                           hardware_cursor_failed: false,
+                                           damage_handler: ptr::null_mut(),
+                                           auto_configured: false });
         (*output).data = Box::into_raw(state) as *mut _;
         Output { liveliness,
                  damage,
@@ -114,6 +171,14 @@ impl Output {
         (*self.output).data = Box::into_raw(data) as *mut _;
     }
 
+    /// Records whether the layout this output is in positioned it automatically.
+    pub(crate) unsafe fn set_auto_configured(&mut self, auto_configured: bool) {
+        let user_data = self.user_data();
+        if !user_data.is_null() {
+            (*user_data).auto_configured = auto_configured;
+        }
+    }
+
     unsafe fn user_data(&mut self) -> *mut OutputState {
         (*self.output).data as *mut _
     }
@@ -189,13 +254,97 @@ impl Output {
     // What will happen?
 
     /// Set this to be the current mode for the Output.
-    pub fn set_mode(&mut self, mode: OutputMode) -> bool {
-        unsafe { wlr_output_set_mode(self.output, mode.as_ptr()) }
+    pub fn set_mode(&mut self, mode: OutputMode) -> Result<(), ModeSetError> {
+        self.check_not_destroyed()?;
+        unsafe {
+            if wlr_output_set_mode(self.output, mode.as_ptr()) {
+                Ok(())
+            } else {
+                Err(ModeSetError::Rejected)
+            }
+        }
+    }
+
+    /// Set this to be the current mode for the Output, keeping the scale and
+    /// transform that are currently applied.
+    ///
+    /// Some backends reset the scale and transform when the mode changes,
+    /// so they are re-applied after the mode has been set. If the backend
+    /// rejects the mode the previous mode is set again.
+    ///
+    /// NOTE This is not atomic. This wlroots has no pending output state to
+    /// commit the mode, scale and transform at once, so they are applied one
+    /// after the other and clients can see the state in between. Restoring
+    /// the previous mode is best-effort, the backend could reject it as well.
+    pub fn set_mode_preserving(&mut self, mode: OutputMode) -> Result<(), ModeSetError> {
+        self.preserving_scale_and_transform(|output| output.set_mode(mode))
+    }
+
+    /// Set a custom mode for this output, keeping the scale and transform
+    /// that are currently applied.
+    ///
+    /// See `set_mode_preserving` for how this differs from `set_custom_mode`.
+    pub fn set_custom_mode_preserving(&mut self,
+                                      size: Size,
+                                      refresh: i32)
+                                      -> Result<(), ModeSetError> {
+        self.preserving_scale_and_transform(|output| output.set_custom_mode(size, refresh))
+    }
+
+    /// Set the mode with the function, then put back the scale and transform
+    /// if the mode change reset them. If the mode is rejected the previous
+    /// one is restored.
+    fn preserving_scale_and_transform<F>(&mut self, set_mode: F) -> Result<(), ModeSetError>
+        where F: FnOnce(&mut Output) -> Result<(), ModeSetError>
+    {
+        self.check_not_destroyed()?;
+        let scale = self.scale();
+        let transform = self.get_transform();
+        let (mode, (width, height), refresh) =
+            unsafe { ((*self.output).current_mode, self.size(), (*self.output).refresh) };
+        let result = set_mode(self);
+        match result {
+            Err(ModeSetError::Destroyed) => return result,
+            Err(ModeSetError::Rejected) => unsafe {
+                if !mode.is_null() {
+                    wlr_output_set_mode(self.output, mode);
+                } else if width > 0 && height > 0 {
+                    wlr_output_set_custom_mode(self.output, width, height, refresh);
+                }
+            },
+            Ok(()) => {}
+        }
+        if self.scale() != scale {
+            self.set_scale(scale);
+        }
+        if self.get_transform() != transform {
+            self.transform(transform);
+        }
+        result
     }
 
     /// Set a custom mode for this output.
-    pub fn set_custom_mode(&mut self, size: Size, refresh: i32) -> bool {
-        unsafe { wlr_output_set_custom_mode(self.output, size.width, size.height, refresh) }
+    pub fn set_custom_mode(&mut self, size: Size, refresh: i32) -> Result<(), ModeSetError> {
+        self.check_not_destroyed()?;
+        unsafe {
+            if wlr_output_set_custom_mode(self.output, size.width, size.height, refresh) {
+                Ok(())
+            } else {
+                Err(ModeSetError::Rejected)
+            }
+        }
+    }
+
+    /// Modesetting an output that is in the middle of being destroyed can't
+    /// succeed, so this is checked up front to give a better error.
+    fn check_not_destroyed(&mut self) -> Result<(), ModeSetError> {
+        unsafe {
+            let data = self.user_data();
+            if data.is_null() || (*data).destroyed {
+                return Err(ModeSetError::Destroyed)
+            }
+        }
+        Ok(())
     }
 
     /// Gets the name of the output in UTF-8.
@@ -302,6 +451,35 @@ impl Output {
         (res, buffer_age)
     }
 
+    /// Makes this output the current rendering context and returns a guard
+    /// that swaps the buffers when it is dropped.
+    ///
+    /// This is the safe way to do manual (e.g opengl) rendering: the guard
+    /// borrows the output so it can't be made current twice, and the buffers
+    /// can't be left unswapped.
+    ///
+    /// Returns `AcquireError::Destroyed` if the output is being destroyed,
+    /// or `AcquireError::MakeCurrentFailed` if the backend could not make it
+    /// the current rendering context.
+    pub fn acquire<'output>(&'output mut self) -> Result<CurrentOutput<'output>, AcquireError> {
+        unsafe {
+            let data = self.user_data();
+            if data.is_null() || (*data).destroyed {
+                return Err(AcquireError::Destroyed)
+            }
+            match self.make_current() {
+                (true, buffer_age) => Ok(CurrentOutput::new(self, buffer_age)),
+                (false, _) => Err(AcquireError::MakeCurrentFailed)
+            }
+        }
+    }
+
+    #[deprecated(note = "use `Output::acquire` instead")]
+    pub fn attach_render<'output>(&'output mut self)
+                                  -> Result<CurrentOutput<'output>, AcquireError> {
+        self.acquire()
+    }
+
     /// Swaps the buffers and draws whatever is in the back buffer on the screen.
     ///
     /// If the time of the frame is not known, set `when` to None.
@@ -319,12 +497,12 @@ impl Output {
         where T: Into<Option<Duration>>,
               U: Into<Option<&'a mut PixmanRegion>>
     {
-        let when = when.into().map(|duration| {
+        let mut when = when.into().map(|duration| {
                                        timespec { tv_sec: duration.as_secs() as clock_t,
                                                   tv_nsec: duration.subsec_nanos() as clock_t }
                                    });
         let when_ptr =
-            when.map(|mut duration| &mut duration as *mut _).unwrap_or_else(|| ptr::null_mut());
+            when.as_mut().map(|when| when as *mut _).unwrap_or_else(|| ptr::null_mut());
         let damage = match damage.into() {
             Some(region) => &mut region.region as *mut _,
             None => ptr::null_mut()
@@ -387,17 +565,54 @@ impl Output {
         }
     }
 
+    /// Rotate and flip the contents of the output.
+    ///
+    /// This is the same as `transform`, without having to know the names of
+    /// the Wayland transforms.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.transform(orientation.into())
+    }
+
+    /// Rotates the output by applying the transform, then maps the cursor to
+    /// the rotated output.
+    ///
+    /// The cursor is mapped to the box the output takes up in the layout
+    /// after the transform is applied, so a quarter turn swaps its width and
+    /// height. This is a fixed region: if the output is moved or rotated
+    /// some other way afterwards the cursor needs to be mapped again.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` without applying the transform if
+    /// the cursor or the output aren't in an `OutputLayout`.
+    pub fn rotate(&mut self, transform: Transform, cursor: &mut Cursor) -> HandleResult<()> {
+        if unsafe { self.layout() }.is_none() {
+            return Err(HandleErr::AlreadyDropped)
+        }
+        cursor.clamp_to_layout()?;
+        self.transform(transform);
+        let (x, y) = self.layout_space_pos();
+        let size = effective_size(self.size(), self.scale(), transform);
+        cursor.map_to_region(Area::new(Origin::new(x, y), size));
+        cursor.clamp_to_layout()
+    }
+
     /// Get the modes associated with this output.
     ///
     /// Note that some backends may have zero modes.
     pub fn modes<'output>(&'output self) -> Vec<OutputMode<'output>> {
-        unsafe {
-            let mut result = vec![];
-            wl_list_for_each!((*self.output).modes, link, (mode: wlr_output_mode) => {
-                result.push(OutputMode::new(mode))
-            });
-            result
-        }
+        self.modes_iter().collect()
+    }
+
+    /// Iterate over the modes associated with this output, without
+    /// allocating.
+    ///
+    /// Note that some backends may have zero modes.
+    pub fn modes_iter<'output>(&'output self) -> OutputModes<'output> {
+        unsafe { OutputModes::new(&mut (*self.output).modes) }
+    }
+
+    /// Get the mode the output prefers, if any mode is marked as preferred.
+    pub fn preferred_mode<'output>(&'output self) -> Option<OutputMode<'output>> {
+        self.modes_iter().find(|mode| mode.is_preferred())
     }
 
     /// Enables or disables an output.
@@ -405,9 +620,101 @@ impl Output {
         unsafe { wlr_output_enable(self.output, enable) }
     }
 
-    /// Sets the gamma based on the size.
-    pub fn set_gamma(&mut self, size: u32, mut r: u16, mut g: u16, mut b: u16) {
-        unsafe { wlr_output_set_gamma(self.output, size, &mut r, &mut g, &mut b) }
+    /// Enables or disables adaptive sync (also known as variable refresh
+    /// rate) on this output.
+    ///
+    /// Returns whether the output ended up in the requested state. Backends
+    /// that don't support adaptive sync will return `false` when enabling.
+    #[cfg(feature = "adaptive-sync")]
+    pub fn enable_adaptive_sync(&mut self, enable: bool) -> bool {
+        unsafe {
+            wlr_output_enable_adaptive_sync(self.output, enable);
+        }
+        let expected = if enable {
+            WLR_OUTPUT_ADAPTIVE_SYNC_ENABLED
+        } else {
+            WLR_OUTPUT_ADAPTIVE_SYNC_DISABLED
+        };
+        self.adaptive_sync_status() == expected
+    }
+
+    /// Gets the current adaptive sync state of this output.
+    #[cfg(feature = "adaptive-sync")]
+    pub fn adaptive_sync_status(&self) -> AdaptiveSyncStatus {
+        unsafe { (*self.output).adaptive_sync_status }
+    }
+
+    /// Sets the gamma ramps of the output.
+    ///
+    /// Each ramp must have exactly `get_gamma_size()` entries.
+    pub fn set_gamma(&mut self, r: &[u16], g: &[u16], b: &[u16]) -> Result<(), GammaError> {
+        let size = self.get_gamma_size();
+        check_gamma_ramps(size, r, g, b)?;
+        unsafe {
+            // NOTE Rationale for casting away const:
+            // wlroots only reads from the ramps.
+            wlr_output_set_gamma(self.output,
+                                 size,
+                                 r.as_ptr() as *mut _,
+                                 g.as_ptr() as *mut _,
+                                 b.as_ptr() as *mut _)
+        }
+        Ok(())
+    }
+
+    /// Show `image` as the cursor of this output, in the cursor plane of the
+    /// backend if possible.
+    ///
+    /// This sets the image of a cursor owned by the output, so wlroots keeps
+    /// track of which cursor is in the cursor plane. The image should be
+    /// rendered at the scale of the output.
+    ///
+    /// Returns `true` if the cursor ended up in the cursor plane. Returns
+    /// `false` if the hotspot lies outside of the image or `pixels` is too
+    /// small for the stride and height, and also if the backend has no cursor
+    /// plane or the image is larger than the plane supports. In the last two
+    /// cases `hardware_cursor_failed` returns `true` and the compositor has
+    /// to draw the cursor itself when rendering the output.
+    pub fn set_hardware_cursor(&mut self, image: &Image) -> bool {
+        let valid = image.width > 0 && image.height > 0 && image.stride >= 0 &&
+                    image.hotspot_x >= 0 && image.hotspot_y >= 0 &&
+                    (image.hotspot_x as u32) < image.width &&
+                    (image.hotspot_y as u32) < image.height &&
+                    image.stride as u64 >= image.width as u64 * 4 &&
+                    image.pixels.len() as u64 >= image.stride as u64 * image.height as u64;
+        unsafe {
+            let data = self.user_data();
+            if data.is_null() {
+                return false
+            }
+            if (*data).cursor.is_null() {
+                // NOTE wlroots destroys the cursors of an output along with
+                // it, so this is never destroyed here.
+                (*data).cursor = wlr_output_cursor_create(self.output);
+            }
+            let cursor = (*data).cursor;
+            let res = valid && !cursor.is_null() &&
+                      wlr_output_cursor_set_image(cursor,
+                                                  image.pixels.as_ptr(),
+                                                  image.stride,
+                                                  image.width,
+                                                  image.height,
+                                                  image.hotspot_x,
+                                                  image.hotspot_y) &&
+                      (*self.output).hardware_cursor == cursor;
+            (*data).hardware_cursor_failed = !res;
+            res
+        }
+    }
+
+    /// Determines if the last call to `set_hardware_cursor` didn't end up
+    /// with a hardware cursor, meaning the cursor has to be rendered in
+    /// software.
+    pub fn hardware_cursor_failed(&self) -> bool {
+        unsafe {
+            let data = (*self.output).data as *mut OutputState;
+            !data.is_null() && (*data).hardware_cursor_failed
+        }
     }
 
     /// Get the gamma size.
@@ -434,6 +741,50 @@ impl Output {
         &mut *self.damage
     }
 
+    /// Set the handler for the damage tracking events of this output.
+    ///
+    /// This replaces any handler that was previously set.
+    pub fn set_damage_handler(&mut self, handler: Box<OutputDamageHandler>) {
+        unsafe {
+            let data = self.user_data();
+            if data.is_null() {
+                return
+            }
+            if !(*data).damage_handler.is_null() {
+                let mut old = Box::from_raw((*data).damage_handler);
+                old.remove_listeners();
+            }
+            let damage_ptr = self.damage.as_ptr();
+            let mut listener = UserOutputDamage::new((self.weak_reference(), handler));
+            wl_signal_add(&mut (*damage_ptr).events.frame as *mut _ as _,
+                          listener.frame_listener() as _);
+            wl_signal_add(&mut (*damage_ptr).events.destroy as *mut _ as _,
+                          listener.destroy_listener() as _);
+            (*data).damage_handler = Box::into_raw(listener);
+        }
+    }
+
+    /// Converts surface damage into damage in output pixels.
+    ///
+    /// `region` is in the coordinate space of a surface rendered at
+    /// `surface_scale`. Each rectangle is scaled by the ratio between this
+    /// output's scale and `surface_scale` and rounded outwards. The result is
+    /// then expanded by one pixel on every side, otherwise fractional scales
+    /// leave seams between neighbouring damaged rectangles. Finally the
+    /// rectangles are transformed into the buffer of this output, so the
+    /// result can be added to the output damage as is.
+    pub fn surface_damage_to_output(&self,
+                                    region: &PixmanRegion,
+                                    surface_scale: f32)
+                                    -> PixmanRegion {
+        let (width, height) = self.transformed_resolution();
+        damage_to_output(region,
+                         self.scale() / surface_scale,
+                         self.get_transform().invert(),
+                         width,
+                         height)
+    }
+
     pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_output {
         self.output
     }
@@ -459,6 +810,72 @@ impl Output {
     }
 }
 
+/// An `Output` that is the current rendering context.
+///
+/// Created with `Output::acquire`. When this is dropped the buffers are
+/// swapped, unless that was already done with `commit`.
+pub struct CurrentOutput<'output> {
+    output: &'output mut Output,
+    buffer_age: Option<c_int>,
+    swapped: bool
+}
+
+impl<'output> CurrentOutput<'output> {
+    fn new(output: &'output mut Output, buffer_age: Option<c_int>) -> Self {
+        CurrentOutput { output,
+                        buffer_age,
+                        swapped: false }
+    }
+
+    /// The drawing buffer age in number of frames, or None if unknown.
+    pub fn buffer_age(&self) -> Option<c_int> {
+        self.buffer_age
+    }
+
+    /// Get the output that is being rendered to.
+    pub fn output(&mut self) -> &mut Output {
+        self.output
+    }
+
+    /// Swaps the buffers with the given frame time and damage, instead of
+    /// swapping them without either when the guard is dropped.
+    pub fn commit<'a, T, U>(mut self, when: T, damage: U) -> bool
+        where T: Into<Option<Duration>>,
+              U: Into<Option<&'a mut PixmanRegion>>
+    {
+        self.commit_once(when.into(), damage.into())
+    }
+
+    fn commit_once(&mut self, when: Option<Duration>, damage: Option<&mut PixmanRegion>) -> bool {
+        if self.swapped {
+            wlr_log!(WLR_ERROR,
+                     "Tried to swap the buffers of {:p} more than once",
+                     self.output.output);
+            return false
+        }
+        self.swapped = true;
+        unsafe { self.output.swap_buffers(when, damage) }
+    }
+}
+
+impl<'output> fmt::Debug for CurrentOutput<'output> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CurrentOutput")
+         .field("output", &self.output)
+         .field("buffer_age", &self.buffer_age)
+         .field("swapped", &self.swapped)
+         .finish()
+    }
+}
+
+impl<'output> Drop for CurrentOutput<'output> {
+    fn drop(&mut self) {
+        if !self.swapped {
+            self.commit_once(None, None);
+        }
+    }
+}
+
 impl Drop for Output {
     fn drop(&mut self) {
         // NOTE
@@ -488,6 +905,20 @@ impl Drop for Output {
     }
 }
 
+/// Checks that gamma ramps can be set on an output with the gamma size.
+fn check_gamma_ramps(size: u32, r: &[u16], g: &[u16], b: &[u16]) -> Result<(), GammaError> {
+    if size == 0 {
+        return Err(GammaError::Unsupported)
+    }
+    for ramp in &[r, g, b] {
+        if ramp.len() != size as usize {
+            return Err(GammaError::LengthMismatch { expected: size as usize,
+                                                    actual: ramp.len() })
+        }
+    }
+    Ok(())
+}
+
 impl OutputHandle {
     /// Constructs a new OutputHandle that is always invalid. Calling `run` on this
     /// will always fail.
@@ -502,6 +933,17 @@ impl OutputHandle {
         }
     }
 
+    /// Frees the damage handler of the output, once the damage tracking
+    /// has been destroyed.
+    pub(crate) unsafe fn clear_damage_handler(&self) {
+        let data = (*self.output).data as *mut OutputState;
+        if data.is_null() || (*data).damage_handler.is_null() {
+            return
+        }
+        Box::from_raw((*data).damage_handler);
+        (*data).damage_handler = ptr::null_mut();
+    }
+
     /// Creates an OutputHandle from the raw pointer, using the saved
     /// user data to recreate the memory model.
     pub(crate) unsafe fn from_ptr(output: *mut wlr_output) -> Self {
@@ -577,8 +1019,18 @@ impl OutputHandle {
     pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_output {
         self.output
     }
+
+    /// Determines if the output this handle refers to still exists.
+    ///
+    /// Unlike `run`, this does not borrow the output, so it can be used
+    /// while the output is already being used elsewhere (e.g when rendering).
+    pub(crate) fn is_alive(&self) -> bool {
+        self.handle.upgrade().is_some()
+    }
 }
 
+impl_handle!(OutputHandle => Output);
+
 impl Default for OutputHandle {
     fn default() -> Self {
         OutputHandle::new()
@@ -592,3 +1044,193 @@ impl PartialEq for OutputHandle {
 }
 
 impl Eq for OutputHandle {}
+
+impl PartialEq<OutputHandle> for Output {
+    fn eq(&self, other: &OutputHandle) -> bool {
+        self.output == other.output
+    }
+}
+
+impl PartialEq<Output> for OutputHandle {
+    fn eq(&self, other: &Output) -> bool {
+        self.output == other.output
+    }
+}
+
+/// The size of an output in layout coordinates, i.e with the transform and
+/// scale applied, like `wlr_output_effective_resolution`.
+fn effective_size((width, height): (c_int, c_int), scale: c_float, transform: Transform) -> Size {
+    // The odd transforms are the ones with a quarter turn.
+    let (width, height) = if transform as u32 % 2 == 1 {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    Size::new((width as c_float / scale) as c_int,
+              (height as c_float / scale) as c_int)
+}
+
+/// Scales each rectangle of `region` by `factor`, rounding outwards and
+/// expanding it by a pixel, then transforms it within a `width` by `height`
+/// output.
+fn damage_to_output(region: &PixmanRegion,
+                    factor: f32,
+                    transform: Transform,
+                    width: c_int,
+                    height: c_int)
+                    -> PixmanRegion {
+    let mut result = PixmanRegion::new();
+    for rect in region.rectangles() {
+        let x1 = (rect.origin.x as f32 * factor).floor() as c_int - 1;
+        let y1 = (rect.origin.y as f32 * factor).floor() as c_int - 1;
+        let x2 = ((rect.origin.x + rect.size.width) as f32 * factor).ceil() as c_int + 1;
+        let y2 = ((rect.origin.y + rect.size.height) as f32 * factor).ceil() as c_int + 1;
+        let rect = Area::new(Origin::new(x1, y1), Size::new(x2 - x1, y2 - y1))
+            .transform(transform, width, height);
+        result.rectangle(rect.origin.x,
+                         rect.origin.y,
+                         rect.size.width as _,
+                         rect.size.height as _);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_ramps_must_match_the_gamma_size() {
+        let ramp = [0u16; 256];
+        assert_eq!(check_gamma_ramps(256, &ramp, &ramp, &ramp), Ok(()));
+        assert_eq!(check_gamma_ramps(256, &ramp, &ramp[..255], &ramp),
+                   Err(GammaError::LengthMismatch { expected: 256,
+                                                    actual: 255 }));
+        assert_eq!(check_gamma_ramps(0, &[], &[], &[]), Err(GammaError::Unsupported));
+    }
+
+    /// Run the callback with an `Output` that isn't backed by a backend.
+    fn with_mock_output<F: FnOnce(&mut Output)>(f: F) {
+        let mut raw: Box<wlr_output> = Box::new(unsafe { ::std::mem::zeroed() });
+        // Holding on to the liveliness keeps the drop of the mock from
+        // freeing state it never had.
+        let liveliness = Rc::new(Cell::new(false));
+        let damage = unsafe { OutputDamage::from_ptr(ptr::null_mut()) };
+        let mut output = Output { liveliness: liveliness.clone(),
+                                  damage: ManuallyDrop::new(damage),
+                                  output: &mut *raw };
+        f(&mut output);
+    }
+
+    /// Link the modes into the (empty) mode list of the output, in order.
+    unsafe fn link_modes(output: &mut Output, modes: &mut [wlr_output_mode]) {
+        let head: *mut wl_list = &mut (*output.output).modes;
+        (*head).prev = head;
+        (*head).next = head;
+        for mode in modes {
+            let link: *mut wl_list = &mut mode.link;
+            (*link).prev = (*head).prev;
+            (*link).next = head;
+            (*(*head).prev).next = link;
+            (*head).prev = link;
+        }
+    }
+
+    fn mode(width: i32, height: i32, flags: u32) -> wlr_output_mode {
+        let mut mode: wlr_output_mode = unsafe { ::std::mem::zeroed() };
+        mode.width = width;
+        mode.height = height;
+        mode.flags = flags;
+        mode
+    }
+
+    #[test]
+    fn preferred_mode_need_not_be_the_first_mode() {
+        // WL_OUTPUT_MODE_CURRENT and WL_OUTPUT_MODE_PREFERRED.
+        let mut modes = [mode(1024, 768, 0x1), mode(1920, 1080, 0x2), mode(1280, 720, 0)];
+        with_mock_output(|output| {
+            unsafe { link_modes(output, &mut modes) }
+            assert_eq!(output.modes().len(), 3);
+            let preferred = output.preferred_mode().expect("No preferred mode");
+            assert_eq!(preferred.dimensions(), (1920, 1080));
+        });
+    }
+
+    #[test]
+    fn no_preferred_mode_without_the_flag() {
+        let mut modes = [mode(1024, 768, 0x1), mode(1280, 720, 0)];
+        with_mock_output(|output| {
+            unsafe { link_modes(output, &mut modes) }
+            assert!(output.preferred_mode().is_none());
+            unsafe { link_modes(output, &mut []) }
+            assert!(output.preferred_mode().is_none());
+        });
+    }
+
+    #[test]
+    fn quarter_turns_swap_the_effective_size() {
+        use self::wl_output_transform::*;
+        for &transform in &[WL_OUTPUT_TRANSFORM_NORMAL,
+                            WL_OUTPUT_TRANSFORM_180,
+                            WL_OUTPUT_TRANSFORM_FLIPPED,
+                            WL_OUTPUT_TRANSFORM_FLIPPED_180] {
+            assert_eq!(effective_size((1920, 1080), 1.0, transform), Size::new(1920, 1080));
+        }
+        for &transform in &[WL_OUTPUT_TRANSFORM_90,
+                            WL_OUTPUT_TRANSFORM_270,
+                            WL_OUTPUT_TRANSFORM_FLIPPED_90,
+                            WL_OUTPUT_TRANSFORM_FLIPPED_270] {
+            assert_eq!(effective_size((1920, 1080), 1.0, transform), Size::new(1080, 1920));
+        }
+        assert_eq!(effective_size((1920, 1080), 2.0, WL_OUTPUT_TRANSFORM_90),
+                   Size::new(540, 960));
+    }
+
+    fn area(x: c_int, y: c_int, width: c_int, height: c_int) -> Area {
+        Area::new(Origin::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn fractional_scale_rounds_damage_outwards() {
+        use self::wl_output_transform::*;
+        let damage = PixmanRegion::from_area(area(0, 0, 10, 10));
+        let output = damage_to_output(&damage, 1.5, WL_OUTPUT_TRANSFORM_NORMAL, 100, 100);
+        // 10 * 1.5 = 15 pixels, plus a pixel on every side.
+        assert_eq!(output.rectangles(), vec![area(-1, -1, 17, 17)]);
+        assert!(output.contains_point(0, 0));
+        assert!(output.contains_point(14, 14));
+        assert!(output.contains_point(15, 15));
+
+        let damage = PixmanRegion::from_area(area(1, 1, 10, 10));
+        let output = damage_to_output(&damage, 1.5, WL_OUTPUT_TRANSFORM_NORMAL, 100, 100);
+        // 1.5 rounds down to 1, 16.5 rounds up to 17.
+        assert_eq!(output.rectangles(), vec![area(0, 0, 18, 18)]);
+    }
+
+    #[test]
+    fn integer_scale_only_expands_damage() {
+        use self::wl_output_transform::*;
+        let damage = PixmanRegion::from_area(area(5, 5, 10, 10));
+        let output = damage_to_output(&damage, 2.0, WL_OUTPUT_TRANSFORM_NORMAL, 100, 100);
+        assert_eq!(output.rectangles(), vec![area(9, 9, 22, 22)]);
+        let output = damage_to_output(&damage, 1.0, WL_OUTPUT_TRANSFORM_NORMAL, 100, 100);
+        assert_eq!(output.rectangles(), vec![area(4, 4, 12, 12)]);
+    }
+
+    #[test]
+    fn damage_follows_the_output_transform() {
+        use self::wl_output_transform::*;
+        let damage = PixmanRegion::from_area(area(11, 21, 8, 18));
+        let output = damage_to_output(&damage, 1.0, WL_OUTPUT_TRANSFORM_180, 100, 50);
+        // The expanded rectangle is (10, 20) 10x20, mirrored on both axes.
+        assert_eq!(output.rectangles(), vec![area(80, 10, 10, 20)]);
+
+        // Quarter turns swap the width and height of the damage.
+        for &transform in &[WL_OUTPUT_TRANSFORM_90, WL_OUTPUT_TRANSFORM_270] {
+            let output = damage_to_output(&damage, 1.0, transform, 100, 50);
+            let rects = output.rectangles();
+            assert_eq!(rects.len(), 1);
+            assert_eq!(rects[0].size, Size::new(20, 10));
+        }
+    }
+}
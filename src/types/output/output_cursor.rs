@@ -36,6 +36,10 @@ impl OutputCursor {
         }
     }
 
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_output_cursor {
+        self.cursor
+    }
+
     /// Sets the hardware cursor's image.
     pub fn set_image(&mut self, image: &Image) -> bool {
         unsafe {
@@ -4,10 +4,13 @@ use wlroots_sys::{timespec, wlr_output, wlr_output_damage, wlr_output_damage_add
                   wlr_output_damage_add_box, wlr_output_damage_add_whole,
                   wlr_output_damage_create, wlr_output_damage_destroy,
                   wlr_output_damage_make_current, wlr_output_damage_swap_buffers,
-                  pixman_region32_fini, pixman_region32_init, pixman_region32_t,
-                  pixman_region32_union_rect};
+                  pixman_region32_clear, pixman_region32_contains_point, pixman_region32_copy,
+                  pixman_region32_fini, pixman_region32_init,
+                  pixman_region32_intersect, pixman_region32_not_empty,
+                  pixman_region32_rectangles, pixman_region32_subtract, pixman_region32_t,
+                  pixman_region32_translate, pixman_region32_union, pixman_region32_union_rect};
 
-use Area;
+use {Area, HandleErr, HandleResult, Origin, Size};
 
 /// A pixman region, used for damage tracking.
 #[derive(Debug)]
@@ -27,12 +30,104 @@ impl PixmanRegion {
         }
     }
 
+    /// Make a new pixman region with a copy of the contents of `region`.
+    ///
+    /// Used to take ownership of regions that are owned by wlroots, since
+    /// dropping a shallow copy of them would free their contents.
+    pub(crate) unsafe fn from_raw_copy(region: *const pixman_region32_t) -> Self {
+        let mut result = PixmanRegion::new();
+        pixman_region32_copy(&mut result.region, region as *mut _);
+        result
+    }
+
+    /// Make a new pixman region covering the given area.
+    pub fn from_area(area: Area) -> Self {
+        let mut region = PixmanRegion::new();
+        region.rectangle(area.origin.x,
+                         area.origin.y,
+                         area.size.width as c_uint,
+                         area.size.height as c_uint);
+        region
+    }
+
+    /// Adds the rectangle to this region.
     pub fn rectangle(&mut self, x: c_int, y: c_int, width: c_uint, height: c_uint) {
         unsafe {
             let region_ptr = &mut self.region as *mut _;
             pixman_region32_union_rect(region_ptr, region_ptr, x, y, width, height);
         }
     }
+
+    /// Makes this region the union of itself and `other`.
+    pub fn union(&mut self, other: &PixmanRegion) {
+        unsafe {
+            let region_ptr = &mut self.region as *mut _;
+            pixman_region32_union(region_ptr, region_ptr, other.as_ptr());
+        }
+    }
+
+    /// Makes this region the intersection of itself and `other`.
+    pub fn intersect(&mut self, other: &PixmanRegion) {
+        unsafe {
+            let region_ptr = &mut self.region as *mut _;
+            pixman_region32_intersect(region_ptr, region_ptr, other.as_ptr());
+        }
+    }
+
+    /// Removes the parts of `other` from this region.
+    pub fn subtract(&mut self, other: &PixmanRegion) {
+        unsafe {
+            let region_ptr = &mut self.region as *mut _;
+            pixman_region32_subtract(region_ptr, region_ptr, other.as_ptr());
+        }
+    }
+
+    /// Moves this region by the given offset.
+    pub fn translate(&mut self, dx: c_int, dy: c_int) {
+        unsafe { pixman_region32_translate(&mut self.region, dx, dy) }
+    }
+
+    /// Gets the rectangles that make up this region.
+    pub fn rectangles(&self) -> Vec<Area> {
+        unsafe {
+            let mut n_rects = 0;
+            let rects = pixman_region32_rectangles(self.as_ptr(), &mut n_rects);
+            (0..n_rects as isize).map(|i| {
+                                      let rect = *rects.offset(i);
+                                      Area::new(Origin::new(rect.x1, rect.y1),
+                                                Size::new(rect.x2 - rect.x1, rect.y2 - rect.y1))
+                                  })
+                                 .collect()
+        }
+    }
+
+    /// NOTE pixman takes mutable pointers even when it only reads from
+    /// a region, so this casts away the const.
+    unsafe fn as_ptr(&self) -> *mut pixman_region32_t {
+        &self.region as *const _ as *mut _
+    }
+
+    /// Determines if the point is inside the region.
+    pub fn contains_point(&self, x: c_int, y: c_int) -> bool {
+        unsafe { pixman_region32_contains_point(self.as_ptr(), x, y, ptr::null_mut()) != 0 }
+    }
+
+    /// Determines if the region covers no area at all.
+    pub fn is_empty(&self) -> bool {
+        unsafe { pixman_region32_not_empty(self.as_ptr()) == 0 }
+    }
+}
+
+impl Default for PixmanRegion {
+    fn default() -> Self {
+        PixmanRegion::new()
+    }
+}
+
+impl Clone for PixmanRegion {
+    fn clone(&self) -> Self {
+        unsafe { PixmanRegion::from_raw_copy(&self.region) }
+    }
 }
 
 impl Drop for PixmanRegion {
@@ -90,20 +185,22 @@ impl OutputDamage {
     }
 
     /// Makes the output rendering context current.
-    /// Returns `true` if `wlr_output_damage_swap_buffers` needs to be called.
     ///
-    ///The region of the output that needs to be repainted is added to `damage`.
-    pub fn make_current<'a, T>(&mut self, damage: T) -> bool
-        where T: Into<Option<&'a mut PixmanRegion>>
-    {
+    /// Returns `(needs_frame, damage)`, where `needs_frame` is `true` if the
+    /// output needs to be repainted (and `swap_buffers` needs to be called)
+    /// and `damage` is the region of the output that needs to be repainted.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` if the rendering context could not
+    /// be made current, e.g because the output is going away.
+    pub fn make_current(&mut self) -> HandleResult<(bool, PixmanRegion)> {
+        let mut damage = PixmanRegion::new();
         unsafe {
-            let mut res = false;
-            let damage = match damage.into() {
-                Some(region) => &mut region.region as *mut _,
-                None => ptr::null_mut()
-            };
-            wlr_output_damage_make_current(self.damage, &mut res, damage);
-            res
+            let mut needs_frame = false;
+            if !wlr_output_damage_make_current(self.damage, &mut needs_frame, &mut damage.region) {
+                wlr_log!(WLR_ERROR, "Could not make {:p} current", self.damage);
+                return Err(HandleErr::AlreadyDropped)
+            }
+            Ok((needs_frame, damage))
         }
     }
 
@@ -117,12 +214,14 @@ impl OutputDamage {
               U: Into<Option<&'a mut PixmanRegion>>
     {
         unsafe {
-            let when = when.into().map(|duration| {
+            let mut when = when.into().map(|duration| {
                                            timespec { tv_sec: duration.as_secs() as clock_t,
                                                       tv_nsec: duration.subsec_nanos() as clock_t }
                                        });
+            // NOTE The timespec has to outlive the call, so don't move it
+            // into a closure to take a pointer to it.
             let when_ptr =
-                when.map(|mut duration| &mut duration as *mut _).unwrap_or_else(|| ptr::null_mut());
+                when.as_mut().map(|when| when as *mut _).unwrap_or_else(|| ptr::null_mut());
             let damage = match damage.into() {
                 Some(region) => &mut region.region as *mut _,
                 None => ptr::null_mut()
@@ -132,21 +231,33 @@ impl OutputDamage {
     }
 
     /// Accumulates damage and schedules a `frame` event.
-    pub fn add(&mut self, damage: &mut PixmanRegion) {
+    pub fn add(&mut self, damage: &PixmanRegion) {
         unsafe {
-            wlr_output_damage_add(self.damage, &mut damage.region);
+            wlr_output_damage_add(self.damage, &damage.region as *const _ as *mut _);
         }
     }
 
     /// Damages the whole output and schedules a `frame` event.
+    ///
+    /// This forces the whole output to be redrawn on the next frame.
     pub fn add_whole(&mut self) {
         unsafe { wlr_output_damage_add_whole(self.damage) }
     }
 
     /// Accumulates damage from an `Area` and schedules a `frame` event.
-    pub fn add_area(&mut self, area: Area) {
+    pub fn add_box(&mut self, area: Area) {
         unsafe { wlr_output_damage_add_box(self.damage, &mut area.into()) }
     }
+
+    #[deprecated(note = "use `OutputDamage::add_box` instead")]
+    pub fn add_area(&mut self, area: Area) {
+        self.add_box(area)
+    }
+
+    /// Throws away the damage accumulated for the next frame.
+    pub fn clear(&mut self) {
+        unsafe { pixman_region32_clear(&mut (*self.damage).current) }
+    }
 }
 
 impl Drop for OutputDamage {
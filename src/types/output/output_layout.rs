@@ -1,7 +1,7 @@
 //! TODO Documentation
 
 use libc::{self, c_double, c_int};
-use std::{fmt, panic, ptr, cell::Cell, marker::PhantomData, rc::{Rc, Weak}};
+use std::{fmt, panic, ptr, cell::{Cell, RefCell}, marker::PhantomData, rc::{Rc, Weak}};
 
 use wayland_sys::server::{signal::wl_signal_add, WAYLAND_SERVER_HANDLE};
 use wlroots_sys::{wlr_output_effective_resolution, wlr_output_layout, wlr_output_layout_add,
@@ -12,11 +12,25 @@ use wlroots_sys::{wlr_output_effective_resolution, wlr_output_layout, wlr_output
                   wlr_output_layout_move, wlr_output_layout_output, wlr_output_layout_output_at,
                   wlr_output_layout_output_coords, wlr_output_layout_remove};
 
-use errors::{HandleErr, HandleResult};
+use errors::{HandleErr, HandleResult, OutputLayoutError};
 
-use {Area, Origin, Output, OutputHandle};
+use {Area, Origin, Output, OutputHandle, OutputState};
 use compositor::{compositor_handle, CompositorHandle};
 
+thread_local! {
+    /// Handles to all of the output layouts that exist, in the order they were
+    /// created.
+    static OUTPUT_LAYOUTS: RefCell<Vec<OutputLayoutHandle>> = RefCell::new(Vec::new());
+}
+
+/// Get handles to all of the output layouts that exist.
+///
+/// Layouts are added when they are created and removed when they are
+/// destroyed.
+pub(crate) fn output_layouts() -> Vec<OutputLayoutHandle> {
+    OUTPUT_LAYOUTS.with(|layouts| layouts.borrow().clone())
+}
+
 struct OutputLayoutState {
     /// A counter that will always have a strong count of 1.
     ///
@@ -43,12 +57,14 @@ pub trait OutputLayoutHandler {
                              OutputLayoutOutput<'this>) {
     }
 
-    /// Callback that's triggered when the layout changes.
-    fn on_change<'this>(&mut self,
-                        CompositorHandle,
-                        OutputLayoutHandle,
-                        OutputLayoutOutput<'this>) {
-    }
+    /// Callback that's triggered when an output is added to the output layout.
+    ///
+    /// Called right after `output_added`, with just a handle to the output.
+    fn on_add(&mut self, CompositorHandle, OutputLayoutHandle, OutputHandle) {}
+
+    /// Callback that's triggered when the layout changes, e.g. when an
+    /// output is added, moved or removed.
+    fn on_change(&mut self, CompositorHandle, OutputLayoutHandle) {}
 }
 
 wayland_listener!(OutputLayout, (*mut wlr_output_layout, Box<OutputLayoutHandler>), [
@@ -63,9 +79,11 @@ wayland_listener!(OutputLayout, (*mut wlr_output_layout, Box<OutputLayoutHandler
         let layout_output = OutputLayoutOutput{layout_output, phantom: PhantomData};
         let output_layout = OutputLayout::from_ptr(output_ptr);
 
-        manager.output_added(compositor,
+        let output = layout_output.output();
+        manager.output_added(compositor.clone(),
                              output_layout.weak_reference(),
                              layout_output);
+        manager.on_add(compositor, output_layout.weak_reference(), output);
 
         Box::into_raw(output_layout);
     };
@@ -87,20 +105,17 @@ wayland_listener!(OutputLayout, (*mut wlr_output_layout, Box<OutputLayoutHandler
 
         Box::into_raw(output_layout);
     };
-    change_listener => change_notify: |this: &mut OutputLayout, data: *mut libc::c_void,|
+    change_listener => change_notify: |this: &mut OutputLayout, _data: *mut libc::c_void,|
     unsafe {
         let (output_ptr, ref mut manager) = this.data;
         let compositor = match compositor_handle() {
             Some(handle) => handle,
             None => return
         };
-        let layout_output = data as *mut wlr_output_layout_output;
-        let layout_output = OutputLayoutOutput { layout_output, phantom: PhantomData};
+        // NOTE The data passed with the change event is the layout itself.
         let output_layout = OutputLayout::from_ptr(output_ptr);
 
-        manager.on_change(compositor,
-                          output_layout.weak_reference(),
-                          layout_output);
+        manager.on_change(compositor, output_layout.weak_reference());
 
         Box::into_raw(output_layout);
     };
@@ -151,7 +166,9 @@ impl OutputLayout {
             let state = Box::new(OutputLayoutState { counter,
                                                      layout: Box::into_raw(output_layout) });
             (*layout).data = Box::into_raw(state) as *mut libc::c_void;
-            OutputLayoutHandle { layout, handle }
+            let handle = OutputLayoutHandle { layout, handle };
+            OUTPUT_LAYOUTS.with(|layouts| layouts.borrow_mut().push(handle.clone()));
+            handle
         }
     }
 
@@ -170,21 +187,24 @@ impl OutputLayout {
 
     /// Get the outputs associated with this OutputLayout.
     ///
-    /// Also returns their absolute position within the layout.
-    pub fn outputs(&mut self) -> Vec<(OutputHandle, Origin)> {
+    /// Also returns their information about their place within the layout,
+    /// such as their absolute position.
+    pub fn outputs<'output>(&'output mut self) -> Vec<(OutputHandle, OutputLayoutOutput<'output>)> {
         unsafe {
             let mut result = vec![];
-            wl_list_for_each!((*self.data.0).outputs, link, (pos: wlr_output_layout_output) => {
-                result.push((OutputHandle::from_ptr((*pos).output),
-                             Origin::new((*pos).x, (*pos).y)))
-            });
+            wl_list_for_each!((*self.data.0).outputs, link,
+                              (layout_output: wlr_output_layout_output) => {
+                                  result.push((OutputHandle::from_ptr((*layout_output).output),
+                                               OutputLayoutOutput { layout_output,
+                                                                    phantom: PhantomData }))
+                              });
             result
         }
     }
 
     /// Get the Outputs in the OutputLayout coupled with their output information.
     ///
-    /// For a version that isn't bound by lifetimes, see `outputs`.
+    /// To also get a handle to each output, see `outputs`.
     pub fn outputs_layouts<'output>(&'output mut self) -> Vec<OutputLayoutOutput<'output>> {
         unsafe {
             let mut result = vec![];
@@ -199,28 +219,51 @@ impl OutputLayout {
     }
 
     /// Adds an output to the layout at the given coordinates.
+    ///
+    /// The output's `layout_space_pos` will reflect the new position.
     pub fn add(&mut self, output: &mut Output, origin: Origin) {
         let (x, y) = (origin.x, origin.y);
-        unsafe { wlr_output_layout_add(self.data.0, output.as_ptr(), x, y) }
+        unsafe {
+            self.claim_output(output);
+            wlr_output_layout_add(self.data.0, output.as_ptr(), x, y);
+            output.set_auto_configured(false);
+            wlr_log!(WLR_DEBUG, "Added {:?} to {:?} at {:?}", output, self, origin);
+        }
     }
 
     /// Adds an output to the layout, automatically positioning it with
     /// the others that are already there.
     pub fn add_auto(&mut self, output: &mut Output) {
         unsafe {
-            let layout_handle = self.weak_reference();
-            output.set_output_layout(Some(layout_handle));
+            self.claim_output(output);
             wlr_output_layout_add_auto(self.data.0, output.as_ptr());
+            output.set_auto_configured(true);
             wlr_log!(WLR_DEBUG, "Added {:?} to {:?}", output, self);
         }
     }
 
+    /// Records in the output's state that it is now in this layout, so that
+    /// it is removed from it when the output is destroyed.
+    ///
+    /// If the output was in another layout it is removed from that one first.
+    unsafe fn claim_output(&mut self, output: &mut Output) {
+        let layout_handle = self.weak_reference();
+        // NOTE This layout is already borrowed, so trying to remove the
+        // output from it through the handle would fail.
+        if output.layout() != Some(layout_handle.clone()) {
+            output.set_output_layout(Some(layout_handle));
+        }
+    }
+
     /// Moves the output to the given coordinates.
     ///
     /// If the output is not part of this layout this does nothing.
     pub fn move_output(&mut self, output: &mut Output, origin: Origin) {
         let (x, y) = (origin.x, origin.y);
-        unsafe { wlr_output_layout_move(self.data.0, output.as_ptr(), x, y) }
+        unsafe {
+            wlr_output_layout_move(self.data.0, output.as_ptr(), x, y);
+            output.set_auto_configured(false);
+        }
     }
 
     /// Get the closest point on this layout from the given point from the reference
@@ -229,12 +272,9 @@ impl OutputLayout {
     /// If reference is None, gets the closest point from the entire layout.
     ///
     /// Returns the closest point in the format (x, y)
-    pub fn closest_point<'this, O>(&mut self, reference: O, x: f64, y: f64) -> (f64, f64)
-        where O: Into<Option<&'this mut Output>>
-    {
+    pub fn closest_point(&self, reference: Option<&OutputHandle>, x: f64, y: f64) -> (f64, f64) {
         unsafe {
-            let output_ptr = reference.into()
-                                      .map(|output| output.as_ptr())
+            let output_ptr = reference.map(|output| output.as_ptr())
                                       .unwrap_or(ptr::null_mut());
             let (ref mut out_x, ref mut out_y) = (0.0, 0.0);
             wlr_output_layout_closest_point(self.data.0, output_ptr, x, y, out_x, out_y);
@@ -253,14 +293,19 @@ impl OutputLayout {
     /// Get the box of the layout for the given reference output.
     ///
     /// If `reference` is None, the box will be for the extents of the entire layout.
-    pub fn get_box<'this, O>(&mut self, reference: O) -> Area
-        where O: Into<Option<&'this mut Output>>
-    {
+    ///
+    /// An empty layout, or a reference output that isn't in the layout,
+    /// yields a zero-size `Area`.
+    pub fn get_box(&self, reference: Option<&OutputHandle>) -> Area {
         unsafe {
-            let output_ptr = reference.into()
-                                      .map(|output| output.as_ptr())
+            let output_ptr = reference.map(|output| output.as_ptr())
                                       .unwrap_or(ptr::null_mut());
-            Area::from_box(*wlr_output_layout_get_box(self.data.0, output_ptr))
+            let area = wlr_output_layout_get_box(self.data.0, output_ptr);
+            if area.is_null() {
+                Area::default()
+            } else {
+                Area::from_box(*area)
+            }
         }
     }
 
@@ -283,10 +328,26 @@ impl OutputLayout {
         unsafe { wlr_output_layout_intersects(self.data.0, output.as_ptr(), &area.into()) }
     }
 
-    /// Given x and y as pointers to global coordinates, adjusts them to local output
-    /// coordinates relative to the given reference output.
-    pub fn output_coords(&mut self, output: &mut Output, x: &mut f64, y: &mut f64) {
-        unsafe { wlr_output_layout_output_coords(self.data.0, output.as_ptr(), x, y) }
+    /// Get the offset of the output within the layout.
+    ///
+    /// Subtract this from layout (global) coordinates to get coordinates local
+    /// to the output.
+    ///
+    /// Returns `OutputLayoutError::OutputDropped` if the output has been
+    /// destroyed and `OutputLayoutError::NotInLayout` if it isn't part of this
+    /// layout.
+    pub fn output_coords(&self, output: &OutputHandle) -> Result<(f64, f64), OutputLayoutError> {
+        if !output.is_alive() {
+            return Err(OutputLayoutError::OutputDropped)
+        }
+        unsafe {
+            if wlr_output_layout_get(self.data.0, output.as_ptr()).is_null() {
+                return Err(OutputLayoutError::NotInLayout)
+            }
+            let (mut x, mut y) = (0.0, 0.0);
+            wlr_output_layout_output_coords(self.data.0, output.as_ptr(), &mut x, &mut y);
+            Ok((-x, -y))
+        }
     }
 
     /// Remove an output from this layout.
@@ -318,7 +379,7 @@ impl OutputLayout {
 
     /// Get the output at the given output layout coordinate location, if there
     /// is one there.
-    pub fn output_at(&mut self, lx: c_double, ly: c_double) -> Option<OutputHandle> {
+    pub fn output_at(&self, lx: c_double, ly: c_double) -> Option<OutputHandle> {
         unsafe {
             let output = wlr_output_layout_output_at(self.data.0, lx, ly);
             if output.is_null() {
@@ -353,6 +414,12 @@ impl Drop for OutputLayout {
                        1,
                        "OutputLayout had more than 1 reference count");
             (*layout_ptr).data = ptr::null_mut();
+            // NOTE The registry is already gone if this runs at thread exit.
+            OUTPUT_LAYOUTS.try_with(|layouts| {
+                                        layouts.borrow_mut()
+                                               .retain(|handle| handle.layout != layout_ptr)
+                                    })
+                          .ok();
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                           wl_list_remove,
                           &mut (*manager.output_add_listener()).link as *mut _ as _);
@@ -447,6 +514,8 @@ impl OutputLayoutHandle {
     }
 }
 
+impl_handle!(OutputLayoutHandle => Box<OutputLayout>);
+
 impl<'output> OutputLayoutOutput<'output> {
     /// Get a handle to the output that this structure describes.
     pub fn output(&self) -> OutputHandle {
@@ -458,6 +527,21 @@ impl<'output> OutputLayoutOutput<'output> {
         unsafe { ((*self.layout_output).x, (*self.layout_output).y) }
     }
 
+    /// Get the position of this output within the layout.
+    pub fn position(&self) -> Origin {
+        self.top_left_edge()
+    }
+
+    /// Determines if the output was placed automatically by the layout
+    /// (e.g with `OutputLayout::add_auto`) rather than at explicit
+    /// coordinates.
+    pub fn auto_configured(&self) -> bool {
+        unsafe {
+            let data = (*(*self.layout_output).output).data as *mut OutputState;
+            !data.is_null() && (*data).auto_configured
+        }
+    }
+
     /// Get the absolute top left edge coordinate of this output in the output
     /// layout.
     pub fn top_left_edge(&self) -> Origin {
@@ -1,11 +1,14 @@
 //! TODO Documentation
 
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
 
-use wlroots_sys::wlr_output_mode;
+use wlroots_sys::{wl_list, wlr_output_mode};
 
 use Output;
 
+/// Flag set on a mode that the output (e.g the monitor) prefers.
+const WL_OUTPUT_MODE_PREFERRED: u32 = 0x2;
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct OutputMode<'output> {
     output_mode: *mut wlr_output_mode,
@@ -29,6 +32,11 @@ impl<'output> OutputMode<'output> {
         unsafe { (*self.output_mode).flags }
     }
 
+    /// Determines if this is the mode the output prefers.
+    pub fn is_preferred(&self) -> bool {
+        self.flags() & WL_OUTPUT_MODE_PREFERRED != 0
+    }
+
     /// Gets the dimensions of this OutputMode.
     ///
     /// Returned value is (width, height)
@@ -36,8 +44,111 @@ impl<'output> OutputMode<'output> {
         unsafe { ((*self.output_mode).width, (*self.output_mode).height) }
     }
 
-    /// Get the refresh value of the output.
+    /// Gets the width of this OutputMode, in pixels.
+    pub fn width(&self) -> i32 {
+        unsafe { (*self.output_mode).width }
+    }
+
+    /// Gets the height of this OutputMode, in pixels.
+    pub fn height(&self) -> i32 {
+        unsafe { (*self.output_mode).height }
+    }
+
+    /// Get the refresh value of the output, in mHz.
+    ///
+    /// This is 0 if the refresh rate is unknown.
     pub fn refresh(&self) -> i32 {
         unsafe { (*self.output_mode).refresh }
     }
 }
+
+/// Formats the mode as e.g `1920x1080@60Hz` or `1920x1080@59.94Hz`.
+///
+/// The refresh rate is left out if it's unknown.
+impl<'output> fmt::Display for OutputMode<'output> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width(), self.height())?;
+        if self.refresh() != 0 {
+            write!(f, "@{}Hz", self.refresh() as f64 / 1000.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the modes of an `Output`.
+///
+/// Created with `Output::modes_iter`. This walks the list of modes lazily
+/// and yields nothing on backends that have no modes.
+#[derive(Debug)]
+pub struct OutputModes<'output> {
+    head: *mut wl_list,
+    pos: *mut wl_list,
+    phantom: PhantomData<&'output Output>
+}
+
+impl<'output> OutputModes<'output> {
+    /// NOTE `head` must be the `modes` list of an output that outlives `'output`.
+    pub(crate) unsafe fn new(head: *mut wl_list) -> Self {
+        OutputModes { head,
+                      pos: (*head).next,
+                      phantom: PhantomData }
+    }
+}
+
+impl<'output> Iterator for OutputModes<'output> {
+    type Item = OutputMode<'output>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.head {
+            return None
+        }
+        unsafe {
+            let mode = container_of!(self.pos, wlr_output_mode, link);
+            self.pos = (*self.pos).next;
+            Some(OutputMode::new(mode))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+
+    fn mode(width: i32, height: i32, refresh: i32) -> wlr_output_mode {
+        let mut mode: wlr_output_mode = unsafe { mem::zeroed() };
+        mode.width = width;
+        mode.height = height;
+        mode.refresh = refresh;
+        mode
+    }
+
+    fn display(mut mode: wlr_output_mode) -> String {
+        unsafe { OutputMode::new(&mut mode).to_string() }
+    }
+
+    #[test]
+    fn display_converts_the_refresh_rate_to_hz() {
+        assert_eq!(display(mode(1920, 1080, 60000)), "1920x1080@60Hz");
+        assert_eq!(display(mode(2560, 1440, 144000)), "2560x1440@144Hz");
+        assert_eq!(display(mode(1920, 1080, 59940)), "1920x1080@59.94Hz");
+    }
+
+    #[test]
+    fn display_leaves_out_an_unknown_refresh_rate() {
+        assert_eq!(display(mode(1024, 768, 0)), "1024x768");
+    }
+
+    #[test]
+    fn accessors() {
+        let mut raw = mode(1920, 1080, 60000);
+        raw.flags = WL_OUTPUT_MODE_PREFERRED;
+        let output_mode = unsafe { OutputMode::new(&mut raw) };
+        assert_eq!(output_mode.width(), 1920);
+        assert_eq!(output_mode.height(), 1080);
+        assert_eq!(output_mode.dimensions(), (1920, 1080));
+        assert_eq!(output_mode.refresh(), 60000);
+        assert!(output_mode.is_preferred());
+    }
+}
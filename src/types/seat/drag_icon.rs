@@ -99,7 +99,6 @@ impl Hash for DragIconHandle {
 }
 
 impl DragIconHandle {
-    #[allow(unused)]
     pub(crate) unsafe fn from_ptr(drag_icon: *mut wlr_drag_icon) -> Self {
         if drag_icon.is_null() {
             panic!("drag icon was null");
@@ -159,3 +158,5 @@ impl DragIconHandle {
         self.drag_icon
     }
 }
+
+impl_handle!(DragIconHandle => DragIcon);
@@ -0,0 +1,309 @@
+//! Momentum ("kinetic") scrolling for finger driven axis events.
+//!
+//! Touchpads report scrolling with `WLR_AXIS_SOURCE_FINGER` and send a zero
+//! delta event (an axis stop) once the fingers are lifted. `KineticScroll`
+//! watches those events and, after the stop, keeps producing scroll deltas
+//! that slowly decay so that the content keeps moving for a bit.
+//!
+//! Feed it every axis event with `handle_axis`, then call `tick` (e.g. once
+//! per frame) while `is_active` returns `true`. `KineticScrollTimer` does the
+//! latter from a timer on the compositor's event loop.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wlroots_sys::{wlr_axis_orientation, wlr_axis_source};
+
+use Compositor;
+use event_loop::TimerHandle;
+use events::pointer_events::AxisEvent;
+use utils::ToMS;
+
+/// Fraction of the velocity that is kept after each millisecond of coasting.
+const DEFAULT_DECELERATION: f64 = 0.995;
+
+/// Velocity (in axis units per millisecond) under which scrolling stops.
+const DEFAULT_MIN_VELOCITY: f64 = 0.01;
+
+/// How far apart two axis events can be and still count as the same gesture.
+const MAX_EVENT_GAP_MS: u32 = 100;
+
+/// Tracks the velocity of finger scrolling and keeps scrolling after the
+/// fingers are lifted.
+///
+/// This only computes deltas, it's up to the compositor to send them to
+/// clients (e.g with `Seat::pointer_notify_axis`).
+#[derive(Debug, Clone)]
+pub struct KineticScroll {
+    /// Velocity in (horizontal, vertical) axis units per millisecond.
+    velocity: (f64, f64),
+    /// The velocity before the events with the last timestamp came in.
+    prev_velocity: (f64, f64),
+    /// Sum of the deltas of the events with the last timestamp.
+    ///
+    /// Events for both axes can arrive in the same frame, and some devices
+    /// split a frame's motion over several events.
+    frame_delta: (f64, f64),
+    /// Milliseconds between the last timestamp and the one before it, if
+    /// they were part of the same gesture.
+    frame_elapsed: Option<u32>,
+    /// Timestamp of the last finger axis event.
+    last_time: Option<u32>,
+    /// Whether the fingers have been lifted and we are producing deltas.
+    coasting: bool,
+    deceleration: f64,
+    min_velocity: f64
+}
+
+impl Default for KineticScroll {
+    fn default() -> Self {
+        KineticScroll { velocity: (0.0, 0.0),
+                        prev_velocity: (0.0, 0.0),
+                        frame_delta: (0.0, 0.0),
+                        frame_elapsed: None,
+                        last_time: None,
+                        coasting: false,
+                        deceleration: DEFAULT_DECELERATION,
+                        min_velocity: DEFAULT_MIN_VELOCITY }
+    }
+}
+
+impl KineticScroll {
+    /// Create a kinetic scroll state that isn't scrolling, with the default
+    /// deceleration and minimum velocity.
+    pub fn new() -> Self {
+        KineticScroll::default()
+    }
+
+    /// Set the fraction of the velocity kept after each millisecond.
+    ///
+    /// Values closer to `1.0` make the scrolling coast for longer.
+    pub fn set_deceleration(&mut self, deceleration: f64) {
+        self.deceleration = deceleration.max(0.0).min(1.0);
+    }
+
+    /// Set the velocity (in axis units per millisecond) under which coasting stops.
+    pub fn set_min_velocity(&mut self, min_velocity: f64) {
+        self.min_velocity = min_velocity.abs();
+    }
+
+    /// Whether there are still decaying deltas to be produced by `tick`.
+    pub fn is_active(&self) -> bool {
+        self.coasting
+    }
+
+    /// Stop any scrolling in progress.
+    pub fn cancel(&mut self) {
+        self.velocity = (0.0, 0.0);
+        self.frame_elapsed = None;
+        self.last_time = None;
+        self.coasting = false;
+    }
+
+    /// Update the scroll state with a new axis event.
+    ///
+    /// Only finger events contribute to the momentum. Any other source
+    /// (e.g. a mouse wheel) cancels scrolling that is in progress.
+    pub fn handle_axis(&mut self, event: &AxisEvent) {
+        self.update(event.source(),
+                    event.orientation(),
+                    event.delta(),
+                    event.time_msec())
+    }
+
+    fn update(&mut self,
+              source: wlr_axis_source,
+              orientation: wlr_axis_orientation,
+              delta: f64,
+              time_msec: u32) {
+        use wlr_axis_orientation::*;
+        if source != wlr_axis_source::WLR_AXIS_SOURCE_FINGER {
+            self.cancel();
+            return
+        }
+        if delta == 0.0 {
+            // Axis stop, the fingers have been lifted.
+            self.coasting = self.velocity.0.abs() >= self.min_velocity ||
+                            self.velocity.1.abs() >= self.min_velocity;
+            self.last_time = None;
+            return
+        }
+        self.coasting = false;
+        match self.last_time {
+            Some(last) if time_msec == last => {}
+            Some(last) if time_msec > last && time_msec - last <= MAX_EVENT_GAP_MS => {
+                self.prev_velocity = self.velocity;
+                self.frame_delta = (0.0, 0.0);
+                self.frame_elapsed = Some(time_msec - last);
+            }
+            _ => {
+                // Start of a new gesture, forget about the old one.
+                self.velocity = (0.0, 0.0);
+                self.frame_elapsed = None;
+            }
+        }
+        self.last_time = Some(time_msec);
+        let elapsed = match self.frame_elapsed {
+            Some(elapsed) => elapsed as f64,
+            // Nothing to measure the first events of a gesture against.
+            None => return
+        };
+        // Average with the previous velocity to smooth out jittery events.
+        match orientation {
+            WLR_AXIS_ORIENTATION_HORIZONTAL => {
+                self.frame_delta.0 += delta;
+                self.velocity.0 = (self.prev_velocity.0 + self.frame_delta.0 / elapsed) / 2.0
+            }
+            WLR_AXIS_ORIENTATION_VERTICAL => {
+                self.frame_delta.1 += delta;
+                self.velocity.1 = (self.prev_velocity.1 + self.frame_delta.1 / elapsed) / 2.0
+            }
+        }
+    }
+
+    /// Advance the scroll by `elapsed` time.
+    ///
+    /// Returns the (horizontal, vertical) delta to scroll by, or `None` once
+    /// the scrolling has settled.
+    pub fn tick(&mut self, elapsed: Duration) -> Option<(f64, f64)> {
+        if !self.coasting {
+            return None
+        }
+        let elapsed = elapsed.to_ms() as f64;
+        let delta = (self.velocity.0 * elapsed, self.velocity.1 * elapsed);
+        let decay = self.deceleration.powf(elapsed);
+        self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
+        if self.velocity.0.abs() < self.min_velocity && self.velocity.1.abs() < self.min_velocity {
+            self.cancel();
+        }
+        Some(delta)
+    }
+}
+
+/// Produces the deltas of a `KineticScroll` from a timer on the compositor's
+/// event loop.
+///
+/// The timer is removed when this is dropped.
+pub struct KineticScrollTimer {
+    scroll: Rc<RefCell<KineticScroll>>,
+    timer: TimerHandle,
+    interval: Duration
+}
+
+impl KineticScrollTimer {
+    /// Call `on_scroll` with the (horizontal, vertical) delta every
+    /// `interval` once the fingers are lifted, until the scrolling settles.
+    pub fn new<F>(compositor: &mut Compositor, interval: Duration, mut on_scroll: F) -> Self
+        where F: FnMut(f64, f64) + 'static
+    {
+        let scroll = Rc::new(RefCell::new(KineticScroll::new()));
+        // NOTE The timer can only be armed again through a handle to it,
+        // which doesn't exist yet when the callback is created.
+        let timer_slot: Rc<RefCell<Option<TimerHandle>>> = Rc::new(RefCell::new(None));
+        let (callback_scroll, callback_slot) = (scroll.clone(), timer_slot.clone());
+        let timer = compositor.add_timer(interval, move || {
+            let delta = callback_scroll.borrow_mut().tick(interval);
+            if let Some((delta_x, delta_y)) = delta {
+                on_scroll(delta_x, delta_y);
+                if let Some(ref timer) = *callback_slot.borrow() {
+                    timer.reset(interval).ok();
+                }
+            }
+        });
+        *timer_slot.borrow_mut() = Some(timer.clone());
+        KineticScrollTimer { scroll,
+                             timer: timer.remove_on_drop(true),
+                             interval }
+    }
+
+    /// Update the scroll state with a new axis event, starting the timer
+    /// once the fingers are lifted.
+    pub fn handle_axis(&self, event: &AxisEvent) {
+        let mut scroll = self.scroll.borrow_mut();
+        let was_active = scroll.is_active();
+        scroll.handle_axis(event);
+        if !was_active && scroll.is_active() {
+            self.timer.reset(self.interval).ok();
+        }
+    }
+
+    /// Whether there are still decaying deltas to be produced.
+    pub fn is_active(&self) -> bool {
+        self.scroll.borrow().is_active()
+    }
+
+    /// Stop any scrolling in progress.
+    pub fn cancel(&self) {
+        self.scroll.borrow_mut().cancel()
+    }
+
+    /// See `KineticScroll::set_deceleration`.
+    pub fn set_deceleration(&self, deceleration: f64) {
+        self.scroll.borrow_mut().set_deceleration(deceleration)
+    }
+
+    /// See `KineticScroll::set_min_velocity`.
+    pub fn set_min_velocity(&self, min_velocity: f64) {
+        self.scroll.borrow_mut().set_min_velocity(min_velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wlroots_sys::{wlr_axis_orientation::*, wlr_axis_source::*};
+
+    use super::*;
+
+    /// Scroll vertically by `delta` for each of the timestamps.
+    fn swipe(scroll: &mut KineticScroll, events: &[(u32, f64)]) {
+        for &(time_msec, delta) in events {
+            scroll.update(WLR_AXIS_SOURCE_FINGER, WLR_AXIS_ORIENTATION_VERTICAL, delta, time_msec)
+        }
+    }
+
+    #[test]
+    fn same_timestamp_deltas_accumulate() {
+        let mut split = KineticScroll::new();
+        swipe(&mut split, &[(0, 10.0), (10, 10.0), (10, 10.0)]);
+        let mut whole = KineticScroll::new();
+        swipe(&mut whole, &[(0, 10.0), (10, 20.0)]);
+        assert_eq!(split.velocity, whole.velocity);
+        assert_eq!(split.velocity, (0.0, 1.0));
+    }
+
+    #[test]
+    fn deltas_decay_after_stop() {
+        let mut scroll = KineticScroll::new();
+        swipe(&mut scroll, &[(0, 10.0), (10, 20.0)]);
+        assert!(!scroll.is_active());
+        assert_eq!(scroll.tick(Duration::from_millis(10)), None);
+        // The fingers are lifted.
+        swipe(&mut scroll, &[(20, 0.0)]);
+        assert!(scroll.is_active());
+        let mut ticks = 0;
+        while let Some((delta_x, delta_y)) = scroll.tick(Duration::from_millis(10)) {
+            let expected = 10.0 * DEFAULT_DECELERATION.powf(10.0 * ticks as f64);
+            assert_eq!(delta_x, 0.0);
+            assert!((delta_y - expected).abs() < 1e-9,
+                    "tick {}: {} != {}",
+                    ticks,
+                    delta_y,
+                    expected);
+            ticks += 1;
+        }
+        // 0.995^(10 * 92) is the first velocity under 0.01.
+        assert_eq!(ticks, 92);
+        assert!(!scroll.is_active());
+    }
+
+    #[test]
+    fn other_sources_cancel() {
+        let mut scroll = KineticScroll::new();
+        swipe(&mut scroll, &[(0, 10.0), (10, 20.0), (20, 0.0)]);
+        assert!(scroll.is_active());
+        scroll.update(WLR_AXIS_SOURCE_WHEEL, WLR_AXIS_ORIENTATION_VERTICAL, 15.0, 30);
+        assert!(!scroll.is_active());
+        assert_eq!(scroll.tick(Duration::from_millis(10)), None);
+    }
+}
@@ -2,10 +2,12 @@ mod drag_icon;
 mod seat_client;
 mod seat;
 mod grab;
+mod kinetic_scroll;
 mod touch_point;
 
 pub use self::drag_icon::*;
 pub use self::grab::*;
+pub use self::kinetic_scroll::{KineticScroll, KineticScrollTimer};
 pub use self::seat::*;
 pub use self::seat_client::*;
 pub use self::touch_point::*;
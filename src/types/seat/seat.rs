@@ -17,23 +17,30 @@ use wlroots_sys::{wlr_axis_orientation, wlr_seat, wlr_seat_create, wlr_seat_dest
                   wlr_seat_pointer_clear_focus, wlr_seat_pointer_end_grab, wlr_seat_pointer_enter,
                   wlr_seat_pointer_has_grab, wlr_seat_pointer_notify_axis,
                   wlr_seat_pointer_notify_button, wlr_seat_pointer_notify_enter,
-                  wlr_seat_pointer_notify_motion, wlr_seat_pointer_request_set_cursor_event,
+                  wlr_seat_pointer_notify_frame, wlr_seat_pointer_notify_motion,
+                  wlr_seat_pointer_request_set_cursor_event,
                   wlr_seat_pointer_send_axis, wlr_seat_pointer_send_button,
                   wlr_seat_pointer_send_motion, wlr_seat_pointer_start_grab,
                   wlr_seat_pointer_surface_has_focus, wlr_seat_set_capabilities,
-                  wlr_seat_set_keyboard, wlr_seat_set_name, wlr_seat_touch_end_grab,
+                  wlr_seat_set_keyboard, wlr_seat_set_name, wlr_seat_set_primary_selection,
+                  wlr_seat_set_selection, wlr_seat_start_pointer_drag, wlr_seat_touch_end_grab,
                   wlr_seat_touch_get_point, wlr_seat_touch_has_grab, wlr_seat_touch_notify_down,
                   wlr_seat_touch_notify_motion, wlr_seat_touch_notify_up,
                   wlr_seat_touch_num_points, wlr_seat_touch_point_clear_focus,
                   wlr_seat_touch_point_focus, wlr_seat_touch_send_down,
                   wlr_seat_touch_send_motion, wlr_seat_touch_send_up, wlr_seat_touch_start_grab,
-                  wlr_axis_source, wlr_drag_icon};
+                  wlr_axis_source, wlr_drag, wlr_drag_icon};
 pub use wlroots_sys::wayland_server::protocol::wl_seat::Capability;
 use xkbcommon::xkb::Keycode;
 
-use {wlr_keyboard_modifiers, InputDevice, KeyboardGrab, KeyboardHandle, PointerGrab, Surface,
-     TouchGrab, TouchId, TouchPoint, events::seat_events::SetCursorEvent, SurfaceHandler, DragIconHandle, DragIcon, DragIconHandler};
+use {wlr_keyboard_modifiers, DataSource, Drag, DragIcon, DragIconHandle, DragIconHandler,
+     InputDevice, KeyboardGrab, KeyboardHandle, PointerGrab, PrimarySelectionSource, Surface,
+     SurfaceHandle, SurfaceHandler, TouchGrab, TouchId, TouchPoint,
+     events::seat_events::{self, SetCursorEvent}};
 use manager::DragIconListener;
+#[cfg(feature = "text-input")]
+use manager::update_text_input_focus;
+use extensions::idle::notify_seat_activity;
 use compositor::{compositor_handle, Compositor, CompositorHandle};
 use errors::{HandleErr, HandleResult};
 use utils::{c_to_rust_string, safe_as_cstring};
@@ -82,10 +89,43 @@ pub trait SeatHandler {
     /// The seat was provided with a selection by the client.
     fn received_selection(&mut self, CompositorHandle, SeatHandle) {}
 
+    /// The selection (i.e the clipboard) of the seat changed, either because
+    /// a client set it or because of `Seat::set_selection`.
+    ///
+    /// `source` is the new selection, or `None` if it was cleared.
+    ///
+    /// Clients can only set a selection when the data device manager is
+    /// enabled with `CompositorBuilder::data_device`.
+    fn on_set_selection(&mut self, CompositorHandle, SeatHandle, Option<DataSource>) {}
+
     /// The seat was provided with a selection from the primary buffer
     /// by the client.
     fn primary_selection(&mut self, CompositorHandle, SeatHandle) {}
 
+    /// The primary selection (used for middle-click paste) of the seat
+    /// changed, either because a client set it or because of
+    /// `Seat::set_primary_selection`.
+    ///
+    /// `source` is the new primary selection, or `None` if it was cleared.
+    fn on_set_primary_selection(&mut self,
+                                CompositorHandle,
+                                SeatHandle,
+                                Option<PrimarySelectionSource>) {
+    }
+
+    /// A client wants to start a drag-and-drop operation.
+    ///
+    /// The drag is only started if it's accepted with `Seat::start_pointer_drag`,
+    /// typically after checking that the serial matches a recent button press.
+    fn on_request_start_drag(&mut self,
+                             CompositorHandle,
+                             SeatHandle,
+                             &seat_events::RequestStartDragEvent) {
+    }
+
+    /// A drag-and-drop operation has started on this seat.
+    fn on_start_drag(&mut self, CompositorHandle, SeatHandle, &Drag) {}
+
     /// The seat is being destroyed.
     fn destroy(&mut self, CompositorHandle, SeatHandle) {}
 
@@ -226,7 +266,10 @@ wayland_listener!(Seat, (*mut wlr_seat, Box<SeatHandler>), [
         };
         let seat = Seat::from_ptr(seat_ptr);
 
-        handler.received_selection(compositor, seat.weak_reference());
+        handler.received_selection(compositor.clone(), seat.weak_reference());
+        let source = (*seat_ptr).selection_source;
+        let source = if source.is_null() { None } else { Some(DataSource::from_ptr(source)) };
+        handler.on_set_selection(compositor, seat.weak_reference(), source);
 
         Box::into_raw(seat);
     };
@@ -240,7 +283,14 @@ wayland_listener!(Seat, (*mut wlr_seat, Box<SeatHandler>), [
         };
         let seat = Seat::from_ptr(seat_ptr);
 
-        handler.primary_selection(compositor, seat.weak_reference());
+        handler.primary_selection(compositor.clone(), seat.weak_reference());
+        let source = (*seat_ptr).primary_selection_source;
+        let source = if source.is_null() {
+            None
+        } else {
+            Some(PrimarySelectionSource::from_ptr(source))
+        };
+        handler.on_set_primary_selection(compositor, seat.weak_reference(), source);
 
         Box::into_raw(seat);
     };
@@ -276,6 +326,35 @@ wayland_listener!(Seat, (*mut wlr_seat, Box<SeatHandler>), [
         }
         wlr_log!(WLR_DEBUG, "New drag icon request {:p}", data);
     };
+    request_start_drag_listener => request_start_drag_notify: |this: &mut Seat,
+    event: *mut libc::c_void,|
+    unsafe {
+        let (seat_ptr, ref mut handler) = this.data;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let event = seat_events::RequestStartDragEvent::from_ptr(event as _);
+        let seat = Seat::from_ptr(seat_ptr);
+
+        handler.on_request_start_drag(compositor, seat.weak_reference(), &event);
+
+        Box::into_raw(seat);
+    };
+    start_drag_listener => start_drag_notify: |this: &mut Seat, event: *mut libc::c_void,|
+    unsafe {
+        let (seat_ptr, ref mut handler) = this.data;
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        let drag = Drag::from_ptr(event as *mut wlr_drag);
+        let seat = Seat::from_ptr(seat_ptr);
+
+        handler.on_start_drag(compositor, seat.weak_reference(), &drag);
+
+        Box::into_raw(seat);
+    };
     destroy_listener => destroy_notify: |this: &mut Seat, _event: *mut libc::c_void,|
     unsafe {
         let (seat_ptr, ref mut handler) = this.data;
@@ -326,6 +405,10 @@ impl Seat {
                           res.primary_selection_listener() as *mut _ as _);
             wl_signal_add(&mut (*seat).events.new_drag_icon as *mut _ as _,
                           res.new_drag_icon_listener() as *mut _ as _);
+            wl_signal_add(&mut (*seat).events.request_start_drag as *mut _ as _,
+                          res.request_start_drag_listener() as *mut _ as _);
+            wl_signal_add(&mut (*seat).events.start_drag as *mut _ as _,
+                          res.start_drag_listener() as *mut _ as _);
             wl_signal_add(&mut (*seat).events.destroy as *mut _ as _,
                           res.destroy_listener() as *mut _ as _);
             let counter = Rc::new(Cell::new(false));
@@ -476,11 +559,45 @@ impl Seat {
         unsafe { wlr_seat_pointer_notify_enter(self.data.0, surface.as_ptr(), sx, sy) }
     }
 
+    /// Give pointer focus to the given surface, or clear the pointer focus
+    /// if `None` is passed.
+    ///
+    /// Pass surface-local coordinates where the enter occurred. Events sent with
+    /// `pointer_notify_motion`, `pointer_notify_button` and `pointer_notify_axis`
+    /// afterwards are delivered to this surface. Defers to any pointer grabs.
+    ///
+    /// Unlike `pointer_notify_enter` this does not borrow the surface, so it can
+    /// be called from its callbacks.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` if the surface has been destroyed,
+    /// in which case the focus is left unchanged.
+    pub fn set_pointer_focus(&mut self,
+                             surface: Option<&SurfaceHandle>,
+                             sx: f64,
+                             sy: f64)
+                             -> HandleResult<()> {
+        let surface = match surface {
+            Some(surface) => surface,
+            None => {
+                self.pointer_clear_focus();
+                return Ok(())
+            }
+        };
+        if !surface.is_alive() {
+            return Err(HandleErr::AlreadyDropped)
+        }
+        unsafe { wlr_seat_pointer_notify_enter(self.data.0, surface.as_ptr(), sx, sy) }
+        Ok(())
+    }
+
     /// Notify the seat of motion over the given surface.
     ///
     /// Pass surface-local coordinates where the pointer motion occurred.
     pub fn pointer_notify_motion(&self, time: Duration, sx: f64, sy: f64) {
-        unsafe { wlr_seat_pointer_notify_motion(self.data.0, time.to_ms(), sx, sy) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_pointer_notify_motion(self.data.0, time.to_ms(), sx, sy)
+        }
     }
 
     // TODO Wrapper type around Button and State
@@ -489,7 +606,10 @@ impl Seat {
     ///
     /// Returns the serial of the button press or zero if no button press was sent.
     pub fn pointer_notify_button(&self, time: Duration, button: u32, state: u32) -> u32 {
-        unsafe { wlr_seat_pointer_notify_button(self.data.0, time.to_ms(), button, state) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_pointer_notify_button(self.data.0, time.to_ms(), button, state)
+        }
     }
 
     /// Notify the seat of an axis event.
@@ -499,7 +619,23 @@ impl Seat {
                                value: f64,
                                value_discrete: i32,
                                source: wlr_axis_source) {
-        unsafe { wlr_seat_pointer_notify_axis(self.data.0, time.to_ms(), orientation, value, value_discrete, source) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_pointer_notify_axis(self.data.0,
+                                         time.to_ms(),
+                                         orientation,
+                                         value,
+                                         value_discrete,
+                                         source)
+        }
+    }
+
+    /// Notify the seat that a group of pointer events is complete.
+    ///
+    /// This should be called after forwarding the events of a pointer frame
+    /// (e.g in `PointerHandler::on_frame`), so clients apply them atomically.
+    pub fn pointer_notify_frame(&self) {
+        unsafe { wlr_seat_pointer_notify_frame(self.data.0) }
     }
 
     /// Set this keyboard as the active keyboard for the seat.
@@ -538,7 +674,8 @@ impl Seat {
     /// Notify the seat that the keyboard focus has changed and request it to be the
     /// focused surface for this keyboard.
     ///
-    /// Defers to any current grab of the seat's keyboard.
+    /// Defers to any current grab of the seat's keyboard. The text inputs of
+    /// the seat are moved to the new focus.
     pub fn keyboard_notify_enter(&self,
                                  surface: &mut Surface,
                                  keycodes: &mut [Keycode],
@@ -549,7 +686,9 @@ impl Seat {
                                            surface.as_ptr(),
                                            keycodes.as_mut_ptr(),
                                            keycodes_length,
-                                           modifiers)
+                                           modifiers);
+            #[cfg(feature = "text-input")]
+            update_text_input_focus(self.data.0);
         }
     }
 
@@ -570,7 +709,9 @@ impl Seat {
                                     surface.as_ptr(),
                                     keycodes.as_mut_ptr(),
                                     keycodes_length,
-                                    modifiers)
+                                    modifiers);
+            #[cfg(feature = "text-input")]
+            update_text_input_focus(self.data.0);
         }
     }
 
@@ -594,14 +735,21 @@ impl Seat {
     /// Clear the focused surface for the keyboard and leave all entered
     /// surfaces.
     pub fn keyboard_clear_focus(&self) {
-        unsafe { wlr_seat_keyboard_clear_focus(self.data.0) }
+        unsafe {
+            wlr_seat_keyboard_clear_focus(self.data.0);
+            #[cfg(feature = "text-input")]
+            update_text_input_focus(self.data.0);
+        }
     }
 
     /// Notify the seat that the modifiers for the keyboard have changed.
     ///
     /// Defers to any keyboard grabs.
     pub fn keyboard_notify_modifiers(&self, modifiers: &mut wlr_keyboard_modifiers) {
-        unsafe { wlr_seat_keyboard_notify_modifiers(self.data.0, modifiers) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_keyboard_notify_modifiers(self.data.0, modifiers)
+        }
     }
 
     // TODO Wrapper type for Key and State
@@ -610,7 +758,139 @@ impl Seat {
     ///
     /// Defers to any keyboard grabs.
     pub fn keyboard_notify_key(&self, time: Duration, key: u32, state: u32) {
-        unsafe { wlr_seat_keyboard_notify_key(self.data.0, time.to_ms(), key, state) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_keyboard_notify_key(self.data.0, time.to_ms(), key, state)
+        }
+    }
+
+    /// Give keyboard focus to the given surface, or clear the keyboard focus
+    /// if `None` is passed.
+    ///
+    /// The pressed keys and modifier state of the seat's keyboard
+    /// (see `set_keyboard`) are sent along, so the surface starts out in sync
+    /// with the keyboard. Defers to any keyboard grabs. The text inputs of the
+    /// seat are moved to the new focus.
+    ///
+    /// Unlike `keyboard_notify_enter` this does not borrow the surface or the
+    /// keyboard, so it can be called from their callbacks.
+    ///
+    /// Returns `HandleErr::AlreadyDropped` if the surface has been destroyed,
+    /// in which case the focus is left unchanged.
+    pub fn set_keyboard_focus(&mut self, surface: Option<&SurfaceHandle>) -> HandleResult<()> {
+        let surface = match surface {
+            Some(surface) => surface,
+            None => {
+                unsafe {
+                    wlr_seat_keyboard_clear_focus(self.data.0);
+                    #[cfg(feature = "text-input")]
+                    update_text_input_focus(self.data.0);
+                }
+                return Ok(())
+            }
+        };
+        if !surface.is_alive() {
+            return Err(HandleErr::AlreadyDropped)
+        }
+        unsafe {
+            let keyboard = wlr_seat_get_keyboard(self.data.0);
+            if keyboard.is_null() {
+                wlr_seat_keyboard_notify_enter(self.data.0,
+                                               surface.as_ptr(),
+                                               ptr::null_mut(),
+                                               0,
+                                               ptr::null_mut());
+            } else {
+                wlr_seat_keyboard_notify_enter(self.data.0,
+                                               surface.as_ptr(),
+                                               (*keyboard).keycodes.as_mut_ptr(),
+                                               (*keyboard).num_keycodes,
+                                               &mut (*keyboard).modifiers);
+                wlr_seat_keyboard_notify_modifiers(self.data.0, &mut (*keyboard).modifiers);
+            }
+            #[cfg(feature = "text-input")]
+            update_text_input_focus(self.data.0);
+        }
+        Ok(())
+    }
+
+    /// Forward a key to whoever should receive it.
+    ///
+    /// This is `keyboard_notify_key`: if there is an active keyboard grab
+    /// (e.g. a lock screen or a popup menu) the key goes to the grab. Otherwise
+    /// the default grab sends it to the surface with keyboard focus, if there
+    /// is one.
+    ///
+    /// Returns `false` if there was no grab and no focused surface, meaning the
+    /// key was not delivered to anyone.
+    pub fn forward_key(&mut self, time: Duration, key: u32, state: u32) -> bool {
+        let delivered = self.keyboard_has_grab() ||
+                        unsafe { !(*self.data.0).keyboard_state.focused_surface.is_null() };
+        self.keyboard_notify_key(time, key, state);
+        delivered
+    }
+
+    /// Set the selection (i.e the clipboard) of this seat, replacing the
+    /// current one. Passing `None` clears the selection.
+    ///
+    /// The serial should be that of the input event that caused the selection
+    /// to change.
+    pub fn set_selection(&mut self, source: Option<DataSource>, serial: u32) {
+        unsafe {
+            let source_ptr = source.map(|source| source.as_ptr()).unwrap_or(ptr::null_mut());
+            wlr_seat_set_selection(self.data.0, source_ptr, serial)
+        }
+    }
+
+    /// Set the primary selection (used for middle-click paste) of this seat,
+    /// replacing the current one. Passing `None` clears the primary selection.
+    ///
+    /// This is independent of the regular selection set with `set_selection`.
+    pub fn set_primary_selection(&mut self, source: Option<PrimarySelectionSource>, serial: u32) {
+        unsafe {
+            let source_ptr = source.map(|source| source.as_ptr()).unwrap_or(ptr::null_mut());
+            wlr_seat_set_primary_selection(self.data.0, source_ptr, serial)
+        }
+    }
+
+    /// Get the source of the current primary selection of this seat,
+    /// if there is one.
+    ///
+    /// The source is owned by the client that offered it, so it borrows the
+    /// seat to make sure it's not used once control returns to the event loop.
+    pub fn primary_selection_source(&self) -> Option<PrimarySelectionSource> {
+        unsafe {
+            let source = (*self.data.0).primary_selection_source;
+            if source.is_null() {
+                None
+            } else {
+                Some(PrimarySelectionSource::from_ptr(source))
+            }
+        }
+    }
+
+    /// Start a drag-and-drop operation driven by the pointer of this seat.
+    ///
+    /// This is how a drag requested through `SeatHandler::on_request_start_drag`
+    /// is accepted. The serial should be the one from that request.
+    pub fn start_pointer_drag(&mut self, drag: Drag, serial: u32) {
+        unsafe { wlr_seat_start_pointer_drag(self.data.0, drag.as_ptr(), serial) }
+    }
+
+    /// Get the source of the current selection (i.e the clipboard) of
+    /// this seat, if there is one.
+    ///
+    /// The source is owned by the client that offered it, so it borrows the
+    /// seat to make sure it's not used once control returns to the event loop.
+    pub fn selection_source(&self) -> Option<DataSource> {
+        unsafe {
+            let source = (*self.data.0).selection_source;
+            if source.is_null() {
+                None
+            } else {
+                Some(DataSource::from_ptr(source))
+            }
+        }
     }
 
     /// How many touch ponits are currently down for the seat.
@@ -738,6 +1018,7 @@ impl Seat {
                              sy: f64)
                              -> u32 {
         unsafe {
+            notify_seat_activity(self.data.0);
             wlr_seat_touch_notify_down(self.data.0,
                                        surface.as_ptr(),
                                        time.to_ms(),
@@ -750,7 +1031,10 @@ impl Seat {
     /// Notify the seat that the touch point given by `touch_id` is up. Defers to any
     /// grab of the touch device.
     pub fn touch_notify_up(&self, time: Duration, touch_id: TouchId) {
-        unsafe { wlr_seat_touch_notify_up(self.data.0, time.to_ms(), touch_id.into()) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_touch_notify_up(self.data.0, time.to_ms(), touch_id.into())
+        }
     }
 
     /// Notify the seat that the touch point given by `touch_id` has moved.
@@ -760,7 +1044,10 @@ impl Seat {
     /// The seat should be notified of touch motion even if the surface is
     /// not the owner of the touch point for processing by grabs.
     pub fn touch_notify_motion(&self, time: Duration, touch_id: TouchId, sx: f64, sy: f64) {
-        unsafe { wlr_seat_touch_notify_motion(self.data.0, time.to_ms(), touch_id.into(), sx, sy) }
+        unsafe {
+            notify_seat_activity(self.data.0);
+            wlr_seat_touch_notify_motion(self.data.0, time.to_ms(), touch_id.into(), sx, sy)
+        }
     }
 
     pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_seat {
@@ -814,6 +1101,12 @@ impl Drop for Seat {
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                           wl_list_remove,
                           &mut (*manager.new_drag_icon_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*manager.request_start_drag_listener()).link as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          &mut (*manager.start_drag_listener()).link as *mut _ as _);
             wlr_seat_destroy(seat_ptr);
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                           wl_list_remove,
@@ -921,6 +1214,8 @@ impl SeatHandle {
     }
 }
 
+impl_handle!(SeatHandle => Box<Seat>);
+
 impl Default for SeatHandle {
     fn default() -> Self {
         SeatHandle::new()
@@ -934,3 +1229,47 @@ impl PartialEq for SeatHandle {
 }
 
 impl Eq for SeatHandle {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, mem};
+    use wlroots_sys::{wlr_keyboard_grab_interface, wlr_seat_keyboard_grab};
+    use CompositorBuilder;
+
+    struct ExSeat;
+
+    impl SeatHandler for ExSeat {}
+
+    thread_local!(static GRABBED_KEYS: RefCell<Vec<u32>> = RefCell::new(Vec::new()));
+
+    unsafe extern "C" fn grab_key(_: *mut wlr_seat_keyboard_grab, _: u32, key: u32, _: u32) {
+        GRABBED_KEYS.with(|keys| keys.borrow_mut().push(key))
+    }
+
+    /// Forward a key with the grab active, then another one once it ended.
+    fn route_keys(seat: &mut Seat, grab: KeyboardGrab) -> (bool, bool) {
+        seat.keyboard_start_grab(grab);
+        let grabbed = seat.forward_key(Duration::from_millis(5), 30, 1);
+        seat.keyboard_end_grab();
+        // Nothing has keyboard focus, so this goes nowhere.
+        let focused = seat.forward_key(Duration::from_millis(6), 31, 1);
+        (grabbed, focused)
+    }
+
+    #[test]
+    fn grabs_take_keys_before_the_focused_surface() {
+        let mut compositor = CompositorBuilder::new().build_headless(())
+                                                     .expect("Could not open the Wayland socket");
+        let seat = Seat::create(&mut compositor, "seat0".into(), Box::new(ExSeat));
+        let mut interface: wlr_keyboard_grab_interface = unsafe { mem::zeroed() };
+        interface.key = Some(grab_key);
+        let mut grab: wlr_seat_keyboard_grab = unsafe { mem::zeroed() };
+        grab.interface = &mut interface as *mut _ as _;
+
+        let grab = unsafe { KeyboardGrab::from_ptr(&mut grab) };
+        let delivered = seat.run(|seat| route_keys(seat, grab)).unwrap();
+        assert_eq!(delivered, (true, false));
+        assert_eq!(GRABBED_KEYS.with(|keys| keys.borrow().clone()), vec![30]);
+    }
+}
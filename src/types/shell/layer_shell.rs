@@ -0,0 +1,387 @@
+//! Layer shell surfaces are used by desktop components (e.g panels, docks,
+//! wallpapers and lock screens) to be placed in a layer on an output,
+//! anchored to its edges.
+
+use std::ptr;
+use std::cell::Cell;
+use std::rc::{Rc, Weak};
+
+use libc::c_void;
+use wlroots_sys::{wlr_layer_surface, wlr_layer_surface_close, wlr_layer_surface_configure,
+                  wlr_layer_surface_for_each_surface, wlr_layer_surface_from_wlr_surface,
+                  wlr_layer_surface_state, wlr_layer_surface_surface_at, wlr_surface,
+                  wlr_surface_is_layer_surface, zwlr_layer_shell_v1_layer};
+use wlroots_sys::zwlr_layer_shell_v1_layer::*;
+
+use {OutputHandle, Surface, SurfaceHandle};
+use errors::{HandleErr, HandleResult};
+use manager::LayerShell;
+use utils::c_to_rust_string;
+
+/// Used internally to reclaim a handle from just a *mut wlr_layer_surface.
+pub(crate) struct LayerShellSurfaceState {
+    /// Pointer to the backing storage.
+    pub(crate) shell: *mut LayerShell,
+    handle: Weak<Cell<bool>>
+}
+
+/// The layer a layer shell surface is rendered in.
+///
+/// Layers are ordered from bottom to top, all normal windows are rendered
+/// between the `Bottom` and `Top` layers.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Layer {
+    Background,
+    Bottom,
+    Top,
+    Overlay
+}
+
+bitflags! {
+    /// The edges of the output a layer shell surface is anchored to.
+    pub struct Anchor: u32 {
+        const TOP = 1;
+        const BOTTOM = 2;
+        const LEFT = 4;
+        const RIGHT = 8;
+    }
+}
+
+/// The distance in surface local coordinates between each anchored edge
+/// of the surface and the edge of the output.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct Margin {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32
+}
+
+/// The double buffered state of a layer shell surface.
+#[derive(Debug, Clone, Copy)]
+pub struct LayerSurfaceState {
+    state: wlr_layer_surface_state
+}
+
+#[derive(Debug)]
+pub struct LayerShellSurface {
+    liveliness: Rc<Cell<bool>>,
+    layer_surface: *mut wlr_layer_surface
+}
+
+#[derive(Debug, Clone)]
+pub struct LayerShellSurfaceHandle {
+    handle: Weak<Cell<bool>>,
+    layer_surface: *mut wlr_layer_surface
+}
+
+impl Layer {
+    pub(crate) fn from_raw(layer: zwlr_layer_shell_v1_layer) -> Self {
+        match layer {
+            ZWLR_LAYER_SHELL_V1_LAYER_BACKGROUND => Layer::Background,
+            ZWLR_LAYER_SHELL_V1_LAYER_BOTTOM => Layer::Bottom,
+            ZWLR_LAYER_SHELL_V1_LAYER_TOP => Layer::Top,
+            ZWLR_LAYER_SHELL_V1_LAYER_OVERLAY => Layer::Overlay
+        }
+    }
+}
+
+impl LayerSurfaceState {
+    /// Get the edges of the output the surface is anchored to.
+    pub fn anchor(&self) -> Anchor {
+        Anchor::from_bits_truncate(self.state.anchor)
+    }
+
+    /// Get the size of the area that other surfaces should not occlude
+    /// along the anchored edge.
+    ///
+    /// Zero means the surface should be moved to avoid occluding other
+    /// exclusive zones, while a negative value means it doesn't care.
+    pub fn exclusive_zone(&self) -> i32 {
+        self.state.exclusive_zone
+    }
+
+    /// Get the margin between the surface and the anchored edges.
+    pub fn margin(&self) -> Margin {
+        let margin = self.state.margin;
+        Margin { top: margin.top,
+                 right: margin.right,
+                 bottom: margin.bottom,
+                 left: margin.left }
+    }
+
+    /// Determines if the surface wants to receive keyboard focus.
+    pub fn keyboard_interactive(&self) -> bool {
+        self.state.keyboard_interactive
+    }
+
+    /// Get the size requested by the client.
+    ///
+    /// A dimension of zero means the compositor should decide, which usually
+    /// means stretching between the anchored edges.
+    ///
+    /// Return value is in (width, height) format.
+    pub fn desired_size(&self) -> (u32, u32) {
+        (self.state.desired_width, self.state.desired_height)
+    }
+
+    /// Get the size that was last sent to the client in a configure.
+    ///
+    /// Return value is in (width, height) format.
+    pub fn actual_size(&self) -> (u32, u32) {
+        (self.state.actual_width, self.state.actual_height)
+    }
+}
+
+impl LayerShellSurface {
+    pub(crate) unsafe fn new(layer_surface: *mut wlr_layer_surface) -> Self {
+        let liveliness = Rc::new(Cell::new(false));
+        let state = Box::new(LayerShellSurfaceState { shell: ptr::null_mut(),
+                                                      handle: Rc::downgrade(&liveliness) });
+        (*layer_surface).data = Box::into_raw(state) as *mut _;
+        LayerShellSurface { liveliness,
+                            layer_surface }
+    }
+
+    unsafe fn from_handle(handle: &LayerShellSurfaceHandle) -> HandleResult<Self> {
+        let liveliness = handle.handle
+                               .upgrade()
+                               .ok_or_else(|| HandleErr::AlreadyDropped)?;
+        Ok(LayerShellSurface { liveliness,
+                               layer_surface: handle.as_ptr() })
+    }
+
+    /// Gets the surface used by this layer shell.
+    pub fn surface(&mut self) -> SurfaceHandle {
+        unsafe {
+            let surface = (*self.layer_surface).surface;
+            if surface.is_null() {
+                panic!("layer shell had a null surface!")
+            }
+            SurfaceHandle::from_ptr(surface)
+        }
+    }
+
+    /// Get the output this surface is displayed on.
+    ///
+    /// If the client didn't request an output this is `None` until the
+    /// compositor assigns one with `set_output`.
+    pub fn output(&self) -> Option<OutputHandle> {
+        unsafe {
+            let output = (*self.layer_surface).output;
+            if output.is_null() {
+                None
+            } else {
+                Some(OutputHandle::from_ptr(output))
+            }
+        }
+    }
+
+    /// Assign the output this surface should be displayed on.
+    ///
+    /// This should be done in `LayerShellManagerHandler::new_surface`
+    /// if the client didn't request a specific output.
+    pub fn set_output(&mut self, output: &OutputHandle) {
+        unsafe { (*self.layer_surface).output = output.as_ptr() }
+    }
+
+    /// Get the layer this surface is rendered in.
+    pub fn layer(&self) -> Layer {
+        unsafe { Layer::from_raw((*self.layer_surface).layer) }
+    }
+
+    /// Get the namespace the client gave this surface (e.g "panel"
+    /// or "wallpaper").
+    pub fn namespace(&self) -> Option<String> {
+        unsafe { c_to_rust_string((*self.layer_surface).namespace) }
+    }
+
+    /// Get the pending client state.
+    pub fn client_pending_state(&self) -> LayerSurfaceState {
+        unsafe { LayerSurfaceState { state: (*self.layer_surface).client_pending } }
+    }
+
+    /// Get the pending server state.
+    pub fn server_pending_state(&self) -> LayerSurfaceState {
+        unsafe { LayerSurfaceState { state: (*self.layer_surface).server_pending } }
+    }
+
+    /// Get the current configure state.
+    pub fn current_state(&self) -> LayerSurfaceState {
+        unsafe { LayerSurfaceState { state: (*self.layer_surface).current } }
+    }
+
+    /// Determines if this layer shell surface has been configured or not.
+    pub fn configured(&self) -> bool {
+        unsafe { (*self.layer_surface).configured }
+    }
+
+    /// Determines if this layer shell surface is mapped.
+    pub fn mapped(&self) -> bool {
+        unsafe { (*self.layer_surface).mapped }
+    }
+
+    /// Determines if this layer shell surface has been closed.
+    pub fn closed(&self) -> bool {
+        unsafe { (*self.layer_surface).closed }
+    }
+
+    /// Tell the client the size it should be.
+    ///
+    /// This should be sent after the client commits a new state, once the
+    /// compositor has decided where the surface goes.
+    pub fn configure(&mut self, width: u32, height: u32) {
+        unsafe { wlr_layer_surface_configure(self.layer_surface, width, height) }
+    }
+
+    /// Tell the client that the surface has been closed and won't be shown
+    /// again (e.g because its output was removed).
+    pub fn close(&mut self) {
+        unsafe { wlr_layer_surface_close(self.layer_surface) }
+    }
+
+    /// Find a surface within this surface at the surface-local coordinates.
+    ///
+    /// Returns the surface and coordinates in the topmost surface coordinate system
+    /// or None if no surface is found at that location.
+    pub fn surface_at(&mut self,
+                      sx: f64,
+                      sy: f64,
+                      sub_sx: &mut f64,
+                      sub_sy: &mut f64)
+                      -> Option<SurfaceHandle> {
+        unsafe {
+            let sub_surface =
+                wlr_layer_surface_surface_at(self.layer_surface, sx, sy, sub_sx, sub_sy);
+            if sub_surface.is_null() {
+                None
+            } else {
+                Some(SurfaceHandle::from_ptr(sub_surface))
+            }
+        }
+    }
+
+    /// Calls the iterator on this surface and each of its subsurfaces and
+    /// popups, with the surface local coordinates of each.
+    pub fn for_each_surface<F>(&self, mut iterator: F)
+        where F: FnMut(SurfaceHandle, i32, i32)
+    {
+        let mut iterator_ref: &mut FnMut(SurfaceHandle, i32, i32) = &mut iterator;
+        unsafe {
+            unsafe extern "C" fn c_iterator(wlr_surface: *mut wlr_surface,
+                                            sx: i32,
+                                            sy: i32,
+                                            data: *mut c_void) {
+                let iterator_fn = &mut *(data as *mut &mut FnMut(SurfaceHandle, i32, i32));
+                let surface = SurfaceHandle::from_ptr(wlr_surface);
+                iterator_fn(surface, sx, sy);
+            }
+            let iterator_ptr: *mut c_void = &mut iterator_ref as *mut _ as *mut c_void;
+            wlr_layer_surface_for_each_surface(self.layer_surface,
+                                               Some(c_iterator),
+                                               iterator_ptr);
+        }
+    }
+
+    /// Creates a weak reference to a `LayerShellSurface`.
+    pub fn weak_reference(&self) -> LayerShellSurfaceHandle {
+        LayerShellSurfaceHandle { handle: Rc::downgrade(&self.liveliness),
+                                  layer_surface: self.layer_surface }
+    }
+}
+
+impl LayerShellSurfaceHandle {
+    /// Constructs a new LayerShellSurfaceHandle that is always invalid. Calling `run` on this
+    /// will always fail.
+    ///
+    /// This is useful for pre-filling a value before it's provided by the server, or
+    /// for mocking/testing.
+    pub fn new() -> Self {
+        LayerShellSurfaceHandle { handle: Weak::new(),
+                                  layer_surface: ptr::null_mut() }
+    }
+
+    /// If the surface is a layer shell surface, get a handle to the layer shell surface.
+    pub fn from_surface(surface: &Surface) -> Option<LayerShellSurfaceHandle> {
+        unsafe {
+            if !wlr_surface_is_layer_surface(surface.as_ptr()) {
+                None
+            } else {
+                let layer_surface_ptr = wlr_layer_surface_from_wlr_surface(surface.as_ptr());
+                Some(LayerShellSurfaceHandle::from_ptr(layer_surface_ptr))
+            }
+        }
+    }
+
+    /// Creates a LayerShellSurfaceHandle from the raw pointer, using the saved
+    /// user data to recreate the memory model.
+    pub(crate) unsafe fn from_ptr(layer_surface: *mut wlr_layer_surface) -> Self {
+        let data = (*layer_surface).data as *mut LayerShellSurfaceState;
+        if data.is_null() {
+            panic!("Cannot construct handle from a layer surface that has not been set up!");
+        }
+        let handle = (*data).handle.clone();
+        LayerShellSurfaceHandle { handle,
+                                  layer_surface }
+    }
+
+    /// Upgrades the layer shell handle to a reference to the backing `LayerShellSurface`.
+    ///
+    /// # Unsafety
+    /// This function is unsafe, because it creates an unbound `LayerShellSurface`
+    /// which may live forever..
+    /// But no surface lives forever and might be disconnected at any time.
+    pub(crate) unsafe fn upgrade(&self) -> HandleResult<LayerShellSurface> {
+        self.handle.upgrade()
+            .ok_or(HandleErr::AlreadyDropped)
+            // NOTE
+            // We drop the Rc here because having two would allow a dangling
+            // pointer to exist!
+            .and_then(|check| {
+                let layer_surface = LayerShellSurface::from_handle(self)?;
+                if check.get() {
+                    return Err(HandleErr::AlreadyBorrowed)
+                }
+                check.set(true);
+                Ok(layer_surface)
+            })
+    }
+
+    /// Run a function on the referenced LayerShellSurface, if it still exists
+    ///
+    /// Returns the result of the function, if successful
+    ///
+    /// # Safety
+    /// By enforcing a rather harsh limit on the lifetime of the output
+    /// to a short lived scope of an anonymous function,
+    /// this function ensures the LayerShellSurface does not live longer
+    /// than it exists.
+    ///
+    /// Returns `HandleErr::AlreadyBorrowed` if the `LayerShellSurface` is
+    /// already borrowed, e.g from within another `run` on it.
+    pub fn run<F, R>(&mut self, runner: F) -> HandleResult<R>
+        where F: FnOnce(&mut LayerShellSurface) -> R
+    {
+        self.try_run(runner)
+    }
+
+    unsafe fn as_ptr(&self) -> *mut wlr_layer_surface {
+        self.layer_surface
+    }
+}
+
+impl_handle!(LayerShellSurfaceHandle => LayerShellSurface);
+
+impl Default for LayerShellSurfaceHandle {
+    fn default() -> Self {
+        LayerShellSurfaceHandle::new()
+    }
+}
+
+impl PartialEq for LayerShellSurfaceHandle {
+    fn eq(&self, other: &LayerShellSurfaceHandle) -> bool {
+        self.layer_surface == other.layer_surface
+    }
+}
+
+impl Eq for LayerShellSurfaceHandle {}
@@ -1,5 +1,7 @@
+mod layer_shell;
 mod xdg_shell_v6;
 mod xdg_shell;
 
+pub use self::layer_shell::*;
 pub use self::xdg_shell_v6::*;
 pub use self::xdg_shell::*;
@@ -320,6 +320,8 @@ impl XdgShellSurfaceHandle {
     }
 }
 
+impl_handle!(XdgShellSurfaceHandle => XdgShellSurface);
+
 impl Default for XdgShellSurfaceHandle {
     fn default() -> Self {
         XdgShellSurfaceHandle::new()
@@ -310,6 +310,8 @@ impl XdgV6ShellSurfaceHandle {
     }
 }
 
+impl_handle!(XdgV6ShellSurfaceHandle => XdgV6ShellSurface);
+
 impl Default for XdgV6ShellSurfaceHandle {
     fn default() -> Self {
         XdgV6ShellSurfaceHandle::new()
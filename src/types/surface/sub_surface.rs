@@ -197,6 +197,8 @@ impl SubsurfaceHandle {
     }
 }
 
+impl_handle!(SubsurfaceHandle => Subsurface);
+
 impl Default for SubsurfaceHandle {
     fn default() -> Self {
         SubsurfaceHandle::new()
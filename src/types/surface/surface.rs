@@ -1,6 +1,6 @@
 //! TODO Documentation
 
-use libc::{self, c_double};
+use libc::{self, c_double, c_void};
 use std::{panic, ptr, cell::Cell, rc::{Rc, Weak}, time::Duration};
 
 use wayland_sys::server::WAYLAND_SERVER_HANDLE;
@@ -8,23 +8,32 @@ use wayland_sys::server::signal::wl_signal_add;
 use wlroots_sys::{timespec, wlr_subsurface, wlr_surface, wlr_surface_get_root_surface,
                   wlr_surface_has_buffer, wlr_surface_point_accepts_input, wlr_surface_send_enter,
                   wlr_surface_send_frame_done, wlr_surface_send_leave, wlr_surface_surface_at,
-                  wlr_surface_is_xdg_surface, wlr_surface_get_texture};
+                  wlr_surface_is_xdg_surface, wlr_surface_get_texture,
+                  wlr_surface_for_each_surface};
 
 use super::{Subsurface, SubsurfaceHandle, SubsurfaceHandler, SubsurfaceManager, SurfaceState,
             InternalSubsurface};
 use compositor::{compositor_handle, CompositorHandle};
-use Output;
+use {Output, OutputHandle};
 use errors::{HandleErr, HandleResult};
 use render::Texture;
 use utils::c_to_rust_string;
 
+/// Handles events from a `Surface`.
 pub trait SurfaceHandler {
+    /// Called when the client commits new state to the surface.
+    ///
+    /// The damage of the commit can be read from `Surface::current_state`.
     fn on_commit(&mut self, CompositorHandle, SurfaceHandle) {}
 
+    /// Called when a new subsurface is added to the surface.
+    ///
+    /// Return a handler to listen to the subsurface's events.
     fn new_subsurface(&mut self, CompositorHandle, SurfaceHandle, SubsurfaceHandle) -> Option<Box<SubsurfaceHandler>> {
         None
     }
 
+    /// Called when the surface is destroyed.
     fn on_destroy(&mut self, CompositorHandle, SurfaceHandle) {}
 }
 
@@ -266,6 +275,29 @@ impl Surface {
         }
     }
 
+    /// Calls the iterator on this surface and each of its subsurfaces,
+    /// with the coordinates of each relative to this surface.
+    ///
+    /// Surfaces are iterated in rendering order, so drawing them in this
+    /// order will correctly stack the subsurfaces.
+    pub fn for_each_surface<F>(&self, mut iterator: F)
+        where F: FnMut(SurfaceHandle, i32, i32)
+    {
+        let mut iterator_ref: &mut FnMut(SurfaceHandle, i32, i32) = &mut iterator;
+        unsafe {
+            unsafe extern "C" fn c_iterator(wlr_surface: *mut wlr_surface,
+                                            sx: i32,
+                                            sy: i32,
+                                            data: *mut c_void) {
+                let iterator_fn = &mut *(data as *mut &mut FnMut(SurfaceHandle, i32, i32));
+                let surface = SurfaceHandle::from_ptr(wlr_surface);
+                iterator_fn(surface, sx, sy);
+            }
+            let iterator_ptr: *mut c_void = &mut iterator_ref as *mut _ as *mut c_void;
+            wlr_surface_for_each_surface(self.surface, Some(c_iterator), iterator_ptr);
+        }
+    }
+
     /// Get the top of the subsurface tree for this surface.
     pub fn get_root_surface(&self) -> Option<SurfaceHandle> {
         unsafe {
@@ -278,14 +310,43 @@ impl Surface {
         }
     }
 
+    /// Tell the client that the surface is now shown on the output
+    /// (e.g because its window was moved onto that monitor).
+    ///
+    /// Clients use this to pick the scale they render at.
     pub fn send_enter(&mut self, output: &mut Output) {
         unsafe { wlr_surface_send_enter(self.surface, output.as_ptr()) }
     }
 
+    /// Tell the client that the surface is no longer shown on the output.
     pub fn send_leave(&mut self, output: &mut Output) {
         unsafe { wlr_surface_send_leave(self.surface, output.as_ptr()) }
     }
 
+    /// Same as `send_enter`, but takes a handle so the output doesn't
+    /// need to be borrowed.
+    ///
+    /// Returns an error if the output has already been destroyed.
+    pub fn send_enter_handle(&mut self, output: &OutputHandle) -> HandleResult<()> {
+        if !output.is_alive() {
+            return Err(HandleErr::AlreadyDropped)
+        }
+        unsafe { wlr_surface_send_enter(self.surface, output.as_ptr()) }
+        Ok(())
+    }
+
+    /// Same as `send_leave`, but takes a handle so the output doesn't
+    /// need to be borrowed.
+    ///
+    /// Returns an error if the output has already been destroyed.
+    pub fn send_leave_handle(&mut self, output: &OutputHandle) -> HandleResult<()> {
+        if !output.is_alive() {
+            return Err(HandleErr::AlreadyDropped)
+        }
+        unsafe { wlr_surface_send_leave(self.surface, output.as_ptr()) }
+        Ok(())
+    }
+
     /// Send the frame done event.
     pub fn send_frame_done(&mut self, duration: Duration) {
         unsafe {
@@ -364,6 +425,21 @@ impl SurfaceHandle {
                         subsurfaces_manager }
     }
 
+    /// Gets the raw pointer to the wlroots surface this handle refers to.
+    ///
+    /// The pointer is only valid while `is_alive` returns `true`.
+    pub(crate) unsafe fn as_ptr(&self) -> *mut wlr_surface {
+        self.surface
+    }
+
+    /// Determines if the backing `Surface` still exists.
+    ///
+    /// Unlike `run`, this does not borrow the surface, so it can be used
+    /// while the surface is already being used elsewhere.
+    pub(crate) fn is_alive(&self) -> bool {
+        self.handle.upgrade().is_some()
+    }
+
     /// Upgrades the surface handle to a reference to the backing `Surface`.
     ///
     /// # Unsafety
@@ -424,6 +500,8 @@ impl SurfaceHandle {
     }
 }
 
+impl_handle!(SurfaceHandle => Surface);
+
 impl Default for SurfaceHandle {
     fn default() -> Self {
         SurfaceHandle::new()
@@ -3,9 +3,9 @@
 use libc::c_int;
 use std::marker::PhantomData;
 
-use wlroots_sys::{wl_output_transform, wl_resource, wlr_surface_state};
+use wlroots_sys::{wl_resource, wlr_surface_state};
 
-use {PixmanRegion, Surface};
+use {PixmanRegion, Surface, Transform};
 
 #[derive(Debug)]
 #[repr(u32)]
@@ -82,27 +82,35 @@ impl<'surface> SurfaceState<'surface> {
         unsafe { (self.state.dx, self.state.dy) }
     }
 
-    /// Get the size of the sub surface.
+    /// Get the size of the surface in surface local coordinates.
+    ///
+    /// This already takes the buffer scale and transform into account,
+    /// so to get the size in output pixels multiply it by the output's scale.
     ///
     /// Return value is in (width, height) format.
     pub fn size(&self) -> (c_int, c_int) {
         unsafe { (self.state.width, self.state.height) }
     }
 
-    /// Get the size of the buffer.
+    /// Get the size of the attached buffer in pixels.
+    ///
+    /// This is the size before the buffer scale and transform are applied.
     ///
-    /// Return value is iw (width, height) format.
+    /// Return value is in (width, height) format.
     pub fn buffer_size(&self) -> (c_int, c_int) {
         unsafe { (self.state.buffer_width, self.state.buffer_height) }
     }
 
-    /// Get the scale applied to the surface.
+    /// Get the scale the client rendered the buffer at.
+    ///
+    /// e.g a HiDPI client on an output with a scale of 2 will usually
+    /// attach buffers with a scale of 2, twice the surface size.
     pub fn scale(&self) -> i32 {
         unsafe { self.state.scale }
     }
 
-    /// Get the output transform applied to the surface.
-    pub fn transform(&self) -> wl_output_transform {
+    /// Get the transform the client applied to the buffer contents.
+    pub fn transform(&self) -> Transform {
         unsafe { self.state.transform }
     }
 
@@ -111,19 +119,35 @@ impl<'surface> SurfaceState<'surface> {
         self.state.buffer_resource
     }
 
-    pub unsafe fn surface_damage(&self) -> PixmanRegion {
-        PixmanRegion { region: self.state.surface_damage }
+    /// Get the damage the client reported in surface local coordinates.
+    ///
+    /// The returned region is a copy, so it can be kept past the commit.
+    pub fn surface_damage(&self) -> PixmanRegion {
+        unsafe { PixmanRegion::from_raw_copy(&self.state.surface_damage) }
     }
 
-    pub unsafe fn buffer_damage(&self) -> PixmanRegion {
-        PixmanRegion { region: self.state.buffer_damage }
+    /// Get the damage the client reported in buffer coordinates.
+    ///
+    /// The returned region is a copy, so it can be kept past the commit.
+    pub fn buffer_damage(&self) -> PixmanRegion {
+        unsafe { PixmanRegion::from_raw_copy(&self.state.buffer_damage) }
+    }
+
+    /// Get the damage accumulated by the commit, in buffer coordinates.
+    ///
+    /// This is what should be read in `SurfaceHandler::on_commit` to track
+    /// damage per surface instead of redrawing everything.
+    pub fn damage(&self) -> PixmanRegion {
+        self.buffer_damage()
     }
 
-    pub unsafe fn opaque(&self) -> PixmanRegion {
-        PixmanRegion { region: self.state.opaque }
+    /// Get the region of the surface the client marked as opaque.
+    pub fn opaque(&self) -> PixmanRegion {
+        unsafe { PixmanRegion::from_raw_copy(&self.state.opaque) }
     }
 
-    pub unsafe fn input(&self) -> PixmanRegion {
-        PixmanRegion { region: self.state.input }
+    /// Get the region of the surface that accepts input.
+    pub fn input(&self) -> PixmanRegion {
+        unsafe { PixmanRegion::from_raw_copy(&self.state.input) }
     }
 }
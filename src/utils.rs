@@ -130,3 +130,99 @@ bitflags! {
         const WLR_EDGE_RIGHT = wlr_edges::WLR_EDGE_RIGHT as u32;
     }
 }
+
+/// The edge or corner of a surface that is being dragged during an
+/// interactive resize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight
+}
+
+impl ResizeEdge {
+    /// Converts the raw edges of a resize request into the edge being dragged.
+    ///
+    /// Returns `None` if no edge is set, or if the combination of edges
+    /// doesn't make sense (e.g both the left and right edges).
+    pub fn from_edges(edges: Edges) -> Option<Self> {
+        let top = edges.contains(Edges::WLR_EDGE_TOP);
+        let bottom = edges.contains(Edges::WLR_EDGE_BOTTOM);
+        let left = edges.contains(Edges::WLR_EDGE_LEFT);
+        let right = edges.contains(Edges::WLR_EDGE_RIGHT);
+        match (top, bottom, left, right) {
+            (true, false, false, false) => Some(ResizeEdge::Top),
+            (false, true, false, false) => Some(ResizeEdge::Bottom),
+            (false, false, true, false) => Some(ResizeEdge::Left),
+            (false, false, false, true) => Some(ResizeEdge::Right),
+            (true, false, true, false) => Some(ResizeEdge::TopLeft),
+            (true, false, false, true) => Some(ResizeEdge::TopRight),
+            (false, true, true, false) => Some(ResizeEdge::BottomLeft),
+            (false, true, false, true) => Some(ResizeEdge::BottomRight),
+            _ => None
+        }
+    }
+
+    /// Gets the raw edges that make up this edge or corner.
+    pub fn edges(self) -> Edges {
+        use self::ResizeEdge::*;
+        match self {
+            Top => Edges::WLR_EDGE_TOP,
+            Bottom => Edges::WLR_EDGE_BOTTOM,
+            Left => Edges::WLR_EDGE_LEFT,
+            Right => Edges::WLR_EDGE_RIGHT,
+            TopLeft => Edges::WLR_EDGE_TOP | Edges::WLR_EDGE_LEFT,
+            TopRight => Edges::WLR_EDGE_TOP | Edges::WLR_EDGE_RIGHT,
+            BottomLeft => Edges::WLR_EDGE_BOTTOM | Edges::WLR_EDGE_LEFT,
+            BottomRight => Edges::WLR_EDGE_BOTTOM | Edges::WLR_EDGE_RIGHT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ResizeEdge; 8] = [ResizeEdge::Top,
+                                  ResizeEdge::Bottom,
+                                  ResizeEdge::Left,
+                                  ResizeEdge::Right,
+                                  ResizeEdge::TopLeft,
+                                  ResizeEdge::TopRight,
+                                  ResizeEdge::BottomLeft,
+                                  ResizeEdge::BottomRight];
+
+    #[test]
+    fn edges_round_trip() {
+        for &edge in &ALL {
+            assert_eq!(ResizeEdge::from_edges(edge.edges()), Some(edge));
+        }
+    }
+
+    #[test]
+    fn corners_combine_two_edges() {
+        assert_eq!(ResizeEdge::from_edges(Edges::WLR_EDGE_BOTTOM | Edges::WLR_EDGE_RIGHT),
+                   Some(ResizeEdge::BottomRight));
+        assert_eq!(ResizeEdge::TopLeft.edges(), Edges::WLR_EDGE_TOP | Edges::WLR_EDGE_LEFT);
+    }
+
+    #[test]
+    fn no_edge_without_any_edges() {
+        assert_eq!(ResizeEdge::from_edges(Edges::WLR_EDGE_NONE), None);
+    }
+
+    #[test]
+    fn no_edge_for_opposite_edges() {
+        assert_eq!(ResizeEdge::from_edges(Edges::WLR_EDGE_LEFT | Edges::WLR_EDGE_RIGHT), None);
+        assert_eq!(ResizeEdge::from_edges(Edges::WLR_EDGE_TOP | Edges::WLR_EDGE_BOTTOM), None);
+        assert_eq!(ResizeEdge::from_edges(Edges::all()), None);
+        assert_eq!(ResizeEdge::from_edges(Edges::WLR_EDGE_TOP | Edges::WLR_EDGE_LEFT |
+                                          Edges::WLR_EDGE_RIGHT),
+                   None);
+    }
+}
@@ -80,6 +80,10 @@ wayland_listener!(XWaylandManager, Box<XWaylandManagerHandler>, [
                         shell.set_window_type_listener() as *mut _ as _);
         wl_signal_add(&mut (*surface_ptr).events.ping_timeout as *mut _ as _,
                         shell.ping_timeout_listener() as *mut _ as _);
+        wl_signal_add(&mut (*surface_ptr).events.set_override_redirect as *mut _ as _,
+                        shell.set_override_redirect_listener() as *mut _ as _);
+        wl_signal_add(&mut (*surface_ptr).events.set_geometry as *mut _ as _,
+                        shell.set_geometry_listener() as *mut _ as _);
         let shell_data = (*surface_ptr).data as *mut XWaylandSurfaceState;
         (*shell_data).shell = Box::into_raw(shell);
         // TODO Pass in the new surface from the data
@@ -1,9 +1,10 @@
-use std::{panic, ptr, cell::Cell, rc::{Rc, Weak}};
+use std::{panic, ptr, slice, cell::Cell, rc::{Rc, Weak}};
 
 use libc::{self, size_t, int16_t, uint16_t};
 
 use wayland_sys::server::WAYLAND_SERVER_HANDLE;
-use wlroots_sys::{pid_t, wl_event_source, wlr_xwayland_surface, xcb_atom_t, xcb_window_t};
+use wlroots_sys::{pid_t, wl_event_source, wlr_xwayland_surface, wlr_xwayland_surface_configure,
+                  xcb_atom_t, xcb_window_t};
 
 use {SurfaceHandle, XWaylandSurfaceHints, XWaylandSurfaceSizeHints};
 use compositor::{compositor_handle, CompositorHandle};
@@ -35,8 +36,10 @@ pub trait XWaylandSurfaceHandler {
     /// Called when the XWayland surface wants to be fullscreen.
     fn on_fullscreen(&mut self, CompositorHandle, SurfaceHandle, XWaylandSurfaceHandle) {}
 
+    /// Called when the XWayland surface is ready to be displayed.
     fn on_map(&mut self, CompositorHandle, SurfaceHandle, XWaylandSurfaceHandle) {}
 
+    /// Called when the XWayland surface should no longer be displayed.
     fn on_unmap(&mut self, CompositorHandle, SurfaceHandle, XWaylandSurfaceHandle) {}
 
     /// Called when the title has been set on the XWayland surface.
@@ -58,6 +61,23 @@ pub trait XWaylandSurfaceHandler {
     ///
     /// This usually indicates something is wrong with the client.
     fn ping_timeout(&mut self, CompositorHandle, SurfaceHandle, XWaylandSurfaceHandle) {}
+
+    /// Called when the window changes whether it is override-redirect.
+    ///
+    /// Check `XWaylandSurface::override_redirect`, override-redirect windows
+    /// must not be managed like normal windows.
+    fn on_set_override_redirect(&mut self,
+                                CompositorHandle,
+                                SurfaceHandle,
+                                XWaylandSurfaceHandle) {
+    }
+
+    /// Called when the window moves or resizes itself.
+    ///
+    /// This is mostly done by override-redirect windows, which place
+    /// themselves. The new geometry is in `XWaylandSurface::coords` and
+    /// `XWaylandSurface::dimensions`.
+    fn on_set_geometry(&mut self, CompositorHandle, SurfaceHandle, XWaylandSurfaceHandle) {}
 }
 
 wayland_listener!(XWaylandShell, (XWaylandSurface, Option<Box<XWaylandSurfaceHandler>>), [
@@ -285,6 +305,38 @@ wayland_listener!(XWaylandShell, (XWaylandSurface, Option<Box<XWaylandSurfaceHan
                              surface,
                              shell_surface.weak_reference());
     };
+    set_override_redirect_listener => set_override_redirect_notify: |this: &mut XWaylandShell,
+                                                                     _data: *mut libc::c_void,|
+    unsafe {
+        let (ref mut shell_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ss, Some(manager)) => (ss, manager)
+        };
+        let surface = shell_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        manager.on_set_override_redirect(compositor,
+                                         surface,
+                                         shell_surface.weak_reference());
+    };
+    set_geometry_listener => set_geometry_notify: |this: &mut XWaylandShell,
+                                                   _data: *mut libc::c_void,|
+    unsafe {
+        let (ref mut shell_surface, ref mut manager) = match &mut this.data {
+            (_, None) => return,
+            (ss, Some(manager)) => (ss, manager)
+        };
+        let surface = shell_surface.surface();
+        let compositor = match compositor_handle() {
+            Some(handle) => handle,
+            None => return
+        };
+        manager.on_set_geometry(compositor,
+                                surface,
+                                shell_surface.weak_reference());
+    };
 ]);
 
 pub(crate) struct XWaylandSurfaceState {
@@ -372,11 +424,24 @@ impl XWaylandSurface {
         unsafe { ((*self.shell_surface).saved_width, (*self.shell_surface).saved_height) }
     }
 
-    /// TODO What does this represent?
+    /// Determines if the window is override-redirect.
+    ///
+    /// Override-redirect windows (e.g menus, tooltips and splash screens)
+    /// place themselves and must bypass normal window management, so they
+    /// should not be tiled, decorated or given focus.
     pub fn override_redirect(&self) -> bool {
         unsafe { (*self.shell_surface).override_redirect }
     }
 
+    /// Tell the X client where its window is and how big it should be.
+    ///
+    /// The coordinates are in layout coordinates. This should be called
+    /// in response to `on_configure`, even if the request is denied.
+    pub fn configure(&mut self, x: int16_t, y: int16_t, width: uint16_t, height: uint16_t) {
+        unsafe { wlr_xwayland_surface_configure(self.shell_surface, x, y, width, height) }
+    }
+
+    /// Determines if the surface is currently being displayed.
     pub fn mapped(&self) -> bool {
         unsafe { (*self.shell_surface).mapped }
     }
@@ -431,6 +496,18 @@ impl XWaylandSurface {
         }
     }
 
+    /// Get the `_NET_WM_WINDOW_TYPE` atoms of the window
+    /// (e.g `_NET_WM_WINDOW_TYPE_DIALOG`), in order of preference.
+    pub fn window_types(&self) -> Vec<xcb_atom_t> {
+        unsafe {
+            let window_type = (*self.shell_surface).window_type;
+            if window_type.is_null() {
+                return Vec::new()
+            }
+            slice::from_raw_parts(window_type, (*self.shell_surface).window_type_len).to_vec()
+        }
+    }
+
     /// Get the type of the window from xcb.
     pub unsafe fn window_type(&self) -> *mut xcb_atom_t {
         (*self.shell_surface).window_type
@@ -590,6 +667,8 @@ impl XWaylandSurfaceHandle {
     }
 }
 
+impl_handle!(XWaylandSurfaceHandle => XWaylandSurface);
+
 impl Drop for XWaylandSurface {
     fn drop(&mut self) {
         if Rc::strong_count(&self.liveliness) > 1 {
@@ -660,6 +739,12 @@ impl Drop for XWaylandShell {
             ffi_dispatch!(WAYLAND_SERVER_HANDLE,
                           wl_list_remove,
                           self.ping_timeout_listener() as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          self.set_override_redirect_listener() as *mut _ as _);
+            ffi_dispatch!(WAYLAND_SERVER_HANDLE,
+                          wl_list_remove,
+                          self.set_geometry_listener() as *mut _ as _);
         }
     }
 }
@@ -0,0 +1,83 @@
+//! Checks which cursor images `Output::set_hardware_cursor` can't put in the
+//! cursor plane.
+//!
+//! The headless backend has no cursor plane, so every valid image falls back
+//! to a software cursor, the same way an image that is larger than the cursor
+//! plane does on other backends.
+
+extern crate wlroots;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wlroots::{CompositorBuilder, CompositorHandle, Image, Output, OutputBuilder,
+              OutputBuilderResult, OutputHandler, OutputManagerHandler};
+
+struct ExOutput;
+
+impl OutputHandler for ExOutput {}
+
+struct OutputManager;
+
+impl OutputManagerHandler for OutputManager {
+    fn output_added<'output>(&mut self,
+                             _: CompositorHandle,
+                             builder: OutputBuilder<'output>)
+                             -> Option<OutputBuilderResult<'output>> {
+        Some(builder.build_best_mode(ExOutput))
+    }
+}
+
+/// The result of `set_hardware_cursor` and `hardware_cursor_failed`.
+type Attempt = (bool, bool);
+
+/// The attempts to set a cursor image, made once the output exists.
+#[derive(Debug, PartialEq)]
+struct Attempts {
+    small: Attempt,
+    oversized: Attempt,
+    hotspot_outside: Attempt,
+    short_pixels: Attempt
+}
+
+fn image(pixels: &[u8], size: u32, hotspot: i32) -> Image {
+    Image::new(pixels, size as i32 * 4, size, size, hotspot, hotspot, 1.0, 0)
+}
+
+fn attempt(output: &mut Output, image: &Image) -> Attempt {
+    (output.set_hardware_cursor(image), output.hardware_cursor_failed())
+}
+
+fn set_cursors(output: &mut Output) -> Attempts {
+    let small = vec![0xff; 16 * 16 * 4];
+    let oversized = vec![0xff; 1024 * 1024 * 4];
+    Attempts { small: attempt(output, &image(&small, 16, 0)),
+               oversized: attempt(output, &image(&oversized, 1024, 512)),
+               hotspot_outside: attempt(output, &image(&small, 16, 16)),
+               short_pixels: attempt(output, &image(&small[..16 * 4], 16, 0)) }
+}
+
+#[test]
+fn cursors_without_a_cursor_plane_are_drawn_in_software() {
+    let mut compositor = CompositorBuilder::new().output_manager(Box::new(OutputManager))
+                                                 .build_headless(())
+                                                 .expect("Could not open the Wayland socket");
+    // Announced to the output manager once the backend starts.
+    let output = compositor.backend_mut().add_headless_output(640, 480).unwrap();
+
+    let attempts = Rc::new(RefCell::new(None));
+    let _timer = {
+        let attempts = attempts.clone();
+        compositor.add_timer(Duration::from_millis(1), move || {
+                      *attempts.borrow_mut() = output.run(set_cursors).ok();
+                      wlroots::terminate()
+                  })
+    };
+    compositor.run();
+
+    let attempts = attempts.borrow_mut().take().expect("No cursor was set");
+    assert_eq!(attempts,
+               Attempts { small: (false, true),
+                          oversized: (false, true),
+                          hotspot_outside: (false, true),
+                          short_pixels: (false, true) });
+}
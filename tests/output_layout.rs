@@ -0,0 +1,103 @@
+//! Checks the geometry queries of an output layout whose outputs leave a gap
+//! between them.
+
+#[macro_use]
+extern crate wlroots;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wlroots::{Area, CompositorBuilder, CompositorHandle, Origin, OutputBuilder,
+              OutputBuilderResult, OutputHandle, OutputHandler, OutputLayout,
+              OutputLayoutError, OutputLayoutHandle, OutputLayoutHandler, OutputManagerHandler,
+              Size};
+
+struct Layout;
+
+impl OutputLayoutHandler for Layout {}
+
+struct Output;
+
+impl OutputHandler for Output {}
+
+/// Puts 640 pixel wide outputs at the origin and every other output at
+/// (1000, 0).
+struct OutputManager {
+    layout: OutputLayoutHandle
+}
+
+impl OutputManagerHandler for OutputManager {
+    fn output_added<'output>(&mut self,
+                             _: CompositorHandle,
+                             builder: OutputBuilder<'output>)
+                             -> Option<OutputBuilderResult<'output>> {
+        let result = builder.build_best_mode(Output);
+        handles!((self.layout, result.output)).run(|layout, output| {
+                                                       let origin = match output.size() {
+                                                           (640, _) => Origin::new(0, 0),
+                                                           _ => Origin::new(1000, 0)
+                                                       };
+                                                       layout.add(output, origin);
+                                                   })
+                                              .unwrap();
+        Some(result)
+    }
+}
+
+/// The answers of the layout, asked once both outputs are in it.
+#[derive(Debug, PartialEq)]
+struct Queries {
+    extents: Area,
+    in_gap: Option<OutputHandle>,
+    on_left: Option<OutputHandle>,
+    on_right: Option<OutputHandle>,
+    closest_in_gap: (f64, f64),
+    closest_on_right: (f64, f64),
+    right_coords: Result<(f64, f64), OutputLayoutError>,
+    dropped_coords: Result<(f64, f64), OutputLayoutError>
+}
+
+fn query(layout: &mut OutputLayout, right: &OutputHandle) -> Queries {
+    Queries { extents: layout.get_box(None),
+              in_gap: layout.output_at(800.0, 100.0),
+              on_left: layout.output_at(100.0, 100.0),
+              on_right: layout.output_at(1100.0, 100.0),
+              closest_in_gap: layout.closest_point(None, 900.0, 100.0),
+              closest_on_right: layout.closest_point(Some(right), 100.0, 100.0),
+              right_coords: layout.output_coords(right),
+              dropped_coords: layout.output_coords(&OutputHandle::default()) }
+}
+
+#[test]
+fn non_adjacent_outputs_leave_a_gap() {
+    let layout = OutputLayout::create(Box::new(Layout));
+    let output_manager = OutputManager { layout: layout.clone() };
+    let mut compositor = CompositorBuilder::new().output_manager(Box::new(output_manager))
+                                                 .build_headless(())
+                                                 .expect("Could not open the Wayland socket");
+    // Announced to the output manager once the backend starts.
+    let left = compositor.backend_mut().add_headless_output(640, 480).unwrap();
+    let right = compositor.backend_mut().add_headless_output(800, 600).unwrap();
+
+    let queries = Rc::new(RefCell::new(None));
+    let _timer = {
+        let (layout, right, queries) = (layout.clone(), right.clone(), queries.clone());
+        compositor.add_timer(Duration::from_millis(1), move || {
+                      *queries.borrow_mut() = layout.run(|layout| query(layout, &right)).ok();
+                      wlroots::terminate()
+                  })
+    };
+    compositor.run();
+
+    let queries = queries.borrow_mut().take().expect("The layout was never queried");
+    assert_eq!(queries,
+               Queries { extents: Area::new(Origin::new(0, 0), Size::new(1800, 600)),
+                         in_gap: None,
+                         on_left: Some(left),
+                         on_right: Some(right),
+                         // The left edge of the right output is nearer than
+                         // the right edge of the left output.
+                         closest_in_gap: (1000.0, 100.0),
+                         closest_on_right: (1000.0, 100.0),
+                         right_coords: Ok((1000.0, 0.0)),
+                         dropped_coords: Err(OutputLayoutError::OutputDropped) });
+}
@@ -0,0 +1,69 @@
+//! Checks that changing the mode of an output with the `_preserving` methods
+//! keeps its scale and transform.
+
+extern crate wlroots;
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use wlroots::{CompositorBuilder, CompositorHandle, ModeSetError, Orientation, Output,
+              OutputBuilder, OutputBuilderResult, OutputHandler, OutputManagerHandler, Size};
+
+struct ExOutput;
+
+impl OutputHandler for ExOutput {}
+
+struct OutputManager;
+
+impl OutputManagerHandler for OutputManager {
+    fn output_added<'output>(&mut self,
+                             _: CompositorHandle,
+                             builder: OutputBuilder<'output>)
+                             -> Option<OutputBuilderResult<'output>> {
+        Some(builder.build_best_mode(ExOutput))
+    }
+}
+
+/// The state of the output after the mode change.
+#[derive(Debug, PartialEq)]
+struct ModeChange {
+    result: Result<(), ModeSetError>,
+    size: (i32, i32),
+    scale: f32,
+    rotated: bool
+}
+
+fn change_mode(output: &mut Output) -> ModeChange {
+    output.set_scale(2.0);
+    output.set_orientation(Orientation::Rotate90);
+    let result = output.set_custom_mode_preserving(Size::new(800, 600), 0);
+    ModeChange { result,
+                 size: output.size(),
+                 scale: output.scale(),
+                 rotated: output.get_transform() == Orientation::Rotate90.into() }
+}
+
+#[test]
+fn mode_change_keeps_scale_and_transform() {
+    let mut compositor = CompositorBuilder::new().output_manager(Box::new(OutputManager))
+                                                 .build_headless(())
+                                                 .expect("Could not open the Wayland socket");
+    // Announced to the output manager once the backend starts.
+    let output = compositor.backend_mut().add_headless_output(640, 480).unwrap();
+
+    let change = Rc::new(RefCell::new(None));
+    let _timer = {
+        let change = change.clone();
+        compositor.add_timer(Duration::from_millis(1), move || {
+                      *change.borrow_mut() = output.run(change_mode).ok();
+                      wlroots::terminate()
+                  })
+    };
+    compositor.run();
+
+    let change = change.borrow_mut().take().expect("The mode was never changed");
+    assert_eq!(change,
+               ModeChange { result: Ok(()),
+                            size: (800, 600),
+                            scale: 2.0,
+                            rotated: true });
+}
@@ -0,0 +1,81 @@
+//! Checks that the guard returned by `Output::acquire` swaps the buffers of
+//! the output exactly once.
+
+extern crate wlroots;
+
+use std::{cell::{Cell, RefCell}, rc::Rc, time::Duration};
+
+use wlroots::{CompositorBuilder, CompositorHandle, Output, OutputBuilder, OutputBuilderResult,
+              OutputHandle, OutputHandler, OutputManagerHandler};
+
+struct ExOutput {
+    swaps: Rc<Cell<u32>>
+}
+
+impl OutputHandler for ExOutput {
+    fn on_buffers_swapped(&mut self, _: CompositorHandle, _: OutputHandle) {
+        self.swaps.set(self.swaps.get() + 1)
+    }
+}
+
+struct OutputManager {
+    swaps: Rc<Cell<u32>>
+}
+
+impl OutputManagerHandler for OutputManager {
+    fn output_added<'output>(&mut self,
+                             _: CompositorHandle,
+                             builder: OutputBuilder<'output>)
+                             -> Option<OutputBuilderResult<'output>> {
+        Some(builder.build_best_mode(ExOutput { swaps: self.swaps.clone() }))
+    }
+}
+
+/// The number of swaps after each step of rendering with the guard.
+#[derive(Debug, PartialEq)]
+struct Swaps {
+    while_acquired: u32,
+    after_drop: u32,
+    after_commit: u32
+}
+
+fn render(output: &mut Output, swaps: &Cell<u32>) -> Swaps {
+    let start = swaps.get();
+    let while_acquired = {
+        let _current = output.acquire().expect("Could not acquire the output");
+        swaps.get() - start
+    };
+    let after_drop = swaps.get() - start;
+    let current = output.acquire().expect("Could not acquire the output");
+    assert!(current.commit(None, None));
+    Swaps { while_acquired,
+            after_drop,
+            after_commit: swaps.get() - start }
+}
+
+#[test]
+fn the_guard_swaps_the_buffers_once() {
+    let swaps = Rc::new(Cell::new(0));
+    let output_manager = OutputManager { swaps: swaps.clone() };
+    let mut compositor = CompositorBuilder::new().output_manager(Box::new(output_manager))
+                                                 .build_headless(())
+                                                 .expect("Could not open the Wayland socket");
+    // Announced to the output manager once the backend starts.
+    let output = compositor.backend_mut().add_headless_output(640, 480).unwrap();
+
+    let result = Rc::new(RefCell::new(None));
+    let _timer = {
+        let result = result.clone();
+        compositor.add_timer(Duration::from_millis(1), move || {
+                      *result.borrow_mut() = output.run(|output| render(output, &swaps)).ok();
+                      wlroots::terminate()
+                  })
+    };
+    compositor.run();
+
+    let result = result.borrow_mut().take().expect("The output was never rendered to");
+    assert_eq!(result,
+               Swaps { while_acquired: 0,
+                       after_drop: 1,
+                       after_commit: 2 });
+}
@@ -0,0 +1,25 @@
+//! Checks that building a compositor reports a Wayland socket that can't be
+//! opened instead of panicking.
+
+extern crate wlroots;
+
+use wlroots::{CompositorBuilder, SocketError};
+
+#[test]
+fn a_name_with_a_nul_byte_is_invalid() {
+    let result = CompositorBuilder::new().socket_name("wlroots-rs\0socket-test".into())
+                                         .build_headless(());
+    assert_eq!(result.err(), Some(SocketError::InvalidName));
+}
+
+#[test]
+fn a_name_that_is_taken_can_not_be_opened() {
+    let name = "wlroots-rs-socket-test";
+    let first = CompositorBuilder::new().socket_name(name.into())
+                                        .build_headless(())
+                                        .expect("Could not open the Wayland socket");
+    let second = CompositorBuilder::new().socket_name(name.into())
+                                         .build_headless(());
+    assert_eq!(first.socket_name(), name);
+    assert_eq!(second.err(), Some(SocketError::CouldNotOpen));
+}
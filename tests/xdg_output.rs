@@ -0,0 +1,143 @@
+//! Checks that a client bound to `zxdg_output_manager_v1` is told the
+//! logical geometry of an output in the layout.
+#![cfg(feature = "xdg-output")]
+
+#[macro_use]
+extern crate wayland_client;
+extern crate wayland_protocols;
+#[macro_use]
+extern crate wlroots;
+
+use std::{env, thread, cell::{Cell, RefCell}, rc::Rc, sync::{mpsc, Arc, Mutex},
+          time::Duration};
+
+use wayland_client::EnvHandler;
+use wayland_client::protocol::wl_output;
+use wayland_protocols::unstable::xdg_output::v1::client::{zxdg_output_manager_v1,
+                                                          zxdg_output_v1};
+use wlroots::{CompositorBuilder, CompositorHandle, Origin, OutputBuilder, OutputBuilderResult,
+              OutputHandler, OutputLayout, OutputLayoutHandle, OutputLayoutHandler,
+              OutputManagerHandler, TimerHandle};
+
+/// How often the compositor checks if the client is done, in milliseconds.
+const POLL_MS: u64 = 10;
+/// How many times it checks before giving up on the client.
+const MAX_POLLS: u32 = 500;
+
+wayland_env!(Globals,
+             output: wl_output::WlOutput,
+             xdg_output_manager: zxdg_output_manager_v1::ZxdgOutputManagerV1);
+
+/// What the client was told about the output.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Geometry {
+    position: Option<(i32, i32)>,
+    size: Option<(i32, i32)>
+}
+
+struct Layout;
+
+impl OutputLayoutHandler for Layout {}
+
+struct Output;
+
+impl OutputHandler for Output {}
+
+/// Puts every output at (100, 50) in the layout with a scale of 2.
+struct OutputManager {
+    layout: OutputLayoutHandle
+}
+
+impl OutputManagerHandler for OutputManager {
+    fn output_added<'output>(&mut self,
+                             _: CompositorHandle,
+                             builder: OutputBuilder<'output>)
+                             -> Option<OutputBuilderResult<'output>> {
+        let result = builder.build_best_mode(Output);
+        handles!((self.layout, result.output)).run(|layout, output| {
+                                                       output.set_scale(2.0);
+                                                       layout.add(output, Origin::new(100, 50));
+                                                   })
+                                              .unwrap();
+        Some(result)
+    }
+}
+
+fn xdg_output_implementation() -> zxdg_output_v1::Implementation<Arc<Mutex<Geometry>>> {
+    zxdg_output_v1::Implementation { logical_position: |_, geometry, _, x, y| {
+                                         geometry.lock().unwrap().position = Some((x, y))
+                                     },
+                                     logical_size: |_, geometry, _, width, height| {
+                                         geometry.lock().unwrap().size = Some((width, height))
+                                     },
+                                     done: |_, _, _| {} }
+}
+
+/// Connect to the compositor and ask for the logical geometry of its output.
+fn query_xdg_output() -> Geometry {
+    let (display, mut event_queue) =
+        wayland_client::default_connect().expect("Could not connect to the compositor");
+    let registry = display.get_registry();
+    let globals = EnvHandler::<Globals>::init(&mut event_queue, &registry);
+    event_queue.sync_roundtrip().unwrap();
+    let xdg_output = {
+        let state = event_queue.state();
+        let globals = state.get(&globals);
+        globals.xdg_output_manager
+               .get_xdg_output(&globals.output)
+               .expect("xdg_output manager was destroyed")
+    };
+    let geometry = Arc::new(Mutex::new(Geometry::default()));
+    event_queue.register(&xdg_output, xdg_output_implementation(), geometry.clone());
+    event_queue.sync_roundtrip().unwrap();
+    let geometry = *geometry.lock().unwrap();
+    geometry
+}
+
+#[test]
+fn client_receives_the_logical_geometry() {
+    let layout = OutputLayout::create(Box::new(Layout));
+    let output_manager = OutputManager { layout: layout.clone() };
+    let mut compositor =
+        CompositorBuilder::new().output_manager(Box::new(output_manager))
+                                .xdg_output_manager(layout.clone())
+                                .socket_name("wlroots-rs-xdg-output-test".into())
+                                .build_headless(())
+                                .expect("Could not open the Wayland socket");
+    // Announced to the output manager once the backend starts.
+    compositor.backend_mut().add_headless_output(640, 480);
+
+    env::set_var("WAYLAND_DISPLAY", compositor.socket_name());
+    let (sender, receiver) = mpsc::channel();
+    let client = thread::spawn(move || sender.send(query_xdg_output()).unwrap());
+
+    // Poll for the result of the client from the event loop, which has to
+    // keep running for the client to get its answers.
+    let result = Rc::new(Cell::new(None));
+    let timer: Rc<RefCell<Option<TimerHandle>>> = Rc::new(RefCell::new(None));
+    let handle = {
+        let (result, timer) = (result.clone(), timer.clone());
+        let mut polls = 0;
+        compositor.add_timer(Duration::from_millis(POLL_MS), move || match receiver.try_recv() {
+            Ok(geometry) => {
+                result.set(Some(geometry));
+                wlroots::terminate()
+            }
+            Err(_) if polls == MAX_POLLS => wlroots::terminate(),
+            Err(_) => {
+                polls += 1;
+                if let Some(ref timer) = *timer.borrow() {
+                    timer.reset(Duration::from_millis(POLL_MS)).unwrap()
+                }
+            }
+        })
+    };
+    *timer.borrow_mut() = Some(handle);
+    compositor.run();
+    client.join().unwrap();
+
+    // The logical size is the mode divided by the scale.
+    assert_eq!(result.get(),
+               Some(Geometry { position: Some((100, 50)),
+                               size: Some((320, 240)) }));
+}
@@ -8,6 +8,15 @@ use std::{env, io, fs};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Optional includes in `src/wlroots.h`, which need a newer wlroots than the
+/// rest of the header.
+///
+/// Each one is included if the cargo feature with the same name (in lower
+/// case, with dashes) is enabled.
+const OPTIONAL_HEADERS: &[&str] = &["VIRTUAL_POINTER", "OUTPUT_MANAGEMENT", "PRESENTATION_TIME",
+                                    "RELATIVE_POINTER", "TEXT_INPUT", "XDG_OUTPUT",
+                                    "FOREIGN_TOPLEVEL"];
+
 fn main() {
     meson();
     let protocol_header_path =
@@ -21,9 +30,11 @@ fn main() {
         .whitelisted_type(r"^wlr_.*$")
         .whitelisted_type(r"^xkb_.*$")
         .whitelisted_type(r"^XKB_.*$")
+        .whitelisted_type(r"^libinput_.*$")
         .whitelisted_function(r"^_?pixman_.*$")
         .whitelisted_function(r"^_?wlr_.*$")
         .whitelisted_function(r"^xkb_.*$")
+        .whitelisted_function(r"^libinput_device_config_.*$")
         .ctypes_prefix("libc")
         .clang_arg("-Iwlroots/include")
         .clang_arg("-Iwlroots/include/wlr")
@@ -43,6 +54,11 @@ fn main() {
     if cfg!(feature = "unstable-features") {
         builder = builder.clang_arg("-DWLR_USE_UNSTABLE");
     }
+    for header in OPTIONAL_HEADERS {
+        if env::var_os(format!("CARGO_FEATURE_{}", header)).is_some() {
+            builder = builder.clang_arg(format!("-DWLR_RS_{}", header));
+        }
+    }
     let generated = builder.generate().unwrap();
 
     println!("cargo:rustc-link-lib=dylib=X11");
@@ -138,26 +154,35 @@ fn generate_protocol_headers() -> io::Result<PathBuf> {
     for entry in protocols {
         let entry = entry?;
         for entry in fs::read_dir(entry.path())? {
-            let entry = entry?;
-            let path = entry.path();
-            let mut filename = entry.file_name().into_string().unwrap();
-            if filename.ends_with(".xml") {
-                let new_length = filename.len() - 4;
-                filename.truncate(new_length);
-            }
-            filename.push_str("-protocol");
-            Command::new("wayland-scanner").arg("server-header")
-                                           .arg(path.clone())
-                                           .arg(format!("{}/{}.h",
-                                                        out_path.to_str().unwrap(),
-                                                        filename))
-                                           .status()
-                                           .unwrap();
+            generate_protocol_header(&entry?.path(), &out_path);
+        }
+    }
+    // wlroots ships some protocols of its own (e.g layer shell) that
+    // its headers depend on.
+    for entry in fs::read_dir("wlroots/protocol")? {
+        let path = entry?.path();
+        if path.extension().map(|ext| ext == "xml").unwrap_or(false) {
+            generate_protocol_header(&path, &out_path);
         }
     }
     Ok(out_path)
 }
 
+/// Generates the server header for the protocol at `path` in `out_path`.
+fn generate_protocol_header(path: &Path, out_path: &Path) {
+    let mut filename = path.file_name().unwrap().to_str().unwrap().to_string();
+    if filename.ends_with(".xml") {
+        let new_length = filename.len() - 4;
+        filename.truncate(new_length);
+    }
+    filename.push_str("-protocol");
+    Command::new("wayland-scanner").arg("server-header")
+                                   .arg(path)
+                                   .arg(format!("{}/{}.h", out_path.to_str().unwrap(), filename))
+                                   .status()
+                                   .unwrap();
+}
+
 fn generate_protocols() {
     let output_dir_str = env::var("OUT_DIR").unwrap();
 
@@ -46,4 +46,74 @@ impl wl_output_transform {
     pub fn compose(self, other: Self) -> Self {
         unsafe { wlr_output_transform_compose(self, other) }
     }
+
+    /// Returns the transform that rotates counter-clockwise by `degrees`,
+    /// flipped around the vertical axis first if `flipped` is set.
+    ///
+    /// Returns `None` if `degrees` isn't a multiple of 90. Negative angles
+    /// rotate clockwise.
+    pub fn from_degrees(degrees: i32, flipped: bool) -> Option<Self> {
+        use self::wl_output_transform::*;
+        if degrees % 90 != 0 {
+            return None
+        }
+        let transform = match (((degrees % 360) + 360) % 360, flipped) {
+            (0, false) => WL_OUTPUT_TRANSFORM_NORMAL,
+            (90, false) => WL_OUTPUT_TRANSFORM_90,
+            (180, false) => WL_OUTPUT_TRANSFORM_180,
+            (_, false) => WL_OUTPUT_TRANSFORM_270,
+            (0, true) => WL_OUTPUT_TRANSFORM_FLIPPED,
+            (90, true) => WL_OUTPUT_TRANSFORM_FLIPPED_90,
+            (180, true) => WL_OUTPUT_TRANSFORM_FLIPPED_180,
+            (_, true) => WL_OUTPUT_TRANSFORM_FLIPPED_270
+        };
+        Some(transform)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wl_output_transform::{self, *};
+
+    #[test]
+    fn from_degrees_covers_all_transforms() {
+        let table = [(0, false, WL_OUTPUT_TRANSFORM_NORMAL),
+                     (90, false, WL_OUTPUT_TRANSFORM_90),
+                     (180, false, WL_OUTPUT_TRANSFORM_180),
+                     (270, false, WL_OUTPUT_TRANSFORM_270),
+                     (0, true, WL_OUTPUT_TRANSFORM_FLIPPED),
+                     (90, true, WL_OUTPUT_TRANSFORM_FLIPPED_90),
+                     (180, true, WL_OUTPUT_TRANSFORM_FLIPPED_180),
+                     (270, true, WL_OUTPUT_TRANSFORM_FLIPPED_270)];
+        for &(degrees, flipped, transform) in table.iter() {
+            assert_eq!(wl_output_transform::from_degrees(degrees, flipped),
+                       Some(transform),
+                       "{} degrees, flipped: {}",
+                       degrees,
+                       flipped);
+            // Whole turns in either direction end up at the same transform.
+            assert_eq!(wl_output_transform::from_degrees(degrees + 360, flipped),
+                       Some(transform));
+            assert_eq!(wl_output_transform::from_degrees(degrees - 720, flipped),
+                       Some(transform));
+        }
+    }
+
+    #[test]
+    fn from_degrees_negative_angles_rotate_clockwise() {
+        assert_eq!(wl_output_transform::from_degrees(-90, false),
+                   Some(WL_OUTPUT_TRANSFORM_270));
+        assert_eq!(wl_output_transform::from_degrees(-180, true),
+                   Some(WL_OUTPUT_TRANSFORM_FLIPPED_180));
+        assert_eq!(wl_output_transform::from_degrees(-270, true),
+                   Some(WL_OUTPUT_TRANSFORM_FLIPPED_90));
+    }
+
+    #[test]
+    fn from_degrees_rejects_other_angles() {
+        for &degrees in [1, 45, 89, 91, -45, 359].iter() {
+            assert_eq!(wl_output_transform::from_degrees(degrees, false), None);
+            assert_eq!(wl_output_transform::from_degrees(degrees, true), None);
+        }
+    }
 }
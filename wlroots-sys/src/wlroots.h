@@ -25,24 +25,57 @@
 #include <wlr/types/wlr_cursor.h>
 #include <wlr/types/wlr_data_device.h>
 #include <wlr/types/wlr_gamma_control.h>
+#include <wlr/types/wlr_idle.h>
+#include <wlr/types/wlr_idle_inhibit_v1.h>
 #include <wlr/types/wlr_input_device.h>
 #include <wlr/types/wlr_keyboard.h>
+#include <wlr/types/wlr_layer_shell.h>
 #include <wlr/types/wlr_output.h>
 #include <wlr/types/wlr_output_layout.h>
 #include <wlr/types/wlr_output_damage.h>
 #include <wlr/types/wlr_pointer.h>
+#include <wlr/types/wlr_pointer_constraints_v1.h>
+#include <wlr/types/wlr_primary_selection.h>
 #include <wlr/types/wlr_region.h>
 #include <wlr/types/wlr_server_decoration.h>
 #include <wlr/types/wlr_screenshooter.h>
 #include <wlr/types/wlr_seat.h>
 #include <wlr/types/wlr_surface.h>
+#include <wlr/types/wlr_switch.h>
 #include <wlr/types/wlr_tablet_pad.h>
 #include <wlr/types/wlr_tablet_tool.h>
 #include <wlr/types/wlr_touch.h>
+#include <wlr/types/wlr_virtual_keyboard_v1.h>
 #include <wlr/types/wlr_wl_shell.h>
 #include <wlr/types/wlr_xdg_shell_v6.h>
 #include <wlr/types/wlr_xdg_shell.h>
 #include <wlr/types/wlr_xcursor_manager.h>
+#include <wlr/types/wlr_xdg_decoration_v1.h>
+
+/// Optional type includes, these need a newer wlroots than the rest.
+/// Each is enabled by the cargo feature of the same name, see build.rs.
+#ifdef WLR_RS_VIRTUAL_POINTER
+#include <wlr/types/wlr_virtual_pointer_v1.h>
+#endif
+#ifdef WLR_RS_OUTPUT_MANAGEMENT
+#include <wlr/types/wlr_output_management_v1.h>
+#endif
+#ifdef WLR_RS_PRESENTATION_TIME
+#include <wlr/types/wlr_presentation_time.h>
+#endif
+#ifdef WLR_RS_RELATIVE_POINTER
+#include <wlr/types/wlr_relative_pointer_v1.h>
+#endif
+#ifdef WLR_RS_TEXT_INPUT
+#include <wlr/types/wlr_input_method_v2.h>
+#include <wlr/types/wlr_text_input_v3.h>
+#endif
+#ifdef WLR_RS_XDG_OUTPUT
+#include <wlr/types/wlr_xdg_output.h>
+#endif
+#ifdef WLR_RS_FOREIGN_TOPLEVEL
+#include <wlr/types/wlr_foreign_toplevel_management_v1.h>
+#endif
 
 /// Util includes
 #include <wlr/util/log.h>